@@ -3,17 +3,31 @@ package dev.fruit.androiddemo
 import android.annotation.SuppressLint
 import android.content.Context
 import android.content.Intent
+import android.content.res.Configuration
+import android.graphics.Bitmap
+import android.graphics.BitmapFactory
 import android.graphics.Color
 import android.os.*
 import android.support.v7.app.AppCompatActivity
 import android.util.Log
-import android.view.Gravity
+import android.util.TypedValue
 import android.view.View
 import android.view.ViewGroup
+import android.text.Editable
+import android.text.InputFilter
+import android.text.InputType
+import android.text.TextWatcher
 import android.widget.Button
+import android.widget.CheckBox
+import android.widget.CompoundButton
+import android.widget.EditText
+import android.widget.ImageView
+import android.widget.RadioButton
+import android.widget.RadioGroup
+import android.widget.ScrollView
+import android.widget.Switch
 import android.widget.LinearLayout
 import android.widget.TextView
-import com.jawnnypoo.physicslayout.PhysicsLinearLayout
 import org.jetbrains.anko.Orientation
 import org.jetbrains.anko.doAsync
 import org.jetbrains.anko.uiThread
@@ -34,22 +48,86 @@ class AppState(val context: Context, val rootViewGroup: ViewGroup) {
     val platform: Long? = null
 }
 
+// Must match the codes in the prop_enum! declarations in src/style/mod.rs
+object PropEnums {
+    const val ORIENTATION_VERTICAL = 0
+    const val ORIENTATION_HORIZONTAL = 1
+
+    const val FLING_DISABLED = 0
+    const val FLING_ENABLED = 1
+
+    const val KEYBOARD_TYPE_TEXT = 0
+    const val KEYBOARD_TYPE_NUMBER = 1
+    const val KEYBOARD_TYPE_EMAIL = 2
+    const val KEYBOARD_TYPE_PHONE = 3
+
+    const val SCALE_MODE_FIT = 0
+    const val SCALE_MODE_FILL = 1
+    const val SCALE_MODE_CROP = 2
+    const val SCALE_MODE_CENTER = 3
+
+    const val EVENT_PRESS = 0
+    const val EVENT_LONG_PRESS = 1
+    const val EVENT_TEXT_CHANGED = 2
+    const val EVENT_VALUE_CHANGED = 3
+    const val EVENT_TOGGLED = 4
+    const val EVENT_SELECTED = 5
+    const val EVENT_FAILED = 6
+
+    const val ENTER_TRANSITION_NONE = 0
+    const val ENTER_TRANSITION_SLIDE_FROM_RIGHT = 1
+    const val ENTER_TRANSITION_SLIDE_FROM_LEFT = 2
+    const val ENTER_TRANSITION_FADE = 3
+
+    const val LIFECYCLE_CREATED = 0
+    const val LIFECYCLE_RESUMED = 1
+    const val LIFECYCLE_PAUSED = 2
+    const val LIFECYCLE_DESTROYED = 3
+}
+
+// The router sets this on its container right before composing the new top route
+fun applyEnterTransition(view: View, transition: Int) {
+    view.animate().cancel()
+    when (transition) {
+        PropEnums.ENTER_TRANSITION_SLIDE_FROM_RIGHT -> {
+            view.translationX = view.width.toFloat()
+            view.animate().translationX(0f)
+        }
+        PropEnums.ENTER_TRANSITION_SLIDE_FROM_LEFT -> {
+            view.translationX = -view.width.toFloat()
+            view.animate().translationX(0f)
+        }
+        PropEnums.ENTER_TRANSITION_FADE -> {
+            view.alpha = 0f
+            view.animate().alpha(1f)
+        }
+    }
+}
+
 interface WiredPlatformView {
-    fun updateProp(k: String, v: Any)
-    fun updateProp(k: String, v: Float)
-    fun updateProp(k: String, v: RustCallback)
-    fun updateProp(k: String, v: String)
+    fun updateProp(k: Int, v: Any)
+    fun updateProp(k: Int, v: Float)
+    fun updateProp(k: Int, v: Int) {}
+    fun updateProp(k: Int, v: Long) {}
+    fun updateProp(k: Int, v: Double) {}
+    fun updateProp(k: Int, v: Boolean) {}
+    fun updateProp(k: Int, v: ByteArray) {}
+    fun updateProp(k: Int, v: Array<String>) {}
+    fun updateProp(k: Int, v: RustCallback)
+    fun updateProp(k: Int, v: String)
+    // The view that goes into the hierarchy, wrappers like WiredAnyView aren't views themselves
+    fun asView(): View = this as View
     fun appendChild(child: WiredPlatformView) {
-        addView(child as View)
+        addView(child.asView())
     }
     fun removeChild(child: WiredPlatformView) {
-        removeView(child as View)
+        removeView(child.asView())
     }
     fun removeChildIndex(idx: Int) {
         removeViewAt(idx)
     }
     fun insertChildAt(child: WiredPlatformView, idx: Int) {
-        addView(child as View, idx)
+        addView(child.asView(), idx)
     }
     fun addView(child: View)
     fun addView(child: View, idx: Int)
@@ -57,40 +135,71 @@ interface WiredPlatformView {
     fun removeViewAt(idx: Int)
 }
 
+// Margins live on the layout params, so make sure they are MarginLayoutParams before touching them.
+// LinearLayout keeps the margins when it converts these on addView.
+fun applyMargin(view: View, k: Int, v: Int) {
+    val params = view.layoutParams as? ViewGroup.MarginLayoutParams
+        ?: ViewGroup.MarginLayoutParams(
+            view.layoutParams ?: ViewGroup.LayoutParams(
+                ViewGroup.LayoutParams.WRAP_CONTENT,
+                ViewGroup.LayoutParams.WRAP_CONTENT
+            )
+        )
+    when (k) {
+        PropKeys.MARGIN_LEFT -> params.leftMargin = v
+        PropKeys.MARGIN_TOP -> params.topMargin = v
+        PropKeys.MARGIN_RIGHT -> params.rightMargin = v
+        PropKeys.MARGIN_BOTTOM -> params.bottomMargin = v
+    }
+    view.layoutParams = params
+}
+
+fun applyPadding(view: View, k: Int, v: Int) {
+    when (k) {
+        PropKeys.PAD_LEFT -> view.setPadding(v, view.paddingTop, view.paddingRight, view.paddingBottom)
+        PropKeys.PAD_TOP -> view.setPadding(view.paddingLeft, v, view.paddingRight, view.paddingBottom)
+        PropKeys.PAD_RIGHT -> view.setPadding(view.paddingLeft, view.paddingTop, v, view.paddingBottom)
+        PropKeys.PAD_BOTTOM -> view.setPadding(view.paddingLeft, view.paddingTop, view.paddingRight, v)
+    }
+}
+
 interface WiredBaseView {
     fun setPadding(left: Int, top: Int, right: Int, bottom: Int)
     fun setTextSize(size: Float)
+    fun setTextSize(unit: Int, size: Float)
     fun getPaddingTop(): Int
     fun getPaddingRight(): Int
     fun getPaddingLeft(): Int
     fun getPaddingBottom(): Int
     fun setX(x: Float)
     fun setY(y: Float)
-    fun updateProp(k: String, v: Float) {
+    fun updateProp(k: Int, v: Float) {
         when (k) {
-            "text_size" ->  setTextSize(v)
-            "pad_left" -> setPadding(v.toInt(), getPaddingTop(), getPaddingRight(), getPaddingBottom())
-            "pad_top" -> setPadding(getPaddingLeft(), v.toInt(), getPaddingRight(), getPaddingBottom())
-            "pad_right" -> setPadding(getPaddingLeft(), getPaddingTop(), v.toInt(), getPaddingBottom())
-            "pad_bottom" -> setPadding(getPaddingLeft(), getPaddingTop(), getPaddingRight(), v.toInt())
-            "set_x" -> setX(v)
-            "set_y" -> setY(v)
+            PropKeys.TEXT_SIZE ->  setTextSize(TypedValue.COMPLEX_UNIT_PX, v)
+            PropKeys.PAD_LEFT -> setPadding(v.toInt(), getPaddingTop(), getPaddingRight(), getPaddingBottom())
+            PropKeys.PAD_TOP -> setPadding(getPaddingLeft(), v.toInt(), getPaddingRight(), getPaddingBottom())
+            PropKeys.PAD_RIGHT -> setPadding(getPaddingLeft(), getPaddingTop(), v.toInt(), getPaddingBottom())
+            PropKeys.PAD_BOTTOM -> setPadding(getPaddingLeft(), getPaddingTop(), getPaddingRight(), v.toInt())
+            PropKeys.SET_X -> setX(v)
+            PropKeys.SET_Y -> setY(v)
+            PropKeys.MARGIN_LEFT, PropKeys.MARGIN_TOP, PropKeys.MARGIN_RIGHT, PropKeys.MARGIN_BOTTOM ->
+                applyMargin(this as View, k, v.toInt())
         }
     }
 }
 
 class WiredTextView(val mContext: Context): TextView(mContext), WiredBaseView, WiredPlatformView {
-    override fun updateProp(k: String, v: String) {
+    override fun updateProp(k: Int, v: String) {
         when (k) {
-            "text" ->  text = v as String
+            PropKeys.TEXT ->  text = v as String
         }
     }
 
-    override fun updateProp(k: String, v: Float) {
+    override fun updateProp(k: Int, v: Float) {
         super.updateProp(k, v)
     }
 
-    override fun updateProp(k: String, v: RustCallback) {
+    override fun updateProp(k: Int, v: RustCallback) {
         TODO("not implemented") //To change body of created functions use File | Settings | File Templates.
     }
 
@@ -110,105 +219,544 @@ class WiredTextView(val mContext: Context): TextView(mContext), WiredBaseView, W
         throw Error("Undefined")
     }
 
-    override fun updateProp(k: String, v: Any) {
+    override fun updateProp(k: Int, v: Any) {
     }
 }
 
-class WiredLinearLayout(val mContext: Context): LinearLayout(mContext), WiredPlatformView {
-    override fun updateProp(k: String, v: String) {
+// Every event goes through dispatch, kind (one of the EVENT_ codes) says which fields Rust reads.
+// Rust frees its side when the prop is replaced or the view is dropped, and zeroes ptr.
+class RustCallback {
+    @Volatile var ptr: Long = 0
+    external fun dispatch(rustCallback: RustCallback, kind: Int, x: Float, y: Float, n: Long, text: String?)
+
+    private fun send(kind: Int, x: Float = 0f, y: Float = 0f, n: Long = 0, text: String? = null) {
+        if (ptr != 0L) {
+            dispatch(this, kind, x, y, n, text)
+        }
+    }
+
+    fun press(x: Float = 0f, y: Float = 0f) = send(PropEnums.EVENT_PRESS, x, y)
+    fun longPress() = send(PropEnums.EVENT_LONG_PRESS)
+    fun textChanged(text: String) = send(PropEnums.EVENT_TEXT_CHANGED, text = text)
+    fun valueChanged(value: Float) = send(PropEnums.EVENT_VALUE_CHANGED, x = value)
+    fun toggled(checked: Boolean) = send(PropEnums.EVENT_TOGGLED, n = if (checked) 1 else 0)
+    fun selected(idx: Int) = send(PropEnums.EVENT_SELECTED, n = idx.toLong())
+    fun failed(reason: String) = send(PropEnums.EVENT_FAILED, text = reason)
+}
+
+// Shared by the checkbox and the switch
+fun updateToggleProp(view: CompoundButton, k: Int, v: Boolean) {
+    when (k) {
+        PropKeys.CHECKED -> if (view.isChecked != v) view.isChecked = v
+    }
+}
+
+fun updateToggleProp(view: CompoundButton, k: Int, v: RustCallback) {
+    when (k) {
+        PropKeys.ON_CHANGE -> view.setOnCheckedChangeListener { _, isChecked -> v.toggled(isChecked) }
+    }
+}
+
+class WiredCheckbox(mContext: Context): CheckBox(mContext), WiredBaseView, WiredPlatformView {
+    override fun updateProp(k: Int, v: String) {
         when (k) {
-            "orientation" ->  when(v as String) {
-                "Vertical" -> orientation = LinearLayout.VERTICAL
-                "Horizontal" -> orientation = LinearLayout.HORIZONTAL
+            PropKeys.TEXT -> text = v
+        }
+    }
+
+    override fun updateProp(k: Int, v: Boolean) = updateToggleProp(this, k, v)
+    override fun updateProp(k: Int, v: RustCallback) = updateToggleProp(this, k, v)
+
+    override fun updateProp(k: Int, v: Any) {
+    }
+
+    override fun updateProp(k: Int, v: Float) {
+        super.updateProp(k, v)
+    }
+
+    override fun addView(child: View) {
+        throw Error("Undefined")
+    }
+
+    override fun addView(child: View, idx: Int) {
+        throw Error("Undefined")
+    }
+
+    override fun removeView(child: View) {
+        throw Error("Undefined")
+    }
+
+    override fun removeViewAt(idx: Int) {
+        throw Error("Undefined")
+    }
+}
+
+class WiredSwitch(mContext: Context): Switch(mContext), WiredBaseView, WiredPlatformView {
+    override fun updateProp(k: Int, v: String) {
+        when (k) {
+            PropKeys.TEXT -> text = v
+        }
+    }
+
+    override fun updateProp(k: Int, v: Boolean) = updateToggleProp(this, k, v)
+    override fun updateProp(k: Int, v: RustCallback) = updateToggleProp(this, k, v)
+
+    override fun updateProp(k: Int, v: Any) {
+    }
+
+    override fun updateProp(k: Int, v: Float) {
+        super.updateProp(k, v)
+    }
+
+    override fun addView(child: View) {
+        throw Error("Undefined")
+    }
+
+    override fun addView(child: View, idx: Int) {
+        throw Error("Undefined")
+    }
+
+    override fun removeView(child: View) {
+        throw Error("Undefined")
+    }
+
+    override fun removeViewAt(idx: Int) {
+        throw Error("Undefined")
+    }
+}
+
+// Rust only deals in the option index, we map those to the generated RadioButton ids
+class WiredRadioGroup(val mContext: Context): RadioGroup(mContext), WiredPlatformView {
+    private var optionIds = listOf<Int>()
+
+    override fun updateProp(k: Int, v: Array<String>) {
+        when (k) {
+            PropKeys.OPTIONS -> {
+                removeAllViews()
+                optionIds = v.map { label ->
+                    val button = RadioButton(mContext)
+                    button.id = View.generateViewId()
+                    button.text = label
+                    addView(button)
+                    button.id
+                }
             }
         }
     }
 
-    override fun updateProp(k: String, v: RustCallback) {
-        TODO("not implemented") //To change body of created functions use File | Settings | File Templates.
+    override fun updateProp(k: Int, v: Int) {
+        when (k) {
+            PropKeys.SELECTED -> {
+                val id = optionIds.getOrNull(v)
+                if (id == null) {
+                    clearCheck()
+                } else if (checkedRadioButtonId != id) {
+                    check(id)
+                }
+            }
+        }
     }
 
-    override fun updateProp(k: String, v: Float) {
+    override fun updateProp(k: Int, v: RustCallback) {
         when (k) {
-            "height" -> layoutParams = ViewGroup.LayoutParams(layoutParams.width, v.toInt())
-            "width" -> layoutParams = ViewGroup.LayoutParams(v.toInt(), layoutParams.height)
-            "set_x" -> x = v
-            "set_y" -> y = v
+            PropKeys.ON_CHANGE -> setOnCheckedChangeListener { _, checkedId ->
+                val idx = optionIds.indexOf(checkedId)
+                if (idx >= 0) {
+                    v.selected(idx)
+                }
+            }
         }
     }
 
-    override fun updateProp(k: String, v: Any) {
-        TODO("not implemented") //To change body of created functions use File | Settings | File Templates.
+    override fun updateProp(k: Int, v: String) {
+    }
+
+    override fun updateProp(k: Int, v: Float) {
+    }
+
+    override fun updateProp(k: Int, v: Any) {
     }
 }
 
-class WiredPhysicsLayout(val mContext: Context): PhysicsLinearLayout(mContext), WiredPlatformView {
-    override fun updateProp(k: String, v: String) {
+class WiredTextInput(mContext: Context): EditText(mContext), WiredBaseView, WiredPlatformView {
+    private var keyboardType = InputType.TYPE_CLASS_TEXT
+    private var secure = false
+    // Kept so a new callback can replace it instead of adding a second watcher
+    private var onChange: TextWatcher? = null
+
+    private fun updateInputType() {
+        inputType = when {
+            secure && keyboardType == InputType.TYPE_CLASS_NUMBER ->
+                keyboardType or InputType.TYPE_NUMBER_VARIATION_PASSWORD
+            secure -> InputType.TYPE_CLASS_TEXT or InputType.TYPE_TEXT_VARIATION_PASSWORD
+            else -> keyboardType
+        }
+    }
+
+    override fun updateProp(k: Int, v: String) {
+        when (k) {
+            // Rust already skips values that came from us, this also covers the initial value
+            PropKeys.VALUE -> if (text.toString() != v) {
+                setText(v)
+                setSelection(v.length)
+            }
+            PropKeys.PLACEHOLDER -> hint = v
+        }
+    }
+
+    override fun updateProp(k: Int, v: Int) {
         when (k) {
-            "orientation" ->  when(v as String) {
-                "Vertical" -> orientation = LinearLayout.VERTICAL
-                "Horizontal" -> orientation = LinearLayout.HORIZONTAL
+            PropKeys.KEYBOARD_TYPE -> {
+                keyboardType = when (v) {
+                    PropEnums.KEYBOARD_TYPE_NUMBER -> InputType.TYPE_CLASS_NUMBER
+                    PropEnums.KEYBOARD_TYPE_EMAIL ->
+                        InputType.TYPE_CLASS_TEXT or InputType.TYPE_TEXT_VARIATION_EMAIL_ADDRESS
+                    PropEnums.KEYBOARD_TYPE_PHONE -> InputType.TYPE_CLASS_PHONE
+                    else -> InputType.TYPE_CLASS_TEXT
+                }
+                updateInputType()
             }
-            "fling" ->  when(v as String) {
-                "on" -> {
-                    physics.enableFling()
-                    physics.enablePhysics()
+            PropKeys.MAX_LENGTH -> filters = arrayOf(InputFilter.LengthFilter(v))
+        }
+    }
+
+    override fun updateProp(k: Int, v: Boolean) {
+        when (k) {
+            PropKeys.SECURE -> {
+                secure = v
+                updateInputType()
+            }
+        }
+    }
+
+    override fun updateProp(k: Int, v: RustCallback) {
+        when (k) {
+            PropKeys.ON_CHANGE -> {
+                onChange?.let { removeTextChangedListener(it) }
+                val watcher = object : TextWatcher {
+                    override fun afterTextChanged(s: Editable?) {
+                        v.textChanged(s.toString())
+                    }
+
+                    override fun beforeTextChanged(s: CharSequence?, start: Int, count: Int, after: Int) {}
+                    override fun onTextChanged(s: CharSequence?, start: Int, before: Int, count: Int) {}
                 }
-                "off" -> orientation = LinearLayout.HORIZONTAL
+                addTextChangedListener(watcher)
+                onChange = watcher
             }
         }
     }
 
+    override fun updateProp(k: Int, v: Any) {
+    }
+
+    override fun updateProp(k: Int, v: Float) {
+        super.updateProp(k, v)
+    }
 
-    override fun updateProp(k: String, v: RustCallback) {
-        TODO("not implemented") //To change body of created functions use File | Settings | File Templates.
+    override fun addView(child: View) {
+        throw Error("Undefined")
+    }
+
+    override fun addView(child: View, idx: Int) {
+        throw Error("Undefined")
+    }
+
+    override fun removeView(child: View) {
+        throw Error("Undefined")
+    }
+
+    override fun removeViewAt(idx: Int) {
+        throw Error("Undefined")
+    }
+}
+
+class WiredImage(val mContext: Context): ImageView(mContext), WiredPlatformView {
+    private var onLoad: RustCallback? = null
+    private var onError: RustCallback? = null
+    private var loaded = false
+
+    private fun showBitmap(bitmap: Bitmap?, source: String) {
+        if (bitmap == null) {
+            onError?.failed("Couldn't decode image from $source")
+            return
+        }
+        loaded = true
+        setImageBitmap(bitmap)
+        onLoad?.press()
     }
 
-    override fun updateProp(k: String, v: Float) {
+    private fun drawableId(name: String): Int =
+        mContext.resources.getIdentifier(name, "drawable", mContext.packageName)
+
+    override fun updateProp(k: Int, v: ByteArray) {
         when (k) {
-            "height" -> layoutParams = ViewGroup.LayoutParams(layoutParams.width, v.toInt())
-            "width" -> layoutParams = ViewGroup.LayoutParams(v.toInt(), layoutParams.height)
-            "set_x" -> x = v
-            "set_y" -> y = v
+            PropKeys.SOURCE_BYTES -> showBitmap(BitmapFactory.decodeByteArray(v, 0, v.size), "bytes")
         }
     }
 
-    override fun updateProp(k: String, v: Any) {
-        TODO("not implemented") //To change body of created functions use File | Settings | File Templates.
+    override fun updateProp(k: Int, v: String) {
+        when (k) {
+            PropKeys.SOURCE_FILE -> showBitmap(BitmapFactory.decodeFile(v), v)
+            PropKeys.SOURCE_RESOURCE -> {
+                val id = drawableId(v)
+                if (id == 0) {
+                    onError?.failed("No drawable named $v")
+                } else {
+                    loaded = true
+                    setImageResource(id)
+                    onLoad?.press()
+                }
+            }
+            PropKeys.PLACEHOLDER -> {
+                val id = drawableId(v)
+                if (id != 0 && !loaded) {
+                    setImageResource(id)
+                }
+            }
+        }
+    }
+
+    override fun updateProp(k: Int, v: Int) {
+        when (k) {
+            PropKeys.SCALE_MODE -> scaleType = when (v) {
+                PropEnums.SCALE_MODE_FILL -> ScaleType.FIT_XY
+                PropEnums.SCALE_MODE_CROP -> ScaleType.CENTER_CROP
+                PropEnums.SCALE_MODE_CENTER -> ScaleType.CENTER
+                else -> ScaleType.FIT_CENTER
+            }
+        }
+    }
+
+    override fun updateProp(k: Int, v: RustCallback) {
+        when (k) {
+            PropKeys.ON_LOAD -> onLoad = v
+            PropKeys.ON_ERROR -> onError = v
+        }
+    }
+
+    override fun updateProp(k: Int, v: Float) {
+        when (k) {
+            PropKeys.HEIGHT -> layoutParams = (layoutParams ?: ViewGroup.LayoutParams(
+                ViewGroup.LayoutParams.WRAP_CONTENT,
+                ViewGroup.LayoutParams.WRAP_CONTENT
+            )).apply { height = v.toInt() }
+            PropKeys.WIDTH -> layoutParams = (layoutParams ?: ViewGroup.LayoutParams(
+                ViewGroup.LayoutParams.WRAP_CONTENT,
+                ViewGroup.LayoutParams.WRAP_CONTENT
+            )).apply { width = v.toInt() }
+            PropKeys.PAD_LEFT, PropKeys.PAD_TOP, PropKeys.PAD_RIGHT, PropKeys.PAD_BOTTOM -> applyPadding(this, k, v.toInt())
+            PropKeys.MARGIN_LEFT, PropKeys.MARGIN_TOP, PropKeys.MARGIN_RIGHT, PropKeys.MARGIN_BOTTOM -> applyMargin(this, k, v.toInt())
+        }
+    }
+
+    override fun updateProp(k: Int, v: Any) {
+    }
+
+    override fun addView(child: View) {
+        throw Error("Undefined")
+    }
+
+    override fun addView(child: View, idx: Int) {
+        throw Error("Undefined")
+    }
+
+    override fun removeView(child: View) {
+        throw Error("Undefined")
+    }
+
+    override fun removeViewAt(idx: Int) {
+        throw Error("Undefined")
     }
 }
 
-class RustCallback {
-    val ptr: Long = 0
-    external fun call(rustCallback: RustCallback)
+// A ScrollView only takes one child, so children go into a vertical layout inside it
+class WiredScrollView(val mContext: Context): ScrollView(mContext), WiredPlatformView {
+    private val content = LinearLayout(mContext)
+    // Setting it again replaces the old one
+    private var onScroll: RustCallback? = null
+
+    init {
+        content.orientation = LinearLayout.VERTICAL
+        super.addView(content, ViewGroup.LayoutParams(
+            ViewGroup.LayoutParams.MATCH_PARENT,
+            ViewGroup.LayoutParams.WRAP_CONTENT
+        ))
+        layoutParams = ViewGroup.LayoutParams(
+            ViewGroup.LayoutParams.MATCH_PARENT,
+            ViewGroup.LayoutParams.MATCH_PARENT
+        )
+    }
+
+    override fun appendChild(child: WiredPlatformView) {
+        content.addView(child.asView())
+    }
+
+    override fun removeChild(child: WiredPlatformView) {
+        content.removeView(child.asView())
+    }
+
+    override fun removeChildIndex(idx: Int) {
+        content.removeViewAt(idx)
+    }
+
+    override fun insertChildAt(child: WiredPlatformView, idx: Int) {
+        content.addView(child.asView(), idx)
+    }
+
+    override fun updateProp(k: Int, v: Float) {
+        when (k) {
+            PropKeys.HEIGHT -> layoutParams = layoutParams.apply { height = v.toInt() }
+            PropKeys.WIDTH -> layoutParams = layoutParams.apply { width = v.toInt() }
+            PropKeys.SCROLL_TO -> scrollTo(0, v.toInt())
+            PropKeys.SMOOTH_SCROLL_TO -> smoothScrollTo(0, v.toInt())
+            PropKeys.PAD_LEFT, PropKeys.PAD_TOP, PropKeys.PAD_RIGHT, PropKeys.PAD_BOTTOM -> applyPadding(this, k, v.toInt())
+            PropKeys.MARGIN_LEFT, PropKeys.MARGIN_TOP, PropKeys.MARGIN_RIGHT, PropKeys.MARGIN_BOTTOM -> applyMargin(this, k, v.toInt())
+        }
+    }
+
+    override fun updateProp(k: Int, v: RustCallback) {
+        when (k) {
+            PropKeys.ON_SCROLL -> onScroll = v
+        }
+    }
+
+    override fun onScrollChanged(l: Int, t: Int, oldl: Int, oldt: Int) {
+        super.onScrollChanged(l, t, oldl, oldt)
+        onScroll?.valueChanged(t.toFloat())
+    }
+
+    override fun updateProp(k: Int, v: String) {
+    }
+
+    override fun updateProp(k: Int, v: Any) {
+    }
+}
+
+// Wraps any view made by class name for NativeView. Props we don't know are setter names, which
+// get called reflectively with the overload matching the value's type.
+class WiredAnyView(val view: View): WiredPlatformView {
+    companion object {
+        // Setters aren't in PropKeys, Rust registers each one's ID before using it
+        val setterNames = HashMap<Int, String>()
+
+        fun keyName(k: Int): String = setterNames[k] ?: PropKeys.NAMES[k]
+    }
+
+    override fun asView(): View = view
+
+    private fun callSetter(k: Int, type: Class<*>, v: Any) {
+        val name = keyName(k)
+        try {
+            view.javaClass.getMethod(name, type).invoke(view, v)
+        } catch (e: ReflectiveOperationException) {
+            // applyCommands reports it back to Rust along with the command that failed
+            throw IllegalArgumentException("Couldn't call ${view.javaClass.name}.$name(${type.simpleName})", e)
+        }
+    }
+
+    override fun updateProp(k: Int, v: Float) {
+        when (k) {
+            PropKeys.HEIGHT -> view.layoutParams = view.layoutParams.apply { height = v.toInt() }
+            PropKeys.WIDTH -> view.layoutParams = view.layoutParams.apply { width = v.toInt() }
+            PropKeys.SET_X -> view.x = v
+            PropKeys.SET_Y -> view.y = v
+            PropKeys.PAD_LEFT, PropKeys.PAD_TOP, PropKeys.PAD_RIGHT, PropKeys.PAD_BOTTOM -> applyPadding(view, k, v.toInt())
+            PropKeys.MARGIN_LEFT, PropKeys.MARGIN_TOP, PropKeys.MARGIN_RIGHT, PropKeys.MARGIN_BOTTOM -> applyMargin(view, k, v.toInt())
+            else -> callSetter(k, java.lang.Float.TYPE, v)
+        }
+    }
+
+    override fun updateProp(k: Int, v: Int) {
+        callSetter(k, Integer.TYPE, v)
+    }
+
+    override fun updateProp(k: Int, v: Long) {
+        callSetter(k, java.lang.Long.TYPE, v)
+    }
+
+    override fun updateProp(k: Int, v: Double) {
+        callSetter(k, java.lang.Double.TYPE, v)
+    }
+
+    override fun updateProp(k: Int, v: Boolean) {
+        callSetter(k, java.lang.Boolean.TYPE, v)
+    }
+
+    override fun updateProp(k: Int, v: String) {
+        // Most text setters take a CharSequence
+        val takesString = view.javaClass.methods.any {
+            it.name == keyName(k) && it.parameterTypes.contentEquals(arrayOf(String::class.java))
+        }
+        callSetter(k, if (takesString) String::class.java else CharSequence::class.java, v)
+    }
+
+    override fun updateProp(k: Int, v: RustCallback) {
+        Log.e("Wired", "NativeView doesn't take callbacks, ignoring ${keyName(k)}")
+    }
+
+    override fun updateProp(k: Int, v: Any) {
+    }
+
+    override fun addView(child: View) {
+        (view as ViewGroup).addView(child)
+    }
+
+    override fun addView(child: View, idx: Int) {
+        (view as ViewGroup).addView(child, idx)
+    }
+
+    override fun removeView(child: View) {
+        (view as ViewGroup).removeView(child)
+    }
+
+    override fun removeViewAt(idx: Int) {
+        (view as ViewGroup).removeViewAt(idx)
+    }
 }
 
 class WiredButton(mContext: Context): Button(mContext), WiredBaseView, WiredPlatformView {
-    override fun updateProp(k: String, v: String) {
+    // Where the last touch went down, so presses can say where they happened
+    private var lastTouchX = 0f
+    private var lastTouchY = 0f
+
+    init {
+        setOnTouchListener { _, event ->
+            lastTouchX = event.x
+            lastTouchY = event.y
+            false
+        }
+    }
+
+    override fun updateProp(k: Int, v: String) {
         when (k) {
-            "text" ->  text = v
+            PropKeys.TEXT ->  text = v
         }
     }
-    override fun updateProp(k: String, v: RustCallback) {
+    override fun updateProp(k: Int, v: RustCallback) {
         when (k) {
-            "on_press" -> {
+            PropKeys.ON_PRESS -> {
                 Log.d("Demo", "REGISTERED ON_PRESS")
                 setOnClickListener {
                     Log.d("Demo", "You've pressed it!")
-                    v.call(v)
+                    v.press(lastTouchX, lastTouchY)
                 }
             }
+            PropKeys.ON_LONG_PRESS -> setOnLongClickListener {
+                v.longPress()
+                true
+            }
             else -> {
-                TODO("not implemented $k") //To change body of created functions use File | Settings | File Templates.
+                TODO("not implemented ${PropKeys.NAMES[k]}") //To change body of created functions use File | Settings | File Templates.
             }
         }
     }
 
-    override fun updateProp(k: String, v: Any) {
+    override fun updateProp(k: Int, v: Any) {
         TODO("not implemented") //To change body of created functions use File | Settings | File Templates.
     }
 
-    override fun updateProp(k: String, v: Float) {
+    override fun updateProp(k: Int, v: Float) {
         super.updateProp(k, v)
     }
 
@@ -230,7 +778,9 @@ class WiredButton(mContext: Context): Button(mContext), WiredBaseView, WiredPlat
 }
 
 
-class WiredViewFactory(val mContext: Context) {
+// The views generated from the widget spec, and the command loop, come from
+// WiredGeneratedViewFactory
+class WiredViewFactory(mContext: Context): WiredGeneratedViewFactory(mContext) {
     fun createTextView(): WiredPlatformView {
         return WiredTextView(mContext)
     }
@@ -244,31 +794,39 @@ class WiredViewFactory(val mContext: Context) {
             )
         return b
     }
-    fun createStackLayoutView(): WiredPlatformView {
-        val l = WiredLinearLayout(mContext)
-        l.layoutParams = ViewGroup.LayoutParams(500, 500)
-        l.gravity = Gravity.CENTER
-        l.orientation = LinearLayout.VERTICAL
-        return l
-    }
-
-    fun createPhysicsLayout(): WiredPlatformView {
-        val l = WiredPhysicsLayout(mContext)
-//        val l = WiredLinearLayout(mContext)
-//        l.layoutParams = ViewGroup.LayoutParams(ViewGroup.LayoutParams.MATCH_PARENT, ViewGroup.LayoutParams.MATCH_PARENT)
-        l.physics.disablePhysics()
-//        l.physics.disableFling()
-        l.layoutParams = ViewGroup.LayoutParams(500, 500)
-//        l.layoutParams = ViewGroup.LayoutParams(ViewGroup.LayoutParams.MATCH_PARENT, ViewGroup.LayoutParams.MATCH_PARENT)
-//        l.addView(createBtnView() as View)
-//        l.physics.enablePhysics()
-        l.physics.enableFling()
-//        l.setBackgroundColor(Color.BLUE)
-//        l.height = 500
-//        l.width = 500
-        return l
-//        return createStackLayoutView()
+    fun createTextInputView(): WiredPlatformView {
+        return WiredTextInput(mContext)
+    }
+    fun createCheckboxView(): WiredPlatformView {
+        return WiredCheckbox(mContext)
+    }
+    fun createSwitchView(): WiredPlatformView {
+        return WiredSwitch(mContext)
+    }
+    fun createRadioGroupView(): WiredPlatformView {
+        return WiredRadioGroup(mContext)
+    }
+    fun createImageView(): WiredPlatformView {
+        return WiredImage(mContext)
+    }
+    fun createScrollView(): WiredPlatformView {
+        return WiredScrollView(mContext)
+    }
+    override fun createNativeView(className: String): WiredPlatformView {
+        val view = Class.forName(className)
+            .getConstructor(Context::class.java)
+            .newInstance(mContext) as View
+        view.layoutParams = ViewGroup.LayoutParams(
+            ViewGroup.LayoutParams.WRAP_CONTENT,
+            ViewGroup.LayoutParams.WRAP_CONTENT
+        )
+        return WiredAnyView(view)
     }
+    fun registerPropKey(id: Int, name: String) {
+        WiredAnyView.setterNames[id] = name
+    }
+    fun isViewGroupClass(className: String): Boolean =
+        ViewGroup::class.java.isAssignableFrom(Class.forName(className))
 }
 
 class Executor {
@@ -317,19 +875,82 @@ class MainActivity : AppCompatActivity() {
 
         Log.d("from rust", hello("Worlld!"))
 
-        val rootView = WiredLinearLayout(this)
+        val rootView = WiredStackLayout(this)
         setContentView(rootView)
 
         val factory = WiredViewFactory(this)
-        init(factory, rootView)
+        factory.setRootView(rootView)
+        val metrics = resources.displayMetrics
+        init(
+            factory,
+            metrics.density,
+            metrics.scaledDensity,
+            metrics.widthPixels,
+            metrics.heightPixels,
+            savedInstanceState?.getByteArray(SAVED_STATE_KEY)
+        )
         androidExecutor.run()
 
         Log.d("fruit", "Finished init")
     }
 
+    override fun onResume() {
+        super.onResume()
+        lifecycleChanged(PropEnums.LIFECYCLE_RESUMED)
+    }
+
+    override fun onPause() {
+        lifecycleChanged(PropEnums.LIFECYCLE_PAUSED)
+        super.onPause()
+    }
+
+    override fun onDestroy() {
+        lifecycleChanged(PropEnums.LIFECYCLE_DESTROYED)
+        super.onDestroy()
+    }
+
+    override fun onConfigurationChanged(newConfig: Configuration) {
+        super.onConfigurationChanged(newConfig)
+        val metrics = resources.displayMetrics
+        configurationChanged(
+            metrics.density,
+            metrics.scaledDensity,
+            metrics.widthPixels,
+            metrics.heightPixels
+        )
+    }
+
+    override fun onSaveInstanceState(outState: Bundle) {
+        super.onSaveInstanceState(outState)
+        outState.putByteArray(SAVED_STATE_KEY, saveState())
+    }
+
+    override fun onBackPressed() {
+        if (!backPressed()) {
+            super.onBackPressed()
+        }
+    }
+
+    external fun lifecycleChanged(state: Int)
+    external fun configurationChanged(
+        density: Float,
+        scaledDensity: Float,
+        widthPixels: Int,
+        heightPixels: Int
+    )
+    external fun backPressed(): Boolean
+    external fun saveState(): ByteArray?
     external fun hello(to: String): String
     external fun init(
         factory: WiredViewFactory,
-        rootView: WiredLinearLayout
+        density: Float,
+        scaledDensity: Float,
+        widthPixels: Int,
+        heightPixels: Int,
+        savedState: ByteArray?
     )
+
+    companion object {
+        const val SAVED_STATE_KEY = "wired_saved_state"
+    }
 }
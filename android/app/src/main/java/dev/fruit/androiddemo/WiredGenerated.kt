@@ -0,0 +1,460 @@
+// Generated by wired_codegen from the widget spec. Edit the spec, not this file.
+// Regenerate with `cargo run -p wired_codegen -- kotlin`
+
+package dev.fruit.androiddemo
+
+import android.content.Context
+import android.util.TypedValue
+import android.view.Gravity
+import android.view.View
+import android.view.ViewGroup
+import android.widget.LinearLayout
+import android.widget.SeekBar
+import com.jawnnypoo.physicslayout.PhysicsLinearLayout
+import java.nio.ByteBuffer
+import java.nio.ByteOrder
+
+// Props cross the bridge as these IDs, Rust numbers its keys the same way
+object PropKeys {
+    const val SET_X = 0
+    const val SET_Y = 1
+    const val TEXT = 2
+    const val TEXT_SIZE = 3
+    const val PAD_LEFT = 4
+    const val PAD_TOP = 5
+    const val PAD_RIGHT = 6
+    const val PAD_BOTTOM = 7
+    const val MARGIN_LEFT = 8
+    const val MARGIN_TOP = 9
+    const val MARGIN_RIGHT = 10
+    const val MARGIN_BOTTOM = 11
+    const val HEIGHT = 12
+    const val WIDTH = 13
+    const val ORIENTATION = 14
+    const val FLING = 15
+    const val ON_PRESS = 16
+    const val ON_LONG_PRESS = 17
+    const val ENTER_TRANSITION = 18
+    const val VALUE = 19
+    const val MAX = 20
+    const val ON_CHANGE = 21
+    const val PLACEHOLDER = 22
+    const val KEYBOARD_TYPE = 23
+    const val MAX_LENGTH = 24
+    const val SECURE = 25
+    const val CHECKED = 26
+    const val OPTIONS = 27
+    const val SELECTED = 28
+    const val SOURCE_BYTES = 29
+    const val SOURCE_FILE = 30
+    const val SOURCE_RESOURCE = 31
+    const val SCALE_MODE = 32
+    const val ON_LOAD = 33
+    const val ON_ERROR = 34
+    const val SCROLL_TO = 35
+    const val SMOOTH_SCROLL_TO = 36
+    const val ON_SCROLL = 37
+
+    val NAMES = arrayOf(
+        "set_x",
+        "set_y",
+        "text",
+        "text_size",
+        "pad_left",
+        "pad_top",
+        "pad_right",
+        "pad_bottom",
+        "margin_left",
+        "margin_top",
+        "margin_right",
+        "margin_bottom",
+        "height",
+        "width",
+        "orientation",
+        "fling",
+        "on_press",
+        "on_long_press",
+        "enter_transition",
+        "value",
+        "max",
+        "on_change",
+        "placeholder",
+        "keyboard_type",
+        "max_length",
+        "secure",
+        "checked",
+        "options",
+        "selected",
+        "source_bytes",
+        "source_file",
+        "source_resource",
+        "scale_mode",
+        "on_load",
+        "on_error",
+        "scroll_to",
+        "smooth_scroll_to",
+        "on_scroll",
+    )
+}
+
+// Opcodes of the view command buffer, Rust numbers them the same way
+object CommandOp {
+    const val ROOT_VIEW = 0
+    const val CREATE_VIEW = 1
+    const val CREATE_NATIVE_VIEW = 2
+    const val RELEASE_VIEW = 3
+    const val SET_F32 = 10
+    const val SET_F64 = 11
+    const val SET_I32 = 12
+    const val SET_I64 = 13
+    const val SET_BOOL = 14
+    const val SET_STRING = 15
+    const val SET_BYTES = 16
+    const val SET_STRINGS = 17
+    const val SET_CALLBACK = 20
+    const val APPEND_CHILD = 30
+    const val INSERT_CHILD_AT = 31
+    const val REMOVE_CHILD = 32
+    const val REMOVE_CHILD_INDEX = 33
+    const val MOVE_CHILD = 34
+}
+
+class WiredStackLayout(mContext: Context): LinearLayout(mContext), WiredPlatformView {
+    override fun updateProp(k: Int, v: Float) {
+        when (k) {
+            PropKeys.HEIGHT -> layoutParams = layoutParams.apply { height = v.toInt() }
+            PropKeys.WIDTH -> layoutParams = layoutParams.apply { width = v.toInt() }
+            PropKeys.SET_X -> x = v
+            PropKeys.SET_Y -> y = v
+            PropKeys.PAD_LEFT -> applyPadding(this, k, v.toInt())
+            PropKeys.PAD_TOP -> applyPadding(this, k, v.toInt())
+            PropKeys.PAD_RIGHT -> applyPadding(this, k, v.toInt())
+            PropKeys.PAD_BOTTOM -> applyPadding(this, k, v.toInt())
+            PropKeys.MARGIN_LEFT -> applyMargin(this, k, v.toInt())
+            PropKeys.MARGIN_TOP -> applyMargin(this, k, v.toInt())
+            PropKeys.MARGIN_RIGHT -> applyMargin(this, k, v.toInt())
+            PropKeys.MARGIN_BOTTOM -> applyMargin(this, k, v.toInt())
+        }
+    }
+
+    override fun updateProp(k: Int, v: Int) {
+        when (k) {
+            PropKeys.ORIENTATION -> when (v) {
+                PropEnums.ORIENTATION_VERTICAL -> orientation = LinearLayout.VERTICAL
+                PropEnums.ORIENTATION_HORIZONTAL -> orientation = LinearLayout.HORIZONTAL
+            }
+            PropKeys.ENTER_TRANSITION -> applyEnterTransition(this, v)
+        }
+    }
+
+    override fun updateProp(k: Int, v: String) {
+    }
+
+    override fun updateProp(k: Int, v: RustCallback) {
+    }
+
+    override fun updateProp(k: Int, v: Any) {
+    }
+}
+
+class WiredPhysicsLayout(mContext: Context): PhysicsLinearLayout(mContext), WiredPlatformView {
+    override fun updateProp(k: Int, v: Float) {
+        when (k) {
+            PropKeys.HEIGHT -> layoutParams = layoutParams.apply { height = v.toInt() }
+            PropKeys.WIDTH -> layoutParams = layoutParams.apply { width = v.toInt() }
+            PropKeys.SET_X -> x = v
+            PropKeys.SET_Y -> y = v
+            PropKeys.PAD_LEFT -> applyPadding(this, k, v.toInt())
+            PropKeys.PAD_TOP -> applyPadding(this, k, v.toInt())
+            PropKeys.PAD_RIGHT -> applyPadding(this, k, v.toInt())
+            PropKeys.PAD_BOTTOM -> applyPadding(this, k, v.toInt())
+            PropKeys.MARGIN_LEFT -> applyMargin(this, k, v.toInt())
+            PropKeys.MARGIN_TOP -> applyMargin(this, k, v.toInt())
+            PropKeys.MARGIN_RIGHT -> applyMargin(this, k, v.toInt())
+            PropKeys.MARGIN_BOTTOM -> applyMargin(this, k, v.toInt())
+        }
+    }
+
+    override fun updateProp(k: Int, v: Int) {
+        when (k) {
+            PropKeys.ORIENTATION -> when (v) {
+                PropEnums.ORIENTATION_VERTICAL -> orientation = LinearLayout.VERTICAL
+                PropEnums.ORIENTATION_HORIZONTAL -> orientation = LinearLayout.HORIZONTAL
+            }
+            PropKeys.FLING -> when (v) {
+                PropEnums.FLING_ENABLED -> {
+                    physics.enableFling()
+                    physics.enablePhysics()
+                }
+                PropEnums.FLING_DISABLED -> physics.disableFling()
+            }
+        }
+    }
+
+    override fun updateProp(k: Int, v: String) {
+    }
+
+    override fun updateProp(k: Int, v: RustCallback) {
+    }
+
+    override fun updateProp(k: Int, v: Any) {
+    }
+}
+
+class WiredSlider(mContext: Context): SeekBar(mContext), WiredPlatformView {
+    override fun updateProp(k: Int, v: Float) {
+        when (k) {
+            PropKeys.HEIGHT -> layoutParams = layoutParams.apply { height = v.toInt() }
+            PropKeys.WIDTH -> layoutParams = layoutParams.apply { width = v.toInt() }
+            PropKeys.PAD_LEFT -> applyPadding(this, k, v.toInt())
+            PropKeys.PAD_TOP -> applyPadding(this, k, v.toInt())
+            PropKeys.PAD_RIGHT -> applyPadding(this, k, v.toInt())
+            PropKeys.PAD_BOTTOM -> applyPadding(this, k, v.toInt())
+            PropKeys.MARGIN_LEFT -> applyMargin(this, k, v.toInt())
+            PropKeys.MARGIN_TOP -> applyMargin(this, k, v.toInt())
+            PropKeys.MARGIN_RIGHT -> applyMargin(this, k, v.toInt())
+            PropKeys.MARGIN_BOTTOM -> applyMargin(this, k, v.toInt())
+            PropKeys.VALUE -> progress = v.toInt()
+            PropKeys.MAX -> max = v.toInt()
+        }
+    }
+
+    override fun updateProp(k: Int, v: String) {
+    }
+
+    override fun updateProp(k: Int, v: RustCallback) {
+        when (k) {
+            PropKeys.ON_CHANGE -> setOnSeekBarChangeListener(object : SeekBar.OnSeekBarChangeListener {
+                override fun onProgressChanged(seekBar: SeekBar, progress: Int, fromUser: Boolean) {
+                    // Changes Rust made don't need to go back to it
+                    if (fromUser) v.valueChanged(progress.toFloat())
+                }
+                override fun onStartTrackingTouch(seekBar: SeekBar) {}
+                override fun onStopTrackingTouch(seekBar: SeekBar) {}
+            })
+        }
+    }
+
+    override fun updateProp(k: Int, v: Any) {
+    }
+
+    override fun addView(child: View) {
+        throw Error("Undefined")
+    }
+
+    override fun addView(child: View, idx: Int) {
+        throw Error("Undefined")
+    }
+
+    override fun removeView(child: View) {
+        throw Error("Undefined")
+    }
+
+    override fun removeViewAt(idx: Int) {
+        throw Error("Undefined")
+    }
+}
+
+// WiredViewFactory extends this with the hand written views
+abstract class WiredGeneratedViewFactory(val mContext: Context) {
+    // Views by the ID Rust gave them
+    private val views = HashMap<Int, WiredPlatformView>()
+
+    fun setRootView(root: WiredPlatformView) {
+        views[CommandOp.ROOT_VIEW] = root
+    }
+
+    private fun view(id: Int): WiredPlatformView =
+        views[id] ?: throw IllegalStateException("No view $id")
+
+    private fun createView(kind: String): WiredPlatformView =
+        javaClass.getMethod("create$kind").invoke(this) as WiredPlatformView
+
+    abstract fun createNativeView(className: String): WiredPlatformView
+
+    private fun ByteBuffer.getString(): String {
+        val bytes = ByteArray(int)
+        get(bytes)
+        return String(bytes, Charsets.UTF_8)
+    }
+
+    // A frame of view changes from Rust, see bindings/command_buffer.rs for the format. Commands
+    // that throw are skipped, and reported together once the rest of the frame is applied.
+    fun applyCommands(buffer: ByteBuffer, callbacks: Array<RustCallback>) {
+        buffer.order(ByteOrder.LITTLE_ENDIAN)
+        val failed = ArrayList<Int>()
+        val causes = ArrayList<Throwable>()
+        while (buffer.hasRemaining()) {
+            val start = buffer.position()
+            val command = try {
+                readCommand(buffer, callbacks)
+            } catch (e: Exception) {
+                // Nothing after a command that can't be read can be trusted
+                failed.add(start)
+                causes.add(e)
+                throw WiredCommandException(failed.toIntArray(), start, causes.toTypedArray())
+            }
+            try {
+                command()
+            } catch (e: Exception) {
+                failed.add(start)
+                causes.add(e)
+            }
+        }
+        if (failed.isNotEmpty()) {
+            throw WiredCommandException(failed.toIntArray(), buffer.limit(), causes.toTypedArray())
+        }
+    }
+
+    // Returns what carrying the command out does
+    private fun readCommand(buffer: ByteBuffer, callbacks: Array<RustCallback>): () -> Unit {
+        val op = buffer.get().toInt()
+        val id = buffer.int
+        when (op) {
+            CommandOp.CREATE_VIEW -> {
+                val kind = buffer.getString()
+                return {
+                    views[id] = createView(kind)
+                }
+            }
+            CommandOp.CREATE_NATIVE_VIEW -> {
+                val className = buffer.getString()
+                return {
+                    views[id] = createNativeView(className)
+                }
+            }
+            CommandOp.RELEASE_VIEW -> {
+                return {
+                    views.remove(id)
+                }
+            }
+            CommandOp.SET_F32 -> {
+                val k = buffer.int
+                val v = buffer.float
+                return {
+                    view(id).updateProp(k, v)
+                }
+            }
+            CommandOp.SET_F64 -> {
+                val k = buffer.int
+                val v = buffer.double
+                return {
+                    view(id).updateProp(k, v)
+                }
+            }
+            CommandOp.SET_I32 -> {
+                val k = buffer.int
+                val v = buffer.int
+                return {
+                    view(id).updateProp(k, v)
+                }
+            }
+            CommandOp.SET_I64 -> {
+                val k = buffer.int
+                val v = buffer.long
+                return {
+                    view(id).updateProp(k, v)
+                }
+            }
+            CommandOp.SET_BOOL -> {
+                val k = buffer.int
+                val v = buffer.get() != 0.toByte()
+                return {
+                    view(id).updateProp(k, v)
+                }
+            }
+            CommandOp.SET_STRING -> {
+                val k = buffer.int
+                val v = buffer.getString()
+                return {
+                    view(id).updateProp(k, v)
+                }
+            }
+            CommandOp.SET_BYTES -> {
+                val k = buffer.int
+                val v = ByteArray(buffer.int).also { buffer.get(it) }
+                return {
+                    view(id).updateProp(k, v)
+                }
+            }
+            CommandOp.SET_STRINGS -> {
+                val k = buffer.int
+                val v = Array(buffer.int) { buffer.getString() }
+                return {
+                    view(id).updateProp(k, v)
+                }
+            }
+            CommandOp.SET_CALLBACK -> {
+                val k = buffer.int
+                val v = callbacks[buffer.int]
+                return {
+                    view(id).updateProp(k, v)
+                }
+            }
+            CommandOp.APPEND_CHILD -> {
+                val child = buffer.int
+                return {
+                    view(id).appendChild(view(child))
+                }
+            }
+            CommandOp.INSERT_CHILD_AT -> {
+                val child = buffer.int
+                val idx = buffer.int
+                return {
+                    view(id).insertChildAt(view(child), idx)
+                }
+            }
+            CommandOp.REMOVE_CHILD -> {
+                val child = buffer.int
+                return {
+                    view(id).removeChild(view(child))
+                }
+            }
+            CommandOp.REMOVE_CHILD_INDEX -> {
+                val idx = buffer.int
+                return {
+                    view(id).removeChildIndex(idx)
+                }
+            }
+            CommandOp.MOVE_CHILD -> {
+                val child = buffer.int
+                val idx = buffer.int
+                return {
+                    view(id).removeChild(view(child))
+                    view(id).insertChildAt(view(child), idx)
+                }
+            }
+            else -> throw IllegalStateException("Unknown view command $op")
+        }
+    }
+
+    fun createStackLayoutView(): WiredPlatformView {
+        val view = WiredStackLayout(mContext)
+        view.layoutParams = ViewGroup.LayoutParams(500, 500)
+        view.gravity = Gravity.CENTER
+        view.orientation = LinearLayout.VERTICAL
+        return view
+    }
+
+    fun createPhysicsLayout(): WiredPlatformView {
+        val view = WiredPhysicsLayout(mContext)
+        view.physics.disablePhysics()
+        view.layoutParams = ViewGroup.LayoutParams(500, 500)
+        view.physics.enableFling()
+        return view
+    }
+
+    fun createSliderView(): WiredPlatformView {
+        val view = WiredSlider(mContext)
+        view.layoutParams = ViewGroup.LayoutParams(
+            ViewGroup.LayoutParams.MATCH_PARENT,
+            ViewGroup.LayoutParams.WRAP_CONTENT
+        )
+        return view
+    }
+}
+
+// What applyCommands throws once the frame is applied. Rust reads which commands failed from
+// it, and how far the host got if it had to stop early.
+class WiredCommandException(val offsets: IntArray, val end: Int, val causes: Array<Throwable>) :
+    RuntimeException("View commands at bytes ${offsets.joinToString()} failed", causes[0])
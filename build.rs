@@ -0,0 +1,27 @@
+use std::env;
+use std::path::Path;
+
+fn main() {
+  let spec = Path::new("src/bindings/android/widgets.toml");
+  let kotlin = Path::new("android/app/src/main/java/dev/fruit/androiddemo/WiredGenerated.kt");
+  println!("cargo:rerun-if-changed={}", spec.display());
+  println!("cargo:rerun-if-changed={}", kotlin.display());
+
+  let out_dir = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+  let out = Path::new(&out_dir).join("android_widgets.rs");
+  if let Err(e) = wired_codegen::generate_rust_bindings(spec, &out) {
+    panic!("{}", e);
+  }
+  let keys = Path::new(&out_dir).join("prop_keys.rs");
+  if let Err(e) = wired_codegen::generate_rust_prop_keys(spec, &keys) {
+    panic!("{}", e);
+  }
+  let ops = Path::new(&out_dir).join("command_ops.rs");
+  if let Err(e) = wired_codegen::generate_rust_command_ops(&ops) {
+    panic!("{}", e);
+  }
+  // The host's half is checked in, so make sure it still agrees with ours
+  if let Err(e) = wired_codegen::check_kotlin_glue(spec, kotlin) {
+    panic!("{}", e);
+  }
+}
@@ -118,6 +118,7 @@ pub unsafe extern "C" fn Java_dev_fruit_androiddemo_Executor_poll(
       }
     }
   }
+  crate::bindings::android::frame::end_frame(&env);
 }
 
 impl AndroidExecutor {
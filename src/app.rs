@@ -3,6 +3,8 @@ use crate::android_executor::spawn_future;
 use crate::bindings::android::views::{Button, StackLayout, Text};
 use crate::bindings::view_helpers::*;
 use crate::helpers::if_signal;
+use crate::lifecycle::{lifecycle_state, LifecycleState};
+use crate::style::sp;
 use discard::DiscardOnDrop;
 use futures::future::ready;
 use futures::prelude::*;
@@ -19,7 +21,7 @@ pub fn main() -> StackLayout {
   };
 
   StackLayout::new().with(move || {
-    Text::new("Hello WIRED").text_size(32.0);
+    Text::new("Hello WIRED").text_size(sp(32.0));
     Button::new(on_press);
     Text::default()
       .text_signal(
@@ -27,14 +29,14 @@ pub fn main() -> StackLayout {
           .signal()
           .map(|i| format!("You've pressed it {} times", i)),
       )
-      .text_size(32.0);
+      .text_size(sp(32.0));
     if_signal(count.signal().map(|i| i % 2 == 0), |is_even| {
       if is_even {
         Text::new("Number is even!");
       }
     });
-    marquee("WEEE").text_size(22.0);
-    Text::new("This is some other message").text_size(22.0);
+    marquee("WEEE").text_size(sp(22.0));
+    Text::new("This is some other message").text_size(sp(22.0));
   })
 }
 
@@ -47,8 +49,11 @@ where
   let f = Interval::new(Duration::from_millis(15))
     .take(900)
     .for_each(move |_| {
-      let mut lock = count.lock_mut();
-      *lock = (*lock + 20.0) % 1400.0;
+      // No point animating while the app is in the background
+      if lifecycle_state() == LifecycleState::Resumed {
+        let mut lock = count.lock_mut();
+        *lock = (*lock + 20.0) % 1400.0;
+      }
       ready(())
     });
 
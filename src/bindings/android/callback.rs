@@ -7,6 +7,7 @@ use {
   futures_signals::{cancelable_future, CancelableFutureHandle},
   jni::{
     objects::{GlobalRef, JClass, JObject, JString, JValue},
+    sys::{jfloat, jint, jlong},
     JNIEnv, JavaVM,
   },
   std::{
@@ -18,15 +19,55 @@ use {
     time::Duration,
   },
 };
-use crate::bindings::callback::Callback;
+use crate::bindings::android::exceptions::take_exception;
+use crate::bindings::callback::{Event, EventHandler};
 
+/// Every callback comes through here, `kind` is an `EventKind` code saying which fields matter.
 #[no_mangle]
-pub unsafe extern "C" fn Java_dev_fruit_androiddemo_RustCallback_call(
+pub unsafe extern "C" fn Java_dev_fruit_androiddemo_RustCallback_dispatch(
   env: JNIEnv,
   _class: JClass,
   callback_ref: JObject,
+  kind: jint,
+  x: jfloat,
+  y: jfloat,
+  n: jlong,
+  text: JString,
 ) {
-  let callback: MutexGuard<Callback> = env.get_rust_field(callback_ref, "ptr").unwrap();
-  debug!("Calling callback!");
-  (callback.f)()
+  let text: Option<String> = if text.into_inner().is_null() {
+    None
+  } else {
+    match env.get_string(text) {
+      Ok(text) => Some(text.into()),
+      Err(e) => {
+        // Panicking here would unwind into Java, and returning with an exception pending
+        // aborts, so drop the event
+        take_exception(&env);
+        warn!("Couldn't read event text: {}", e);
+        return;
+      }
+    }
+  };
+  let event = match Event::decode(kind, x, y, n, text) {
+    Some(event) => event,
+    None => {
+      warn!("Unknown event kind {}", kind);
+      return;
+    }
+  };
+  // Don't hold the field lock while the callback runs
+  let handler: EventHandler = match env.get_rust_field(callback_ref, "ptr") {
+    Ok(handler) => {
+      let handler: MutexGuard<EventHandler> = handler;
+      handler.clone()
+    }
+    Err(_) => {
+      // The view outlived its callback, see dispose_callback
+      debug!("Dropping event for a disposed callback");
+      return;
+    }
+  };
+  debug!("Dispatching {:?}", event);
+  handler.dispatch(event);
+  crate::bindings::android::frame::end_frame(&env);
 }
@@ -0,0 +1,59 @@
+//! A Java exception stays pending after the call that threw it, and the next JNI call made with
+//! one pending aborts the VM. Calls into Java that can throw go through `check`, which logs and
+//! clears the exception and returns it as a `WiredError::JavaException`.
+
+use crate::bindings::android::local_frame::with_local_frame;
+use crate::error::{JavaOp, WiredError};
+use jni::objects::{JObject, JThrowable};
+use jni::JNIEnv;
+
+/// Passes `result` on, unless Java threw, then it's the exception with `op` for context
+pub fn check<T, F>(env: &JNIEnv, result: jni::errors::Result<T>, op: F) -> Result<T, WiredError>
+where
+  F: FnOnce() -> JavaOp,
+{
+  match take_exception(env) {
+    Some(throwable) => Err(to_error(env, throwable.into(), op())),
+    None => Ok(result?),
+  }
+}
+
+/// Clears the pending exception and returns it, if there is one. It's a local ref.
+pub fn take_exception<'a>(env: &JNIEnv<'a>) -> Option<JThrowable<'a>> {
+  if !env.exception_check().unwrap_or(false) {
+    return None;
+  }
+  let throwable = env.exception_occurred().ok();
+  // Prints the stack trace to logcat
+  let _ = env.exception_describe();
+  let _ = env.exception_clear();
+  throwable
+}
+
+pub fn to_error(env: &JNIEnv, throwable: JObject, op: JavaOp) -> WiredError {
+  let described = with_local_frame(env, 4, || {
+    let class = env
+      .call_method(throwable, "getClass", "()Ljava/lang/Class;", &[])?
+      .l()?;
+    let class_name = env
+      .call_method(class, "getName", "()Ljava/lang/String;", &[])?
+      .l()?;
+    let message = env
+      .call_method(throwable, "getMessage", "()Ljava/lang/String;", &[])?
+      .l()?;
+    let message: Option<String> = if message.is_null() {
+      None
+    } else {
+      Some(env.get_string(message.into())?.into())
+    };
+    let class_name: String = env.get_string(class_name.into())?.into();
+    Ok((class_name, message))
+  });
+  let (class, message) = described.unwrap_or_else(|e| {
+    // Don't leave an exception from describing this one pending either
+    take_exception(env);
+    warn!("Couldn't describe Java exception: {}", e);
+    ("<unknown>".into(), None)
+  });
+  WiredError::JavaException { class, message, op }
+}
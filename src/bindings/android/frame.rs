@@ -0,0 +1,433 @@
+//! Views record their changes into one buffer (see `bindings::command_buffer`) and `flush` hands
+//! it to the host in a single call. Entry points that can change views flush before returning to
+//! Java, so a frame is whatever one of them did.
+
+use crate::bindings::android::exceptions::{take_exception, to_error};
+use crate::bindings::android::jni_cache::jni_cache;
+use crate::bindings::android::local_frame::{for_each_in_frame, with_local_frame, LocalFrames};
+use crate::bindings::android::views::VIEWFACTORY;
+use crate::bindings::callback::EventHandler;
+use crate::bindings::command_buffer::{
+  Command, CommandBuffer, HeldCallbacks, SharedBuffer, ViewId, ROOT_VIEW,
+};
+use crate::error::{JavaOp, WiredError};
+use jni::objects::{GlobalRef, JClass, JObject, JValue};
+use jni::signature::{JavaType, Primitive};
+use jni::JNIEnv;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+thread_local! {
+  static FRAME: SharedBuffer = Arc::new(Mutex::new(CommandBuffer::new()));
+  /// The RustCallback objects the host's views hold. Their boxes are freed when the host lets go.
+  static CALLBACKS: RefCell<HeldCallbacks<GlobalRef>> = RefCell::new(HeldCallbacks::new());
+  /// What kind each live view is, so errors from the host can say which view failed
+  static VIEW_KINDS: RefCell<HashMap<ViewId, String>> = RefCell::new(HashMap::new());
+}
+
+/// The buffer views record into until the next flush
+pub fn buffer() -> SharedBuffer {
+  FRAME.with(|f| f.clone())
+}
+
+/// Sends everything recorded since the last flush to the host. A command that throws is skipped
+/// and the host applies the rest of the frame, then the first exception comes back as a
+/// `JavaException` for its command. Any others are logged.
+pub fn flush(env: &JNIEnv) -> Result<(), WiredError> {
+  FRAME.with(|frame| CALLBACKS.with(|callbacks| flush_frame(env, frame, callbacks)))
+}
+
+// What `flush` needs from the host, so a frame can be run against a mock env in tests
+trait FrameEnv: LocalFrames {
+  /// The host's handle on a RustCallback object, which owns its handler until it's disposed
+  type Callback;
+
+  /// Called in a local frame, so it's free to make local refs
+  fn new_callback(&self, handler: EventHandler) -> Result<Self::Callback, WiredError>;
+  /// Runs the frame on the host, returns what it didn't get done
+  fn apply_commands(
+    &self,
+    bytes: &mut [u8],
+    callbacks: &[Self::Callback],
+  ) -> Result<Option<HostFailure>, WiredError>;
+  /// Frees the Rust side of a RustCallback. The view may still hold on to the Java object, but
+  /// dispatching to it after this is a no-op.
+  fn dispose_callback(&self, callback: &Self::Callback);
+}
+
+impl<'a> FrameEnv for JNIEnv<'a> {
+  type Callback = GlobalRef;
+
+  fn new_callback(&self, handler: EventHandler) -> Result<GlobalRef, WiredError> {
+    let ids = jni_cache();
+    let callback_obj = self.new_object_unchecked(
+      JClass::from(ids.rust_callback_class.as_obj()),
+      ids.rust_callback_new.id(),
+      &[],
+    )?;
+    self.set_rust_field(callback_obj, "ptr", handler)?;
+    Ok(self.new_global_ref(callback_obj)?)
+  }
+
+  fn apply_commands(
+    &self,
+    bytes: &mut [u8],
+    callbacks: &[GlobalRef],
+  ) -> Result<Option<HostFailure>, WiredError> {
+    let env = self;
+    let ids = jni_cache();
+    with_local_frame(env, 2, || {
+      let callback_array = env.new_object_array(
+        callbacks.len() as i32,
+        JClass::from(ids.rust_callback_class.as_obj()),
+        JObject::null(),
+      )?;
+      for (i, callback_obj) in callbacks.iter().enumerate() {
+        env.set_object_array_element(callback_array, i as i32, callback_obj.as_obj())?;
+      }
+      // The host only reads the buffer during the call, so it can point at our copy
+      let commands = env.new_direct_byte_buffer(bytes)?;
+      let result = VIEWFACTORY.with(|view_factory| -> Result<_, WiredError> {
+        let view_factory_ref = view_factory.borrow();
+        let view_factory = view_factory_ref.as_ref().ok_or(WiredError::NoViewFactory)?;
+        // The ID was resolved with this signature, so the unchecked call is safe
+        Ok(env.call_method_unchecked(
+          view_factory.inner.as_obj(),
+          ids.apply_commands.id(),
+          JavaType::Primitive(Primitive::Void),
+          &[
+            JValue::Object(commands.into()),
+            JValue::Object(JObject::from(callback_array)),
+          ],
+        ))
+      })?;
+      match result {
+        Ok(_) => Ok(None),
+        Err(e) => {
+          let throwable = take_exception(env).ok_or(e)?;
+          Ok(Some(command_failure(env, throwable.into(), bytes)?))
+        }
+      }
+    })
+  }
+
+  fn dispose_callback(&self, callback_obj: &GlobalRef) {
+    if let Err(e) = self.take_rust_field::<_, EventHandler>(callback_obj.as_obj(), "ptr") {
+      warn!("Couldn't free callback: {}", e);
+    }
+  }
+}
+
+fn flush_frame<E: FrameEnv + ?Sized>(
+  env: &E,
+  frame: &SharedBuffer,
+  callbacks: &RefCell<HeldCallbacks<E::Callback>>,
+) -> Result<(), WiredError> {
+  let frame = frame.lock().unwrap().take();
+  if frame.is_empty() {
+    return Ok(());
+  }
+
+  let callback_objs = new_callbacks(env, frame.handlers(), |handler| {
+    env.new_callback(handler.clone())
+  })?;
+
+  let (mut bytes, _) = frame.into_parts();
+  track_created_views(&bytes);
+  let failure = env
+    .apply_commands(&mut bytes, &callback_objs)?
+    .unwrap_or_else(|| HostFailure {
+      failed: vec![],
+      end: bytes.len(),
+      errors: vec![],
+    });
+  track_frame(
+    env,
+    callbacks,
+    &bytes[..failure.end],
+    &failure.failed,
+    callback_objs,
+  )?;
+  let mut errors = failure.errors.into_iter();
+  let first = errors.next();
+  for error in errors {
+    error!("Couldn't apply view command: {}", error);
+  }
+  match first {
+    Some(error) => Err(error),
+    None => Ok(()),
+  }
+}
+
+/// `flush` for entry points, which have nowhere to return the error to
+pub fn end_frame(env: &JNIEnv) {
+  if let Err(e) = flush(env) {
+    error!("Couldn't apply view commands: {}", e);
+  }
+}
+
+// Frames can have any number of callbacks, so each object's local ref is freed as soon as
+// `new_callback` has made a global one
+fn new_callbacks<E, C, F>(
+  env: &E,
+  handlers: &[EventHandler],
+  mut new_callback: F,
+) -> Result<Vec<C>, WiredError>
+where
+  E: LocalFrames + ?Sized,
+  F: FnMut(&EventHandler) -> Result<C, WiredError>,
+{
+  let mut callback_objs = Vec::with_capacity(handlers.len());
+  for_each_in_frame(env, 1, handlers, |handler| {
+    callback_objs.push(new_callback(handler)?);
+    Ok(())
+  })?;
+  Ok(callback_objs)
+}
+
+// What the host didn't get done
+struct HostFailure {
+  /// Offsets of the commands that threw
+  failed: Vec<usize>,
+  /// How far into the buffer the host got, it stops early if a command can't be read
+  end: usize,
+  errors: Vec<WiredError>,
+}
+
+// applyCommands reports the commands that threw in one WiredCommandException, with their
+// offsets and what each of them threw
+fn command_failure(
+  env: &JNIEnv,
+  throwable: JObject,
+  bytes: &[u8],
+) -> Result<HostFailure, WiredError> {
+  let ids = jni_cache();
+  let command_exception = JClass::from(ids.command_exception_class.as_obj());
+  if !env.is_instance_of(throwable, command_exception)? {
+    return Ok(HostFailure {
+      failed: vec![],
+      end: 0,
+      errors: vec![to_error(env, throwable, JavaOp::call("applyCommands"))],
+    });
+  }
+  let end = env.get_field(throwable, "end", "I")?.i()? as usize;
+  let offsets = env.get_field(throwable, "offsets", "[I")?.l()?.into_inner();
+  let causes = env
+    .get_field(throwable, "causes", "[Ljava/lang/Throwable;")?
+    .l()?
+    .into_inner();
+  let mut failed = vec![0; env.get_array_length(offsets)? as usize];
+  env.get_int_array_region(offsets, 0, &mut failed)?;
+
+  let mut errors = Vec::with_capacity(failed.len());
+  for (i, &offset) in failed.iter().enumerate() {
+    let cause = env.get_object_array_element(causes, i as i32)?;
+    let op = match CommandBuffer::decode(bytes.get(offset as usize..).unwrap_or(&[])).next() {
+      Some(Ok(command)) => command_op(&command),
+      _ => JavaOp::call("applyCommands"),
+    };
+    errors.push(to_error(env, cause, op));
+    // There can be one for every command in the frame
+    env.delete_local_ref(cause)?;
+  }
+  Ok(HostFailure {
+    failed: failed.into_iter().map(|offset| offset as usize).collect(),
+    end: end.min(bytes.len()),
+    errors,
+  })
+}
+
+fn view_kind(view: ViewId) -> String {
+  VIEW_KINDS.with(|kinds| match kinds.borrow().get(&view) {
+    Some(kind) => kind.clone(),
+    None if view == ROOT_VIEW => "the root view".into(),
+    None => format!("view {}", view),
+  })
+}
+
+// What the host calls to carry out a command
+fn command_op(command: &Command) -> JavaOp {
+  match command {
+    Command::CreateView { kind, .. } => {
+      JavaOp::call(format!("create{}", kind)).on_view(kind.as_str())
+    }
+    Command::CreateNativeView { class_name, .. } => {
+      JavaOp::call("createNativeView").on_view(class_name.as_str())
+    }
+    Command::ReleaseView { view } => JavaOp::call("releaseView").on_view(view_kind(*view)),
+    Command::SetProp { view, key, .. } | Command::SetCallback { view, key, .. } => {
+      JavaOp::call("updateProp")
+        .on_view(view_kind(*view))
+        .with_key(key.name())
+    }
+    Command::AppendChild { parent, .. } => JavaOp::call("appendChild").on_view(view_kind(*parent)),
+    Command::InsertChildAt { parent, .. } => {
+      JavaOp::call("insertChildAt").on_view(view_kind(*parent))
+    }
+    Command::RemoveChild { parent, .. } => JavaOp::call("removeChild").on_view(view_kind(*parent)),
+    Command::RemoveChildIndex { parent, .. } => {
+      JavaOp::call("removeChildIndex").on_view(view_kind(*parent))
+    }
+    Command::MoveChild { parent, .. } => JavaOp::call("moveChild").on_view(view_kind(*parent)),
+  }
+}
+
+// Before the host runs the frame, so a failing command can name views made in the same frame
+fn track_created_views(bytes: &[u8]) {
+  VIEW_KINDS.with(|kinds| {
+    let mut kinds = kinds.borrow_mut();
+    for command in CommandBuffer::decode(bytes) {
+      match command {
+        Ok(Command::CreateView { view, kind }) => {
+          kinds.insert(view, kind);
+        }
+        Ok(Command::CreateNativeView { view, class_name }) => {
+          kinds.insert(view, class_name);
+        }
+        _ => {}
+      }
+    }
+  });
+}
+
+// Once the host has applied a frame, the callbacks it replaced or whose views it let go of are
+// unreachable from Rust. So are the ones for commands that failed or that it didn't get to.
+fn track_frame<E: FrameEnv + ?Sized>(
+  env: &E,
+  callbacks: &RefCell<HeldCallbacks<E::Callback>>,
+  applied: &[u8],
+  failed: &[usize],
+  callback_objs: Vec<E::Callback>,
+) -> Result<(), WiredError> {
+  let mut callback_objs: Vec<Option<E::Callback>> = callback_objs.into_iter().map(Some).collect();
+  {
+    let mut callbacks = callbacks.borrow_mut();
+    let mut commands = CommandBuffer::decode(applied);
+    loop {
+      let offset = commands.offset();
+      let command = match commands.next() {
+        Some(command) => command?,
+        None => break,
+      };
+      if failed.contains(&offset) {
+        continue;
+      }
+      match command {
+        Command::SetCallback { view, key, handler } => {
+          let callback_obj = callback_objs[handler].take().expect("Callback used twice");
+          if let Some(old) = callbacks.hold(view, key, callback_obj) {
+            env.dispose_callback(&old);
+          }
+        }
+        Command::ReleaseView { view } => {
+          VIEW_KINDS.with(|kinds| kinds.borrow_mut().remove(&view));
+          for callback_obj in callbacks.release(view) {
+            env.dispose_callback(&callback_obj);
+          }
+        }
+        _ => {}
+      }
+    }
+  }
+  for callback_obj in callback_objs.iter().flatten() {
+    env.dispose_callback(callback_obj);
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::bindings::android::local_frame::mock::MockEnv;
+  use crate::bindings::callback::{live_callbacks, Callback};
+  use crate::bindings::command_buffer::RecordingView;
+  use crate::bindings::prop_key::PropKey;
+  use crate::bindings::view_helpers::UpdateProp;
+  use crate::ui_tree::{PlatformView, PlatformViewInner};
+  use std::mem;
+
+  // Stands in for a RustCallback's global ref, which owns the handler until it's disposed
+  pub(super) struct MockCallback(RefCell<Option<EventHandler>>);
+
+  impl Drop for MockCallback {
+    fn drop(&mut self) {
+      // Letting go of the global ref leaves the handler with the Java object, which leaks it
+      mem::forget(self.0.borrow_mut().take());
+    }
+  }
+
+  impl FrameEnv for MockEnv {
+    type Callback = MockCallback;
+
+    fn new_callback(&self, handler: EventHandler) -> Result<MockCallback, WiredError> {
+      // The RustCallback object, its global ref doesn't take up the table
+      self.new_local_ref();
+      Ok(MockCallback(RefCell::new(Some(handler))))
+    }
+
+    fn apply_commands(
+      &self,
+      _bytes: &mut [u8],
+      _callbacks: &[MockCallback],
+    ) -> Result<Option<HostFailure>, WiredError> {
+      // The callback array and the byte buffer, the host applies everything
+      with_local_frame(self, 2, || {
+        self.new_local_ref();
+        self.new_local_ref();
+        Ok(None)
+      })
+    }
+
+    fn dispose_callback(&self, callback: &MockCallback) {
+      assert!(
+        callback.0.borrow_mut().take().is_some(),
+        "Callback disposed twice"
+      );
+    }
+  }
+
+  fn press_handlers(buttons: &mut [PlatformView]) {
+    for button in buttons.iter_mut() {
+      button
+        .update_prop(PropKey::ON_PRESS, Callback::new(|()| {}))
+        .unwrap();
+    }
+  }
+
+  #[test]
+  fn frames_with_many_callbacks() {
+    let env = MockEnv::new();
+    let frame = buffer();
+    let callbacks = RefCell::new(HeldCallbacks::new());
+    let live_before = live_callbacks();
+
+    // A list of a few thousand buttons, all made in one frame
+    let mut root = PlatformView::new(RecordingView::root(&frame, "StackLayout"));
+    let mut buttons: Vec<PlatformView> = (0..10_000)
+      .map(|_| PlatformView::new(RecordingView::create(&frame, "Button")))
+      .collect();
+    press_handlers(&mut buttons);
+    for button in buttons.iter() {
+      root.append_child(button).unwrap();
+    }
+    flush_frame(&env, &frame, &callbacks).unwrap();
+    assert_eq!(live_callbacks(), live_before + 10_000);
+
+    // Setting them all again frees the old ones
+    press_handlers(&mut buttons);
+    flush_frame(&env, &frame, &callbacks).unwrap();
+    assert_eq!(live_callbacks(), live_before + 10_000);
+
+    // And clearing the list frees the rest
+    mem::drop(buttons);
+    for idx in (0..10_000).rev() {
+      root.remove_child_index(idx).unwrap();
+    }
+    flush_frame(&env, &frame, &callbacks).unwrap();
+    assert_eq!(live_callbacks(), live_before);
+
+    assert_eq!((env.depth(), env.live()), (1, 0));
+    assert_eq!(*env.peak.borrow(), 2);
+  }
+}
@@ -0,0 +1,97 @@
+//! Classes and method IDs the bindings call every frame, resolved once in `init` instead of
+//! by name and signature on each call. `init` runs on the UI thread from Java, so `find_class`
+//! sees the app's class loader there, which a native thread wouldn't.
+
+use crate::bindings::android::exceptions::check;
+use crate::bindings::android::local_frame::with_local_frame;
+use crate::error::{JavaOp, WiredError};
+use jni::objects::{GlobalRef, JClass, JMethodID};
+use jni::sys::jmethodID;
+use jni::JNIEnv;
+use std::cell::RefCell;
+use std::sync::Arc;
+
+const VIEW_FACTORY_CLASS: &str = "dev/fruit/androiddemo/WiredViewFactory";
+const RUST_CALLBACK_CLASS: &str = "dev/fruit/androiddemo/RustCallback";
+const COMMAND_EXCEPTION_CLASS: &str = "dev/fruit/androiddemo/WiredCommandException";
+
+thread_local! {
+  static JNI_CACHE: RefCell<Option<Arc<JniCache>>> = RefCell::new(None);
+}
+
+/// A method ID that outlives the env it was resolved with. It stays valid while its class is
+/// loaded, and the cache holds a global ref to every class it has IDs for.
+#[derive(Clone, Copy, Debug)]
+pub struct MethodId(jmethodID);
+
+// IDs belong to the class, not to the thread that looked them up
+unsafe impl Send for MethodId {}
+unsafe impl Sync for MethodId {}
+
+impl MethodId {
+  fn resolve(env: &JNIEnv, class: &GlobalRef, name: &str, sig: &str) -> Result<Self, WiredError> {
+    let id = env.get_method_id(JClass::from(class.as_obj()), name, sig);
+    let id = check(env, id, || {
+      JavaOp::call(format!("getMethodID({}{})", name, sig))
+    })?;
+    Ok(MethodId(id.into_inner()))
+  }
+
+  pub fn id<'a>(self) -> JMethodID<'a> {
+    JMethodID::from(self.0)
+  }
+}
+
+pub struct JniCache {
+  pub view_factory_class: GlobalRef,
+  pub rust_callback_class: GlobalRef,
+  /// What applyCommands throws, with the offsets of the commands that failed
+  pub command_exception_class: GlobalRef,
+  pub rust_callback_new: MethodId,
+  /// Takes a frame's command buffer and the callbacks it refers to
+  pub apply_commands: MethodId,
+}
+
+impl JniCache {
+  pub fn new(env: &JNIEnv) -> Result<Self, WiredError> {
+    let find_class = |name: &str| {
+      with_local_frame(env, 1, || {
+        let class = check(env, env.find_class(name), || {
+          JavaOp::call(format!("findClass({})", name))
+        })?;
+        Ok(env.new_global_ref(class.into())?)
+      })
+    };
+    let view_factory_class = find_class(VIEW_FACTORY_CLASS)?;
+    let rust_callback_class = find_class(RUST_CALLBACK_CLASS)?;
+    let command_exception_class = find_class(COMMAND_EXCEPTION_CLASS)?;
+
+    Ok(JniCache {
+      apply_commands: MethodId::resolve(
+        env,
+        &view_factory_class,
+        "applyCommands",
+        "(Ljava/nio/ByteBuffer;[Ldev/fruit/androiddemo/RustCallback;)V",
+      )?,
+      rust_callback_new: MethodId::resolve(env, &rust_callback_class, "<init>", "()V")?,
+      view_factory_class,
+      rust_callback_class,
+      command_exception_class,
+    })
+  }
+}
+
+/// Builds the cache, call it from `init` before any view is made
+pub fn init(env: &JNIEnv) -> Result<(), WiredError> {
+  let cache = Arc::new(JniCache::new(env)?);
+  JNI_CACHE.with(|c| *c.borrow_mut() = Some(cache));
+  Ok(())
+}
+
+pub fn jni_cache() -> Arc<JniCache> {
+  JNI_CACHE.with(|c| {
+    c.borrow()
+      .clone()
+      .expect("JNI cache isn't initialized, call init first")
+  })
+}
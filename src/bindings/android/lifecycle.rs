@@ -0,0 +1,71 @@
+use crate::bindings::android::exceptions::take_exception;
+use crate::bindings::android::frame;
+use crate::lifecycle::{self, LifecycleState};
+use crate::saved_state;
+use crate::style::{DisplayMetrics, PropEnum};
+use jni::objects::JObject;
+use jni::sys::{jboolean, jbyteArray, jfloat, jint, JNI_FALSE, JNI_TRUE};
+use jni::JNIEnv;
+
+#[no_mangle]
+pub unsafe extern "C" fn Java_dev_fruit_androiddemo_MainActivity_lifecycleChanged(
+  env: JNIEnv,
+  _activity: JObject,
+  state: jint,
+) {
+  match LifecycleState::from_code(state) {
+    Some(state) => lifecycle::set_lifecycle_state(state),
+    None => warn!("Unknown lifecycle state {}", state),
+  }
+  frame::end_frame(&env);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Java_dev_fruit_androiddemo_MainActivity_configurationChanged(
+  env: JNIEnv,
+  _activity: JObject,
+  density: jfloat,
+  scaled_density: jfloat,
+  width_px: jint,
+  height_px: jint,
+) {
+  lifecycle::set_configuration(DisplayMetrics {
+    density,
+    scaled_density,
+    width_px: width_px as f32,
+    height_px: height_px as f32,
+  });
+  frame::end_frame(&env);
+}
+
+/// Returns whether Rust consumed the press, if not the activity should handle it
+#[no_mangle]
+pub unsafe extern "C" fn Java_dev_fruit_androiddemo_MainActivity_backPressed(
+  env: JNIEnv,
+  _activity: JObject,
+) -> jboolean {
+  let handled = lifecycle::dispatch_back_pressed();
+  frame::end_frame(&env);
+  if handled {
+    JNI_TRUE
+  } else {
+    JNI_FALSE
+  }
+}
+
+/// The blob to hand back to `init` if the activity is recreated, null if it couldn't be made
+#[no_mangle]
+pub unsafe extern "C" fn Java_dev_fruit_androiddemo_MainActivity_saveState(
+  env: JNIEnv,
+  _activity: JObject,
+) -> jbyteArray {
+  match env.byte_array_from_slice(&saved_state::save_state()) {
+    Ok(blob) => blob,
+    Err(e) => {
+      // Don't return to Java with the exception pending
+      take_exception(&env);
+      error!("Couldn't create saved state array: {}", e);
+      JObject::null().into_inner()
+    }
+  }
+}
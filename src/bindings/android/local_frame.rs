@@ -0,0 +1,180 @@
+//! Every JNI call that returns an object makes a local ref, and they're only freed when the native
+//! method returns to Java. The table holding them is small (512 entries on Android), so work
+//! that can make more than a handful, or runs inside a long native call, goes in a local frame.
+
+use crate::error::WiredError;
+use jni::objects::JObject;
+use jni::JNIEnv;
+
+/// The part of `JNIEnv` frames need, so the bookkeeping can be tested without a VM
+pub trait LocalFrames {
+  fn push_frame(&self, capacity: i32) -> Result<(), WiredError>;
+  fn pop_frame(&self);
+}
+
+impl<'a> LocalFrames for JNIEnv<'a> {
+  fn push_frame(&self, capacity: i32) -> Result<(), WiredError> {
+    self.push_local_frame(capacity)?;
+    Ok(())
+  }
+
+  fn pop_frame(&self) {
+    if let Err(e) = self.pop_local_frame(JObject::null()) {
+      warn!("Couldn't pop local frame: {}", e);
+    }
+  }
+}
+
+// Pops on drop, so a frame is left on error or panic too
+struct FrameGuard<'e, E: LocalFrames + ?Sized>(&'e E);
+
+impl<'e, E: LocalFrames + ?Sized> Drop for FrameGuard<'e, E> {
+  fn drop(&mut self) {
+    self.0.pop_frame();
+  }
+}
+
+/// Runs `f` in a frame with room for `capacity` local refs, which are all freed when it returns.
+/// So `f` can't return a local ref, make it a global one if it has to outlive the frame.
+pub fn with_local_frame<E, T, F>(env: &E, capacity: i32, f: F) -> Result<T, WiredError>
+where
+  E: LocalFrames + ?Sized,
+  F: FnOnce() -> Result<T, WiredError>,
+{
+  env.push_frame(capacity)?;
+  let _guard = FrameGuard(env);
+  f()
+}
+
+/// Calls `f` on each item in a frame of its own, so only one item's local refs are alive at a
+/// time however many items there are
+pub fn for_each_in_frame<E, I, F>(
+  env: &E,
+  capacity: i32,
+  items: I,
+  mut f: F,
+) -> Result<(), WiredError>
+where
+  E: LocalFrames + ?Sized,
+  I: IntoIterator,
+  F: FnMut(I::Item) -> Result<(), WiredError>,
+{
+  for item in items {
+    with_local_frame(env, capacity, || f(item))?;
+  }
+  Ok(())
+}
+
+/// A stand-in `JNIEnv` for testing code that makes local refs
+#[cfg(test)]
+pub(crate) mod mock {
+  use super::*;
+  use std::cell::RefCell;
+
+  pub const TABLE_SIZE: usize = 512;
+
+  /// Counts local refs like the VM's table, which aborts the process when it overflows
+  pub struct MockEnv {
+    /// Refs made in each frame, the first is the native call's own
+    frames: RefCell<Vec<usize>>,
+    pub peak: RefCell<usize>,
+  }
+
+  impl MockEnv {
+    pub fn new() -> Self {
+      MockEnv {
+        frames: RefCell::new(vec![0]),
+        peak: RefCell::new(0),
+      }
+    }
+
+    pub fn new_local_ref(&self) {
+      *self.frames.borrow_mut().last_mut().unwrap() += 1;
+      let live = self.live();
+      assert!(live <= TABLE_SIZE, "local reference table overflow");
+      let mut peak = self.peak.borrow_mut();
+      *peak = (*peak).max(live);
+    }
+
+    pub fn live(&self) -> usize {
+      self.frames.borrow().iter().sum()
+    }
+
+    pub fn depth(&self) -> usize {
+      self.frames.borrow().len()
+    }
+  }
+
+  impl LocalFrames for MockEnv {
+    fn push_frame(&self, _capacity: i32) -> Result<(), WiredError> {
+      self.frames.borrow_mut().push(0);
+      Ok(())
+    }
+
+    fn pop_frame(&self) {
+      let mut frames = self.frames.borrow_mut();
+      assert!(frames.len() > 1, "popped the native call's frame");
+      frames.pop();
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::mock::MockEnv;
+  use super::*;
+  use crate::error::JavaOp;
+  use std::panic::{catch_unwind, AssertUnwindSafe};
+
+  // Stands in for an update, which makes a string and a callback object
+  fn update(env: &MockEnv) -> Result<(), WiredError> {
+    env.new_local_ref();
+    env.new_local_ref();
+    Ok(())
+  }
+
+  #[test]
+  fn many_updates_in_one_native_call() {
+    // A signal firing every 15ms for a few minutes, all inside one long poll
+    let env = MockEnv::new();
+    for_each_in_frame(&env, 2, 0..10_000, |_| update(&env)).unwrap();
+    assert_eq!(env.live(), 0);
+    assert_eq!(*env.peak.borrow(), 2);
+
+    // The same updates without frames overflow the table
+    let env = MockEnv::new();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+      for _ in 0..10_000 {
+        update(&env).unwrap();
+      }
+    }));
+    assert!(result.is_err());
+  }
+
+  #[test]
+  fn frames_are_popped_on_errors_and_panics() {
+    let env = MockEnv::new();
+    let result = for_each_in_frame(&env, 2, 0..10, |i| {
+      update(&env)?;
+      if i == 5 {
+        return Err(WiredError::JavaException {
+          class: "java.lang.IllegalArgumentException".into(),
+          message: None,
+          op: JavaOp::call("setRating"),
+        });
+      }
+      Ok(())
+    });
+    assert!(result.is_err());
+    assert_eq!((env.depth(), env.live()), (1, 0));
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+      with_local_frame(&env, 2, || -> Result<(), WiredError> {
+        update(&env)?;
+        panic!("oops")
+      })
+    }));
+    assert!(result.is_err());
+    assert_eq!((env.depth(), env.live()), (1, 0));
+  }
+}
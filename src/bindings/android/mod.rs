@@ -1,2 +1,7 @@
 pub mod views;
 pub mod callback;
+pub mod exceptions;
+pub mod frame;
+pub mod jni_cache;
+pub mod local_frame;
+pub mod lifecycle;
@@ -32,17 +32,17 @@ impl Button {
 impl SetText for Button {}
 impl SetTextSize for Button {}
 impl OnPress for Button {}
+impl Padding for Button {}
+impl Margin for Button {}
 
 impl Composable for Button {
   fn compose(&mut self, composer: &mut Composer) {
     if let Some(on_press) = self.on_press.take() {
       info!("REGISTERING in RUST");
-      let cb: Box<dyn Any + Send> = Box::new(Some(Callback {
-        f: Arc::new(on_press),
-      }));
+      let cb = Callback::new(move |()| on_press());
       self
         .inner
-        .update_prop("on_press", cb)
+        .update_prop(PropKey::ON_PRESS, cb)
         .expect("NO native android view");
     }
     composer
@@ -0,0 +1,123 @@
+use super::*;
+use crate::bindings::callback::Failure;
+use crate::bindings::image::ImageSource;
+use crate::style::{PropEnum, ScaleMode};
+
+#[derive(UpdateProp)]
+pub struct Image {
+  inner: PlatformView,
+  after_remove: AttachedFutures,
+  on_load: Option<Box<dyn Fn() + Send + Sync>>,
+  on_error: Option<Box<dyn Fn(String) + Send + Sync>>,
+  // The host loads a source as soon as it gets it, so sources wait for compose to have the
+  // callbacks set first
+  source: Option<ImageSource>,
+  source_updates: Option<BoxFuture<'static, ()>>,
+}
+
+impl Default for Image {
+  fn default() -> Self {
+    Image {
+      inner: PlatformView::new(create_wired_native_view("ImageView")),
+      after_remove: vec![],
+      on_load: None,
+      on_error: None,
+      source: None,
+      source_updates: None,
+    }
+  }
+}
+
+impl Image {
+  pub fn new(source: ImageSource) -> Self {
+    Self::default().source(source)
+  }
+
+  pub fn source(mut self, source: ImageSource) -> Self {
+    self.source = Some(source);
+    self
+  }
+
+  pub fn source_signal<S>(mut self, s: S) -> Self
+  where
+    S: 'static + Signal<Item = ImageSource> + Send,
+  {
+    let mut inner = self.inner.clone();
+    let f = s.for_each(move |source| {
+      source
+        .apply(&mut inner)
+        .expect("Couldn't update image source");
+      ready(())
+    });
+    self.source_updates = Some(f.boxed());
+    self
+  }
+
+  pub fn scale_mode(mut self, scale_mode: ScaleMode) -> Self {
+    self
+      .update_prop(PropKey::SCALE_MODE, scale_mode.code())
+      .expect("Couldn't update scale_mode");
+    self
+  }
+
+  /// Drawable resource shown until the source has loaded
+  pub fn placeholder<S: Into<String>>(mut self, resource: S) -> Self {
+    self
+      .update_prop(PropKey::PLACEHOLDER, resource.into())
+      .expect("Couldn't update placeholder");
+    self
+  }
+
+  pub fn on_load<F>(mut self, f: F) -> Self
+  where
+    F: Fn() + Send + Sync + 'static,
+  {
+    self.on_load = Some(Box::new(f));
+    self
+  }
+
+  /// Called with a description of what went wrong when a source couldn't be loaded
+  pub fn on_error<F>(mut self, f: F) -> Self
+  where
+    F: Fn(String) + Send + Sync + 'static,
+  {
+    self.on_error = Some(Box::new(f));
+    self
+  }
+}
+
+impl SetHeightWidth for Image {}
+impl Padding for Image {}
+impl Margin for Image {}
+
+impl Composable for Image {
+  fn compose(&mut self, composer: &mut Composer) {
+    if let Some(on_load) = self.on_load.take() {
+      let cb = Callback::new(move |()| on_load());
+      self
+        .inner
+        .update_prop(PropKey::ON_LOAD, cb)
+        .expect("NO native android view");
+    }
+    if let Some(on_error) = self.on_error.take() {
+      let cb = Callback::new(move |failure: Failure| on_error(failure.reason));
+      self
+        .inner
+        .update_prop(PropKey::ON_ERROR, cb)
+        .expect("NO native android view");
+    }
+    if let Some(source) = self.source.take() {
+      source
+        .apply(&mut self.inner)
+        .expect("Couldn't update image source");
+    }
+    if let Some(f) = self.source_updates.take() {
+      self.after_remove.push(spawn_future(f));
+    }
+    let mut after_remove = vec![];
+    std::mem::swap(&mut self.after_remove, &mut after_remove);
+    composer
+      .add_view_with_futures(&mut self.inner, Some(after_remove))
+      .expect("Couldn't add image view");
+  }
+}
@@ -0,0 +1,257 @@
+use super::*;
+use crate::bindings::android::exceptions::check;
+use crate::bindings::android::local_frame::with_local_frame;
+use crate::error::{JavaOp, WiredError};
+
+/// A value for a NativeView setter. Its type picks the setter's overload.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NativeValue {
+  Bool(bool),
+  Int(i32),
+  Long(i64),
+  Float(f32),
+  Double(f64),
+  String(String),
+}
+
+macro_rules! native_value_from {
+  ($($t:ty => $variant:ident),*) => {
+    $(
+      impl From<$t> for NativeValue {
+        fn from(v: $t) -> Self {
+          NativeValue::$variant(v.into())
+        }
+      }
+    )*
+  };
+}
+
+native_value_from!(
+  bool => Bool,
+  i32 => Int,
+  i64 => Long,
+  f32 => Float,
+  f64 => Double,
+  String => String,
+  &str => String
+);
+
+/// Any Android view, made from its class name. It's an escape hatch for platform widgets that
+/// don't have a wrapper yet: props are set by calling the view's setters by name, e.g.
+/// `NativeView::new("android.widget.RatingBar").set("setNumStars", 5)`.
+#[derive(UpdateProp)]
+pub struct NativeView {
+  pub(crate) inner: PlatformView,
+  after_remove: AttachedFutures,
+  class_name: String,
+  /// Whether the view is a ViewGroup, only those can have children
+  container: bool,
+}
+
+impl NativeView {
+  pub fn new<S: Into<String>>(class_name: S) -> Self {
+    let class_name = class_name.into();
+    let (view, container) = create_native_view(&class_name);
+    NativeView {
+      inner: PlatformView::new(view),
+      after_remove: vec![],
+      class_name,
+      container,
+    }
+  }
+
+  pub fn is_container(&self) -> bool {
+    self.container
+  }
+
+  /// Calls `setter` on the view with `v`, e.g. `.set("setRating", 3.5)`
+  pub fn set<V: Into<NativeValue>>(mut self, setter: &str, v: V) -> Self {
+    set_native_value(&mut self.inner, setter_key(setter), v.into())
+      .expect("Couldn't call setter on native view");
+    self
+  }
+
+  pub fn set_signal<S, V>(mut self, setter: &str, s: S) -> Self
+  where
+    S: 'static + Signal<Item = V> + Send,
+    V: Into<NativeValue>,
+  {
+    let key = setter_key(setter);
+    let mut inner = self.inner.clone();
+    let f = s.for_each(move |v| {
+      set_native_value(&mut inner, key, v.into()).expect("Couldn't call setter on native view");
+      ready(())
+    });
+    self.after_remove.push(spawn_future(f));
+    self
+  }
+}
+
+// The host calls the setter named by the key, with the overload that matches the value's type
+fn set_native_value(
+  view: &mut PlatformView,
+  key: PropKey,
+  v: NativeValue,
+) -> Result<(), WiredError> {
+  match v {
+    NativeValue::Bool(v) => view.set_prop(key, v),
+    NativeValue::Int(v) => view.set_prop(key, v),
+    NativeValue::Long(v) => view.set_prop(key, v),
+    NativeValue::Float(v) => view.set_prop(key, v),
+    NativeValue::Double(v) => view.set_prop(key, v),
+    NativeValue::String(v) => view.set_prop(key, v),
+  }
+}
+
+// Setters aren't in the widget spec, so the first use of one registers it and tells the host
+// which name its ID stands for
+fn setter_key(setter: &str) -> PropKey {
+  intern_setter(setter, |key| {
+    with_view_factory(|view_factory, env| {
+      with_local_frame(env, 1, || {
+        let name = env.new_string(setter)?;
+        let result = env.call_method(
+          view_factory.inner.as_obj(),
+          "registerPropKey",
+          "(ILjava/lang/String;)V",
+          &[JValue::Int(key.id()), JValue::Object(name.into())],
+        );
+        check(env, result, || {
+          JavaOp::call("registerPropKey").with_key(setter)
+        })?;
+        Ok(())
+      })
+    })
+    .expect("Couldn't register setter with the host");
+  })
+}
+
+// Reuses the setter's key if it has one, `announce` is only called for a new one
+fn intern_setter<F: FnOnce(PropKey)>(setter: &str, announce: F) -> PropKey {
+  if let Ok(key) = PropKey::intern(setter) {
+    return key;
+  }
+  let key = PropKey::register(setter);
+  announce(key);
+  key
+}
+
+// The view itself is made when the frame is flushed, but whether it can have children is needed
+// now, so that's asked of the class right away
+fn create_native_view(class_name: &str) -> (RecordingView, bool) {
+  let container = with_view_factory(|view_factory, env| {
+    with_local_frame(env, 1, || {
+      let class_name_string = env.new_string(class_name)?;
+      // Throws if there's no such class
+      let result = env.call_method(
+        view_factory.inner.as_obj(),
+        "isViewGroupClass",
+        "(Ljava/lang/String;)Z",
+        &[JValue::Object(class_name_string.into())],
+      );
+      let container = check(env, result, || {
+        JavaOp::call("isViewGroupClass").on_view(class_name)
+      })?;
+      Ok(container.z()?)
+    })
+  })
+  .unwrap_or_else(|e| panic!("Couldn't look up native view class {}: {}", class_name, e));
+  (
+    RecordingView::create_native(&frame::buffer(), class_name),
+    container,
+  )
+}
+
+// The host handles these before falling back to setters
+impl SetXY for NativeView {}
+impl SetHeightWidth for NativeView {}
+impl Padding for NativeView {}
+impl Margin for NativeView {}
+
+impl ParentWith for NativeView {
+  fn with<F>(mut self, f: F) -> Self
+  where
+    F: FnOnce(),
+  {
+    assert!(
+      self.container,
+      "{} isn't a ViewGroup, so it can't have children",
+      self.class_name
+    );
+    with_parent(&mut self.inner, f);
+    self
+  }
+}
+
+impl Composable for NativeView {
+  fn compose(&mut self, composer: &mut Composer) {
+    let mut after_remove = vec![];
+    std::mem::swap(&mut self.after_remove, &mut after_remove);
+    composer
+      .add_view_with_futures(&mut self.inner, Some(after_remove))
+      .expect("Couldn't add native view");
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::bindings::test::DummyPlatformView;
+
+  #[test]
+  fn values_convert_to_their_overload() {
+    assert_eq!(NativeValue::from(true), NativeValue::Bool(true));
+    assert_eq!(NativeValue::from(5), NativeValue::Int(5));
+    assert_eq!(NativeValue::from(5i64), NativeValue::Long(5));
+    assert_eq!(NativeValue::from(3.5f32), NativeValue::Float(3.5));
+    assert_eq!(NativeValue::from(3.5f64), NativeValue::Double(3.5));
+    assert_eq!(NativeValue::from("a"), NativeValue::String("a".into()));
+    assert_eq!(
+      NativeValue::from(String::from("a")),
+      NativeValue::String("a".into())
+    );
+  }
+
+  #[test]
+  fn values_keep_their_type_on_the_way_down() {
+    let mut view = DummyPlatformView::new("Native");
+    let values = vec![
+      ("setA", NativeValue::Bool(true)),
+      ("setB", NativeValue::Int(1)),
+      ("setC", NativeValue::Long(1)),
+      ("setD", NativeValue::Float(1.0)),
+      ("setE", NativeValue::Double(1.0)),
+      ("setF", NativeValue::String("1".into())),
+    ];
+    for (setter, v) in values {
+      set_native_value(&mut view, intern_setter(setter, |_| {}), v).expect("Couldn't set value");
+    }
+
+    let raw_view = view.get_raw_view().expect("view is there");
+    let raw_view = raw_view.lock().unwrap();
+    let props = raw_view
+      .downcast_ref::<Vec<(String, Box<dyn Any + Send>)>>()
+      .expect("Not a dummy view");
+    assert!(props[0].1.is::<bool>());
+    assert!(props[1].1.is::<i32>());
+    assert!(props[2].1.is::<i64>());
+    assert!(props[3].1.is::<f32>());
+    assert!(props[4].1.is::<f64>());
+    assert!(props[5].1.is::<String>());
+  }
+
+  #[test]
+  fn setters_are_registered_once() {
+    let mut announced = vec![];
+    let key = intern_setter("setNumStars", |key| announced.push(key));
+    assert_eq!(intern_setter("setNumStars", |key| announced.push(key)), key);
+    assert_eq!(announced, vec![key]);
+    assert!(!key.is_static());
+    assert_eq!(key.name(), "setNumStars");
+
+    // The host already knows the spec's keys
+    let height = intern_setter("height", |key| announced.push(key));
+    assert!(height.is_static());
+    assert_eq!(announced.len(), 1);
+  }
+}
@@ -0,0 +1,18 @@
+use super::*;
+use crate::navigation::{RouteState, Router};
+
+impl<R> Router<R>
+where
+  R: Clone + Send + Sync + 'static,
+{
+  /// Composes the top route inside a StackLayout, which is added to the current parent when it's
+  /// dropped like any other widget
+  pub fn build<F>(&self, render: F) -> StackLayout
+  where
+    F: Fn(&R, &RouteState) + Send + 'static,
+  {
+    let layout = StackLayout::new();
+    self.compose_routes(layout.inner.clone(), render);
+    layout
+  }
+}
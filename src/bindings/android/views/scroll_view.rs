@@ -0,0 +1,109 @@
+use super::*;
+use crate::bindings::callback::Callback;
+use crate::style::{Dimension, Orientation};
+
+/// A vertically scrolling container. The scroll position is reported back as a signal.
+#[derive(UpdateProp)]
+pub struct ScrollView {
+  pub(crate) inner: PlatformView,
+  after_remove: AttachedFutures,
+  scroll_offset: Mutable<f32>,
+  on_scroll: Option<Box<dyn Fn(f32) + Send + Sync>>,
+}
+
+/// Lets you move the scroll position after the ScrollView has been composed
+#[derive(Clone)]
+pub struct ScrollViewHandle {
+  inner: PlatformView,
+}
+
+impl ScrollViewHandle {
+  pub fn scroll_to<D: Into<Dimension>>(&self, offset: D, animated: bool) {
+    let mut inner = self.inner.clone();
+    let key = if animated {
+      PropKey::SMOOTH_SCROLL_TO
+    } else {
+      PropKey::SCROLL_TO
+    };
+    inner
+      .update_prop(key, offset.into().resolve(Orientation::Vertical))
+      .expect("Couldn't scroll");
+  }
+}
+
+impl Default for ScrollView {
+  fn default() -> Self {
+    ScrollView::new()
+  }
+}
+
+impl ScrollView {
+  pub fn new() -> Self {
+    ScrollView {
+      inner: PlatformView::new(create_wired_native_view("ScrollView")),
+      after_remove: vec![],
+      scroll_offset: Mutable::new(0.0),
+      on_scroll: None,
+    }
+  }
+
+  /// Scroll offset from the top, in pixels
+  pub fn scroll_offset_signal(&self) -> impl Signal<Item = f32> + Send {
+    self.scroll_offset.signal()
+  }
+
+  pub fn on_scroll<F>(mut self, f: F) -> Self
+  where
+    F: Fn(f32) + Send + Sync + 'static,
+  {
+    self.on_scroll = Some(Box::new(f));
+    self
+  }
+
+  pub fn handle(&self) -> ScrollViewHandle {
+    ScrollViewHandle {
+      inner: self.inner.clone(),
+    }
+  }
+}
+
+impl SetHeightWidth for ScrollView {}
+impl Padding for ScrollView {}
+impl Margin for ScrollView {}
+
+impl ParentWith for ScrollView {
+  fn with<F>(mut self, f: F) -> Self
+  where
+    F: FnOnce(),
+  {
+    with_parent(&mut self.inner, f);
+    self
+  }
+}
+
+impl Composable for ScrollView {
+  fn compose(&mut self, composer: &mut Composer) {
+    // We always listen so the offset signal stays up to date
+    let scroll_offset = self.scroll_offset.clone();
+    let on_scroll = self.on_scroll.take();
+    let cb = Callback::new(move |offset: f32| {
+      {
+        let mut lock = scroll_offset.lock_mut();
+        *lock = offset;
+      }
+      if let Some(on_scroll) = on_scroll.as_ref() {
+        on_scroll(offset);
+      }
+    });
+    self
+      .inner
+      .update_prop(PropKey::ON_SCROLL, cb)
+      .expect("NO native android view");
+
+    let mut after_remove = vec![];
+    std::mem::swap(&mut self.after_remove, &mut after_remove);
+    composer
+      .add_view_with_futures(&mut self.inner, Some(after_remove))
+      .expect("Couldn't add scroll view");
+  }
+}
@@ -0,0 +1,121 @@
+use super::*;
+use crate::bindings::callback::Callback;
+use crate::bindings::echo_guard::EchoGuard;
+use crate::style::{KeyboardType, PropEnum};
+
+#[derive(UpdateProp)]
+pub struct TextInput {
+  inner: PlatformView,
+  after_remove: AttachedFutures,
+  on_change: Option<Box<dyn Fn(String) + Send + Sync>>,
+  echo_guard: EchoGuard<String>,
+}
+
+impl Default for TextInput {
+  fn default() -> Self {
+    TextInput {
+      inner: PlatformView::new(create_wired_native_view("TextInputView")),
+      after_remove: vec![],
+      on_change: None,
+      echo_guard: EchoGuard::new(),
+    }
+  }
+}
+
+impl TextInput {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Two way binding. The input shows the value, and edits in the input are written back to it.
+  pub fn bind(self, value: Mutable<String>) -> Self {
+    let value_clone = value.clone();
+    self
+      .value_signal(value.signal_cloned())
+      .on_change(move |text| {
+        let mut lock = value_clone.lock_mut();
+        *lock = text;
+      })
+  }
+
+  /// Text to show in the input. Values that the input itself just reported are not sent back.
+  pub fn value_signal<S>(mut self, s: S) -> Self
+  where
+    S: 'static + Signal<Item = String> + Send,
+  {
+    let mut inner = self.inner.clone();
+    let echo_guard = self.echo_guard.clone();
+    let f = s.for_each(move |text| {
+      if echo_guard.should_forward(&text) {
+        inner
+          .update_prop(PropKey::VALUE, text)
+          .expect("Couldn't update value");
+      }
+      ready(())
+    });
+    self.after_remove.push(spawn_future(f));
+    self
+  }
+
+  pub fn on_change<F>(mut self, f: F) -> Self
+  where
+    F: Fn(String) + Send + Sync + 'static,
+  {
+    self.on_change = Some(Box::new(f));
+    self
+  }
+
+  pub fn placeholder<S: Into<String>>(mut self, s: S) -> Self {
+    self
+      .update_prop(PropKey::PLACEHOLDER, s.into())
+      .expect("Couldn't update placeholder");
+    self
+  }
+
+  pub fn keyboard_type(mut self, k: KeyboardType) -> Self {
+    self
+      .update_prop(PropKey::KEYBOARD_TYPE, k.code())
+      .expect("Couldn't update keyboard_type");
+    self
+  }
+
+  /// Hide what's typed, for passwords
+  pub fn secure(mut self, secure: bool) -> Self {
+    self
+      .update_prop(PropKey::SECURE, secure)
+      .expect("Couldn't update secure");
+    self
+  }
+
+  pub fn max_length(mut self, max_length: i32) -> Self {
+    self
+      .update_prop(PropKey::MAX_LENGTH, max_length)
+      .expect("Couldn't update max_length");
+    self
+  }
+}
+
+impl SetTextSize for TextInput {}
+impl Padding for TextInput {}
+impl Margin for TextInput {}
+
+impl Composable for TextInput {
+  fn compose(&mut self, composer: &mut Composer) {
+    if let Some(on_change) = self.on_change.take() {
+      let echo_guard = self.echo_guard.clone();
+      let cb = Callback::new(move |text: String| {
+        echo_guard.from_view(&text);
+        on_change(text)
+      });
+      self
+        .inner
+        .update_prop(PropKey::ON_CHANGE, cb)
+        .expect("NO native android view");
+    }
+    let mut after_remove = vec![];
+    std::mem::swap(&mut self.after_remove, &mut after_remove);
+    composer
+      .add_view_with_futures(&mut self.inner, Some(after_remove))
+      .expect("Couldn't add text input view");
+  }
+}
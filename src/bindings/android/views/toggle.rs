@@ -0,0 +1,210 @@
+use super::*;
+use crate::bindings::callback::Callback;
+use crate::bindings::echo_guard::EchoGuard;
+
+// Checkbox and Switch only differ in what the host draws, so they share an implementation.
+macro_rules! toggle_widget {
+  ($name:ident, $view_name:expr) => {
+    #[derive(UpdateProp)]
+    pub struct $name {
+      inner: PlatformView,
+      after_remove: AttachedFutures,
+      on_change: Option<Box<dyn Fn(bool) + Send + Sync>>,
+      echo_guard: EchoGuard<bool>,
+    }
+
+    impl Default for $name {
+      fn default() -> Self {
+        $name {
+          inner: PlatformView::new(create_wired_native_view($view_name)),
+          after_remove: vec![],
+          on_change: None,
+          echo_guard: EchoGuard::new(),
+        }
+      }
+    }
+
+    impl $name {
+      pub fn new() -> Self {
+        Self::default()
+      }
+
+      /// Two way binding. The view shows the value, and taps are written back to it.
+      pub fn bind(self, value: Mutable<bool>) -> Self {
+        let value_clone = value.clone();
+        self.checked_signal(value.signal()).on_change(move |checked| {
+          let mut lock = value_clone.lock_mut();
+          *lock = checked;
+        })
+      }
+
+      pub fn checked(mut self, checked: bool) -> Self {
+        self
+          .update_prop(PropKey::CHECKED, checked)
+          .expect("Couldn't update checked");
+        self
+      }
+
+      pub fn checked_signal<S>(mut self, s: S) -> Self
+      where
+        S: 'static + Signal<Item = bool> + Send,
+      {
+        let mut inner = self.inner.clone();
+        let echo_guard = self.echo_guard.clone();
+        let f = s.for_each(move |checked| {
+          if echo_guard.should_forward(&checked) {
+            inner
+              .update_prop(PropKey::CHECKED, checked)
+              .expect("Couldn't update checked");
+          }
+          ready(())
+        });
+        self.after_remove.push(spawn_future(f));
+        self
+      }
+
+      pub fn on_change<F>(mut self, f: F) -> Self
+      where
+        F: Fn(bool) + Send + Sync + 'static,
+      {
+        self.on_change = Some(Box::new(f));
+        self
+      }
+    }
+
+    impl SetText for $name {}
+    impl SetTextSize for $name {}
+    impl Padding for $name {}
+    impl Margin for $name {}
+
+    impl Composable for $name {
+      fn compose(&mut self, composer: &mut Composer) {
+        if let Some(on_change) = self.on_change.take() {
+          let echo_guard = self.echo_guard.clone();
+          let cb = Callback::new(move |checked: bool| {
+            echo_guard.from_view(&checked);
+            on_change(checked)
+          });
+          self
+            .inner
+            .update_prop(PropKey::ON_CHANGE, cb)
+            .expect("NO native android view");
+        }
+        let mut after_remove = vec![];
+        std::mem::swap(&mut self.after_remove, &mut after_remove);
+        composer
+          .add_view_with_futures(&mut self.inner, Some(after_remove))
+          .expect("Couldn't add toggle view");
+      }
+    }
+  };
+}
+
+toggle_widget!(Checkbox, "CheckboxView");
+toggle_widget!(Switch, "SwitchView");
+
+/// Pick one of a set of options. The host only sees the labels and the selected index.
+pub struct RadioGroup<T> {
+  inner: PlatformView,
+  after_remove: AttachedFutures,
+  options: Arc<Vec<T>>,
+  on_change: Option<Box<dyn Fn(i32) + Send + Sync>>,
+  echo_guard: EchoGuard<i32>,
+}
+
+fn index_of<T: PartialEq>(options: &[T], v: &T) -> i32 {
+  options
+    .iter()
+    .position(|option| option == v)
+    .map(|i| i as i32)
+    .unwrap_or(-1)
+}
+
+impl<T> RadioGroup<T>
+where
+  T: Clone + PartialEq + Send + Sync + 'static,
+{
+  pub fn new<L: Into<String>>(options: Vec<(T, L)>) -> Self {
+    let (options, labels): (Vec<T>, Vec<String>) = options
+      .into_iter()
+      .map(|(option, label)| (option, label.into()))
+      .unzip();
+    let mut inner = PlatformView::new(create_wired_native_view("RadioGroupView"));
+    inner
+      .update_prop(PropKey::OPTIONS, labels)
+      .expect("Couldn't update options");
+    RadioGroup {
+      inner,
+      after_remove: vec![],
+      options: Arc::new(options),
+      on_change: None,
+      echo_guard: EchoGuard::new(),
+    }
+  }
+
+  /// Two way binding. The group shows the value, and selections are written back to it.
+  pub fn bind(self, value: Mutable<T>) -> Self {
+    let value_clone = value.clone();
+    self
+      .selected_signal(value.signal_cloned())
+      .on_change(move |selected| {
+        let mut lock = value_clone.lock_mut();
+        *lock = selected;
+      })
+  }
+
+  /// Values that aren't one of the options clear the selection
+  pub fn selected_signal<S>(mut self, s: S) -> Self
+  where
+    S: 'static + Signal<Item = T> + Send,
+  {
+    let mut inner = self.inner.clone();
+    let options = self.options.clone();
+    let echo_guard = self.echo_guard.clone();
+    let f = s.for_each(move |selected| {
+      let idx = index_of(&options, &selected);
+      if echo_guard.should_forward(&idx) {
+        inner
+          .update_prop(PropKey::SELECTED, idx)
+          .expect("Couldn't update selected");
+      }
+      ready(())
+    });
+    self.after_remove.push(spawn_future(f));
+    self
+  }
+
+  pub fn on_change<F>(mut self, f: F) -> Self
+  where
+    F: Fn(T) + Send + Sync + 'static,
+  {
+    let options = self.options.clone();
+    self.on_change = Some(Box::new(move |idx: i32| {
+      if let Some(selected) = options.get(idx as usize) {
+        f(selected.clone())
+      }
+    }));
+    self
+  }
+}
+
+impl<T> Composable for RadioGroup<T> {
+  fn compose(&mut self, composer: &mut Composer) {
+    if let Some(on_change) = self.on_change.take() {
+      let echo_guard = self.echo_guard.clone();
+      let cb = Callback::new(move |idx: i32| {
+        echo_guard.from_view(&idx);
+        on_change(idx)
+      });
+      self
+        .inner
+        .update_prop(PropKey::ON_CHANGE, cb)
+        .expect("NO native android view");
+    }
+    let mut after_remove = vec![];
+    std::mem::swap(&mut self.after_remove, &mut after_remove);
+    composer
+      .add_view_with_futures(&mut self.inner, Some(after_remove))
+      .expect("Couldn't add radio group view");
+  }
+}
@@ -0,0 +1,36 @@
+use super::*;
+use crate::bindings::virtual_list::{Row, VirtualList, VirtualListHandle};
+use crate::style::Orientation;
+
+impl<T> VirtualList<T>
+where
+  T: Clone + PartialEq + Send + Sync + 'static,
+{
+  /// Composes the list as a ScrollView in the current parent
+  pub fn build<B>(self, builder: B) -> VirtualListHandle
+  where
+    B: Fn(Row<T>),
+  {
+    let row_height = self.row_height.resolve(Orientation::Vertical);
+    let scroll_view = ScrollView::new().height(self.viewport_height);
+    let scroll_handle = scroll_view.handle();
+    let scroll_offset = scroll_view.scroll_offset_signal();
+
+    let mut visible_range = None;
+    scroll_view.with(|| {
+      StackLayout::new().with(|| {
+        visible_range = Some(self.compose_rows(
+          scroll_offset,
+          || PlatformView::new(create_wired_native_view("StackLayoutView")),
+          builder,
+        ));
+      });
+    });
+
+    VirtualListHandle::new(
+      visible_range.expect("Rows were composed"),
+      row_height,
+      Box::new(move |offset, animated| scroll_handle.scroll_to(offset, animated)),
+    )
+  }
+}
@@ -1,5 +1,329 @@
+#[cfg(target_os = "android")]
+use crate::android_executor::spawn_future;
+#[cfg(not(target_os = "android"))]
+use crate::ui_tree::spawn_future;
+use discard::DiscardOnDrop;
+use futures::future::lazy;
+use std::any::Any;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
-pub struct Callback {
-  pub(crate) f: Arc<Box<dyn Fn() + Send + Sync>>,
-}
\ No newline at end of file
+prop_enum! {
+  /// Tells the host side dispatcher which payload fields mean something
+  pub enum EventKind {
+    Press = 0,
+    LongPress = 1,
+    TextChanged = 2,
+    ValueChanged = 3,
+    Toggled = 4,
+    Selected = 5,
+    Failed = 6,
+  }
+}
+
+/// Where a press happened, in pixels relative to the view
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PressEvent {
+  pub x: f32,
+  pub y: f32,
+}
+
+/// What a view reports when something it tried didn't work, e.g. an image that didn't load
+#[derive(Clone, Debug, PartialEq)]
+pub struct Failure {
+  pub reason: String,
+}
+
+/// Everything a view can report back to Rust
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+  Press(PressEvent),
+  LongPress,
+  TextChanged(String),
+  ValueChanged(f32),
+  Toggled(bool),
+  Selected(i32),
+  /// Something the view tried to do didn't work (e.g. an image didn't load)
+  Failed(String),
+}
+
+impl Event {
+  /// The host sends every event as the same handful of fields, `kind` says which ones to read.
+  pub fn decode(kind: i32, x: f32, y: f32, n: i64, text: Option<String>) -> Option<Event> {
+    use crate::style::PropEnum;
+
+    let event = match EventKind::from_code(kind)? {
+      EventKind::Press => Event::Press(PressEvent { x, y }),
+      EventKind::LongPress => Event::LongPress,
+      EventKind::TextChanged => Event::TextChanged(text.unwrap_or_default()),
+      EventKind::ValueChanged => Event::ValueChanged(x),
+      EventKind::Toggled => Event::Toggled(n != 0),
+      EventKind::Selected => Event::Selected(n as i32),
+      EventKind::Failed => Event::Failed(text.unwrap_or_default()),
+    };
+    Some(event)
+  }
+}
+
+/// Payloads a callback can ask for. Returns None when the event doesn't carry one.
+pub trait FromEvent: Sized {
+  fn from_event(e: Event) -> Option<Self>;
+}
+
+/// For callbacks that only care that something happened, like a press or an image loading
+impl FromEvent for () {
+  fn from_event(e: Event) -> Option<Self> {
+    match e {
+      Event::Press(_) | Event::LongPress => Some(()),
+      _ => None,
+    }
+  }
+}
+
+impl FromEvent for Event {
+  fn from_event(e: Event) -> Option<Self> {
+    Some(e)
+  }
+}
+
+impl FromEvent for PressEvent {
+  fn from_event(e: Event) -> Option<Self> {
+    match e {
+      Event::Press(p) => Some(p),
+      _ => None,
+    }
+  }
+}
+
+impl FromEvent for String {
+  fn from_event(e: Event) -> Option<Self> {
+    match e {
+      Event::TextChanged(s) => Some(s),
+      _ => None,
+    }
+  }
+}
+
+impl FromEvent for Failure {
+  fn from_event(e: Event) -> Option<Self> {
+    match e {
+      Event::Failed(reason) => Some(Failure { reason }),
+      _ => None,
+    }
+  }
+}
+
+impl FromEvent for f32 {
+  fn from_event(e: Event) -> Option<Self> {
+    match e {
+      Event::ValueChanged(v) => Some(v),
+      _ => None,
+    }
+  }
+}
+
+impl FromEvent for bool {
+  fn from_event(e: Event) -> Option<Self> {
+    match e {
+      Event::Toggled(b) => Some(b),
+      _ => None,
+    }
+  }
+}
+
+impl FromEvent for i32 {
+  fn from_event(e: Event) -> Option<Self> {
+    match e {
+      Event::Selected(i) => Some(i),
+      _ => None,
+    }
+  }
+}
+
+pub struct Callback<E = ()> {
+  pub(crate) f: Arc<Box<dyn Fn(E) + Send + Sync>>,
+}
+
+impl<E> Clone for Callback<E> {
+  fn clone(&self) -> Self {
+    Callback { f: self.f.clone() }
+  }
+}
+
+impl<E> Callback<E> {
+  pub fn new<F>(f: F) -> Self
+  where
+    F: Fn(E) + Send + Sync + 'static,
+  {
+    Callback {
+      f: Arc::new(Box::new(f)),
+    }
+  }
+}
+
+impl<E: FromEvent + 'static> Callback<E> {
+  /// Forgets the payload type, so that every callback looks the same to the host
+  pub fn into_handler(self) -> EventHandler {
+    let f = self.f;
+    EventHandler {
+      live: Arc::new(LiveToken::new()),
+      f: Arc::new(move |e: Event| match E::from_event(e.clone()) {
+        Some(payload) => f(payload),
+        None => warn!("Callback can't handle {:?}", e),
+      }),
+    }
+  }
+}
+
+/// A callback with its payload type erased. This is what the host actually holds on to.
+/// Clones share the same closure, it's freed when the last one goes.
+#[derive(Clone)]
+pub struct EventHandler {
+  f: Arc<dyn Fn(Event) + Send + Sync>,
+  live: Arc<LiveToken>,
+}
+
+impl EventHandler {
+  pub fn call(&self, e: Event) {
+    (self.f)(e)
+  }
+
+  /// A handle that only reaches this handler while something else keeps it alive, like the
+  /// RustCallback objects the Android host hands its views. It doesn't count as live itself.
+  pub(crate) fn downgrade(&self) -> EventHandler {
+    let f = Arc::downgrade(&self.f);
+    EventHandler {
+      f: Arc::new(move |e: Event| match f.upgrade() {
+        Some(f) => f(e),
+        None => debug!("Dropping event for a freed callback"),
+      }),
+      live: Arc::new(LiveToken { count: None }),
+    }
+  }
+
+  /// How hosts should deliver events. The callback runs later on the executor, outside of
+  /// whatever the host was doing (and any composer borrow), and a panic in it is logged rather
+  /// than unwinding into the host.
+  pub fn dispatch(&self, e: Event) {
+    let handler = self.clone();
+    let f = lazy(move |_| {
+      let description = format!("{:?}", e);
+      if let Err(cause) = catch_unwind(AssertUnwindSafe(|| handler.call(e))) {
+        error!(
+          "Callback panicked handling {}: {}",
+          description,
+          panic_message(&cause)
+        );
+      }
+    });
+    DiscardOnDrop::leak(spawn_future(f));
+  }
+}
+
+pub(crate) fn panic_message(cause: &Box<dyn Any + Send>) -> String {
+  if let Some(s) = cause.downcast_ref::<&str>() {
+    s.to_string()
+  } else if let Some(s) = cause.downcast_ref::<String>() {
+    s.clone()
+  } else {
+    "unknown cause".into()
+  }
+}
+
+thread_local! {
+  static LIVE_CALLBACKS: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+}
+
+/// How many callbacks created on this thread haven't been freed yet. Counting per thread
+/// keeps tests that run side by side from seeing each other's callbacks.
+pub fn live_callbacks() -> usize {
+  LIVE_CALLBACKS.with(|live| live.load(Ordering::SeqCst))
+}
+
+// Counts a handler as live until it's dropped, on whichever thread that happens
+struct LiveToken {
+  count: Option<Arc<AtomicUsize>>,
+}
+
+impl LiveToken {
+  fn new() -> Self {
+    let count = LIVE_CALLBACKS.with(|live| live.clone());
+    count.fetch_add(1, Ordering::SeqCst);
+    LiveToken { count: Some(count) }
+  }
+}
+
+impl Drop for LiveToken {
+  fn drop(&mut self) {
+    if let Some(count) = &self.count {
+      count.fetch_sub(1, Ordering::SeqCst);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::style::PropEnum;
+  use std::sync::Mutex;
+
+  #[test]
+  fn decodes_typed_payloads() {
+    assert_eq!(
+      Event::decode(EventKind::Press.code(), 1.0, 2.0, 0, None),
+      Some(Event::Press(PressEvent { x: 1.0, y: 2.0 }))
+    );
+    assert_eq!(
+      Event::decode(EventKind::TextChanged.code(), 0.0, 0.0, 0, Some("hi".into())),
+      Some(Event::TextChanged("hi".into()))
+    );
+    assert_eq!(
+      Event::decode(EventKind::Toggled.code(), 0.0, 0.0, 1, None),
+      Some(Event::Toggled(true))
+    );
+    assert_eq!(Event::decode(99, 0.0, 0.0, 0, None), None);
+  }
+
+  #[test]
+  fn handler_delivers_payload() {
+    let seen = Arc::new(Mutex::new(vec![]));
+    let seen_clone = seen.clone();
+    let handler =
+      Callback::new(move |p: PressEvent| seen_clone.lock().unwrap().push(p.x)).into_handler();
+
+    handler.call(Event::Press(PressEvent { x: 3.0, y: 4.0 }));
+    // Not a press, so the callback isn't called
+    handler.call(Event::LongPress);
+
+    assert_eq!(*seen.lock().unwrap(), vec![3.0]);
+  }
+
+  #[test]
+  fn handler_drops_other_kinds() {
+    let seen = Arc::new(Mutex::new(vec![]));
+    let seen_clone = seen.clone();
+    let on_change =
+      Callback::new(move |text: String| seen_clone.lock().unwrap().push(text)).into_handler();
+    let pressed = Arc::new(Mutex::new(0));
+    let pressed_clone = pressed.clone();
+    let on_press = Callback::new(move |()| *pressed_clone.lock().unwrap() += 1).into_handler();
+
+    on_change.call(Event::TextChanged("hi".into()));
+    on_change.call(Event::Failed("Couldn't load".into()));
+    on_press.call(Event::Press(PressEvent { x: 0.0, y: 0.0 }));
+    on_press.call(Event::LongPress);
+    on_press.call(Event::TextChanged("hi".into()));
+    on_press.call(Event::Toggled(true));
+
+    assert_eq!(*seen.lock().unwrap(), vec![String::from("hi")]);
+    assert_eq!(*pressed.lock().unwrap(), 2);
+    // Failures have their own payload
+    assert_eq!(
+      Failure::from_event(Event::Failed("Couldn't load".into())),
+      Some(Failure {
+        reason: "Couldn't load".into()
+      })
+    );
+  }
+}
@@ -0,0 +1,1066 @@
+//! View operations serialized into one binary buffer per frame, so the host gets a single call
+//! instead of one per operation. `RecordingView` writes them, the host applies them, and
+//! `Replayer` applies them onto any `PlatformViewInner`, which is how the protocol is tested.
+//!
+//! Everything is little endian. A command is a one byte opcode and its fields, where views are
+//! u32 IDs, keys are i32 `PropKey` IDs, strings are a u32 byte length then UTF-8, byte arrays a
+//! u32 length then the bytes, and string arrays a u32 count then the strings.
+//!
+//! | opcode | command            | fields                              |
+//! |--------|--------------------|-------------------------------------|
+//! | 1      | create view        | view, kind: string                  |
+//! | 2      | create native view | view, class name: string            |
+//! | 3      | release view       | view                                |
+//! | 10..17 | set prop           | view, key, value (see `PropValue`)  |
+//! | 20     | set callback       | view, key, handler index: u32       |
+//! | 30     | append child       | parent, child                       |
+//! | 31     | insert child at    | parent, child, index: u32           |
+//! | 32     | remove child       | parent, child                       |
+//! | 33     | remove child index | parent, index: u32                  |
+//! | 34     | move child         | parent, child, index: u32           |
+//!
+//! Callbacks can't be serialized, so the buffer keeps their handlers on the side and commands
+//! refer to them by index.
+
+use crate::bindings::callback::{Callback, EventHandler};
+use crate::bindings::prop_key::PropKey;
+use crate::bindings::view_helpers::SetProp;
+use crate::error::WiredError;
+use crate::ui_tree::{PlatformView, PlatformViewInner};
+use std::any::Any;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::fmt::{self, Debug, Formatter};
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+pub type ViewId = u32;
+
+// ROOT_VIEW and the opcodes, from wired_codegen's command table that the host's is made from
+include!(concat!(env!("OUT_DIR"), "/command_ops.rs"));
+
+/// A prop value that can go in the buffer
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropValue {
+  F32(f32),
+  F64(f64),
+  I32(i32),
+  I64(i64),
+  Bool(bool),
+  String(String),
+  Bytes(Vec<u8>),
+  Strings(Vec<String>),
+}
+
+macro_rules! prop_value_from {
+  ($($t:ty => $variant:ident),*) => {
+    $(
+      impl From<$t> for PropValue {
+        fn from(v: $t) -> Self {
+          PropValue::$variant(v)
+        }
+      }
+    )*
+  };
+}
+
+prop_value_from!(
+  f32 => F32,
+  f64 => F64,
+  i32 => I32,
+  i64 => I64,
+  bool => Bool,
+  String => String,
+  Vec<u8> => Bytes,
+  Vec<String> => Strings
+);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+  CreateView {
+    view: ViewId,
+    kind: String,
+  },
+  CreateNativeView {
+    view: ViewId,
+    class_name: String,
+  },
+  /// Rust let go of the view, the host can forget its ID
+  ReleaseView {
+    view: ViewId,
+  },
+  SetProp {
+    view: ViewId,
+    key: PropKey,
+    value: PropValue,
+  },
+  SetCallback {
+    view: ViewId,
+    key: PropKey,
+    handler: usize,
+  },
+  AppendChild {
+    parent: ViewId,
+    child: ViewId,
+  },
+  InsertChildAt {
+    parent: ViewId,
+    child: ViewId,
+    idx: usize,
+  },
+  RemoveChild {
+    parent: ViewId,
+    child: ViewId,
+  },
+  RemoveChildIndex {
+    parent: ViewId,
+    idx: usize,
+  },
+  /// Puts a child that's already in `parent` at `idx`
+  MoveChild {
+    parent: ViewId,
+    child: ViewId,
+    idx: usize,
+  },
+}
+
+/// The commands for one frame. View IDs keep counting up across frames.
+pub struct CommandBuffer {
+  bytes: Vec<u8>,
+  handlers: Vec<EventHandler>,
+  next_view: ViewId,
+  /// Parent, child and offset of the last command, if it was a remove_child
+  last_removal: Option<(ViewId, ViewId, usize)>,
+}
+
+impl Default for CommandBuffer {
+  fn default() -> Self {
+    CommandBuffer::new()
+  }
+}
+
+impl Debug for CommandBuffer {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(
+      f,
+      "CommandBuffer ({} bytes, {} handlers)",
+      self.bytes.len(),
+      self.handlers.len()
+    )
+  }
+}
+
+impl CommandBuffer {
+  pub fn new() -> Self {
+    CommandBuffer {
+      bytes: vec![],
+      handlers: vec![],
+      next_view: ROOT_VIEW + 1,
+      last_removal: None,
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.bytes.is_empty()
+  }
+
+  pub fn bytes(&self) -> &[u8] {
+    &self.bytes
+  }
+
+  pub fn handlers(&self) -> &[EventHandler] {
+    &self.handlers
+  }
+
+  /// Hands over this frame's commands and starts the next one
+  pub fn take(&mut self) -> CommandBuffer {
+    CommandBuffer {
+      bytes: mem::take(&mut self.bytes),
+      handlers: mem::take(&mut self.handlers),
+      next_view: self.next_view,
+      last_removal: self.last_removal.take(),
+    }
+  }
+
+  pub fn commands(&self) -> Commands<'_> {
+    CommandBuffer::decode(&self.bytes)
+  }
+
+  /// Decodes bytes that came out of a buffer, e.g. after `into_parts`
+  pub fn decode(bytes: &[u8]) -> Commands<'_> {
+    Commands { bytes, offset: 0 }
+  }
+
+  pub fn into_parts(self) -> (Vec<u8>, Vec<EventHandler>) {
+    (self.bytes, self.handlers)
+  }
+
+  /// Returns the new view's ID
+  pub fn create_view(&mut self, kind: &str) -> ViewId {
+    let view = self.next_view_id();
+    self.op(CREATE_VIEW, view);
+    self.string(kind);
+    view
+  }
+
+  pub fn create_native_view(&mut self, class_name: &str) -> ViewId {
+    let view = self.next_view_id();
+    self.op(CREATE_NATIVE_VIEW, view);
+    self.string(class_name);
+    view
+  }
+
+  pub fn release_view(&mut self, view: ViewId) {
+    self.op(RELEASE_VIEW, view);
+  }
+
+  pub fn set_prop(&mut self, view: ViewId, key: PropKey, value: PropValue) {
+    let opcode = match value {
+      PropValue::F32(_) => SET_F32,
+      PropValue::F64(_) => SET_F64,
+      PropValue::I32(_) => SET_I32,
+      PropValue::I64(_) => SET_I64,
+      PropValue::Bool(_) => SET_BOOL,
+      PropValue::String(_) => SET_STRING,
+      PropValue::Bytes(_) => SET_BYTES,
+      PropValue::Strings(_) => SET_STRINGS,
+    };
+    self.op(opcode, view);
+    self.i32(key.id());
+    match value {
+      PropValue::F32(v) => self.bytes.extend_from_slice(&v.to_le_bytes()),
+      PropValue::F64(v) => self.bytes.extend_from_slice(&v.to_le_bytes()),
+      PropValue::I32(v) => self.i32(v),
+      PropValue::I64(v) => self.bytes.extend_from_slice(&v.to_le_bytes()),
+      PropValue::Bool(v) => self.bytes.push(v as u8),
+      PropValue::String(v) => self.string(&v),
+      PropValue::Bytes(v) => {
+        self.len(v.len());
+        self.bytes.extend_from_slice(&v);
+      }
+      PropValue::Strings(v) => {
+        self.len(v.len());
+        for string in v.iter() {
+          self.string(string);
+        }
+      }
+    }
+  }
+
+  pub fn set_callback(&mut self, view: ViewId, key: PropKey, handler: EventHandler) {
+    self.op(SET_CALLBACK, view);
+    self.i32(key.id());
+    self.len(self.handlers.len());
+    self.handlers.push(handler);
+  }
+
+  pub fn append_child(&mut self, parent: ViewId, child: ViewId) {
+    self.op(APPEND_CHILD, parent);
+    self.u32(child);
+  }
+
+  /// The composer moves a child by removing and reinserting it, that pair is recorded as one move
+  pub fn insert_child_at(&mut self, parent: ViewId, child: ViewId, idx: usize) {
+    if let Some((removed_from, removed, offset)) = self.last_removal.take() {
+      if (removed_from, removed) == (parent, child) {
+        self.bytes.truncate(offset);
+        return self.move_child(parent, child, idx);
+      }
+    }
+    self.op(INSERT_CHILD_AT, parent);
+    self.u32(child);
+    self.len(idx);
+  }
+
+  pub fn remove_child(&mut self, parent: ViewId, child: ViewId) {
+    let offset = self.bytes.len();
+    self.op(REMOVE_CHILD, parent);
+    self.u32(child);
+    self.last_removal = Some((parent, child, offset));
+  }
+
+  pub fn remove_child_index(&mut self, parent: ViewId, idx: usize) {
+    self.op(REMOVE_CHILD_INDEX, parent);
+    self.len(idx);
+  }
+
+  pub fn move_child(&mut self, parent: ViewId, child: ViewId, idx: usize) {
+    self.op(MOVE_CHILD, parent);
+    self.u32(child);
+    self.len(idx);
+  }
+
+  fn next_view_id(&mut self) -> ViewId {
+    let view = self.next_view;
+    self.next_view += 1;
+    view
+  }
+
+  fn op(&mut self, opcode: u8, view: ViewId) {
+    self.last_removal = None;
+    self.bytes.push(opcode);
+    self.u32(view);
+  }
+
+  fn u32(&mut self, v: u32) {
+    self.bytes.extend_from_slice(&v.to_le_bytes());
+  }
+
+  fn i32(&mut self, v: i32) {
+    self.bytes.extend_from_slice(&v.to_le_bytes());
+  }
+
+  fn len(&mut self, len: usize) {
+    self.u32(len as u32);
+  }
+
+  fn string(&mut self, s: &str) {
+    self.len(s.len());
+    self.bytes.extend_from_slice(s.as_bytes());
+  }
+}
+
+/// Decodes a buffer's commands in order
+pub struct Commands<'a> {
+  bytes: &'a [u8],
+  offset: usize,
+}
+
+impl<'a> Commands<'a> {
+  /// Where the next command starts
+  pub fn offset(&self) -> usize {
+    self.offset
+  }
+
+  fn malformed(&self, what: &str) -> WiredError {
+    WiredError::BadCommandBuffer {
+      offset: self.offset,
+      reason: what.into(),
+    }
+  }
+
+  fn take(&mut self, n: usize) -> Result<&'a [u8], WiredError> {
+    if self.bytes.len() - self.offset < n {
+      return Err(self.malformed("truncated"));
+    }
+    let bytes = &self.bytes[self.offset..self.offset + n];
+    self.offset += n;
+    Ok(bytes)
+  }
+
+  fn u8(&mut self) -> Result<u8, WiredError> {
+    Ok(self.take(1)?[0])
+  }
+
+  fn u32(&mut self) -> Result<u32, WiredError> {
+    Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+  }
+
+  fn i32(&mut self) -> Result<i32, WiredError> {
+    Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+  }
+
+  fn len(&mut self) -> Result<usize, WiredError> {
+    Ok(self.u32()? as usize)
+  }
+
+  fn key(&mut self) -> Result<PropKey, WiredError> {
+    let id = self.i32()?;
+    PropKey::from_id(id).ok_or_else(|| self.malformed("unregistered prop key"))
+  }
+
+  fn string(&mut self) -> Result<String, WiredError> {
+    let len = self.len()?;
+    let bytes = self.take(len)?;
+    String::from_utf8(bytes.to_vec()).map_err(|_| self.malformed("string isn't UTF-8"))
+  }
+
+  fn prop_value(&mut self, opcode: u8) -> Result<PropValue, WiredError> {
+    Ok(match opcode {
+      SET_F32 => PropValue::F32(f32::from_le_bytes(self.take(4)?.try_into().unwrap())),
+      SET_F64 => PropValue::F64(f64::from_le_bytes(self.take(8)?.try_into().unwrap())),
+      SET_I32 => PropValue::I32(self.i32()?),
+      SET_I64 => PropValue::I64(i64::from_le_bytes(self.take(8)?.try_into().unwrap())),
+      SET_BOOL => PropValue::Bool(self.u8()? != 0),
+      SET_STRING => PropValue::String(self.string()?),
+      SET_BYTES => {
+        let len = self.len()?;
+        PropValue::Bytes(self.take(len)?.to_vec())
+      }
+      _ => {
+        let count = self.len()?;
+        let mut strings = Vec::with_capacity(count.min(1024));
+        for _ in 0..count {
+          strings.push(self.string()?);
+        }
+        PropValue::Strings(strings)
+      }
+    })
+  }
+
+  fn command(&mut self) -> Result<Command, WiredError> {
+    let opcode = self.u8()?;
+    let view = self.u32()?;
+    Ok(match opcode {
+      CREATE_VIEW => Command::CreateView {
+        view,
+        kind: self.string()?,
+      },
+      CREATE_NATIVE_VIEW => Command::CreateNativeView {
+        view,
+        class_name: self.string()?,
+      },
+      RELEASE_VIEW => Command::ReleaseView { view },
+      SET_F32..=SET_STRINGS => Command::SetProp {
+        view,
+        key: self.key()?,
+        value: self.prop_value(opcode)?,
+      },
+      SET_CALLBACK => Command::SetCallback {
+        view,
+        key: self.key()?,
+        handler: self.len()?,
+      },
+      APPEND_CHILD => Command::AppendChild {
+        parent: view,
+        child: self.u32()?,
+      },
+      INSERT_CHILD_AT => Command::InsertChildAt {
+        parent: view,
+        child: self.u32()?,
+        idx: self.len()?,
+      },
+      REMOVE_CHILD => Command::RemoveChild {
+        parent: view,
+        child: self.u32()?,
+      },
+      REMOVE_CHILD_INDEX => Command::RemoveChildIndex {
+        parent: view,
+        idx: self.len()?,
+      },
+      MOVE_CHILD => Command::MoveChild {
+        parent: view,
+        child: self.u32()?,
+        idx: self.len()?,
+      },
+      _ => return Err(self.malformed("unknown opcode")),
+    })
+  }
+}
+
+impl<'a> Iterator for Commands<'a> {
+  type Item = Result<Command, WiredError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.offset >= self.bytes.len() {
+      return None;
+    }
+    let command = self.command();
+    if command.is_err() {
+      // Nothing after a bad command can be trusted
+      self.offset = self.bytes.len();
+    }
+    Some(command)
+  }
+}
+
+/// The buffer a frame's views record into
+pub type SharedBuffer = Arc<Mutex<CommandBuffer>>;
+
+/// A view that records what's done to it instead of doing it. It's released on the host when
+/// it's dropped, and parents hold on to their children, so that's once it's out of the tree and
+/// nothing else has it.
+pub struct RecordingView {
+  id: ViewId,
+  kind: &'static str,
+  buffer: SharedBuffer,
+  // What parents see of it, so they can record its ID
+  raw_view: Arc<Mutex<ViewId>>,
+  children: Vec<PlatformView>,
+}
+
+impl Debug for RecordingView {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "RecordingView [{} #{}]", self.kind, self.id)
+  }
+}
+
+impl RecordingView {
+  pub fn create(buffer: &SharedBuffer, kind: &'static str) -> Self {
+    let id = buffer.lock().unwrap().create_view(kind);
+    RecordingView::with_id(buffer, kind, id)
+  }
+
+  pub fn create_native(buffer: &SharedBuffer, class_name: &str) -> Self {
+    let id = buffer.lock().unwrap().create_native_view(class_name);
+    RecordingView::with_id(buffer, "NativeView", id)
+  }
+
+  /// The view the host registered as `ROOT_VIEW`
+  pub fn root(buffer: &SharedBuffer, kind: &'static str) -> Self {
+    RecordingView::with_id(buffer, kind, ROOT_VIEW)
+  }
+
+  fn with_id(buffer: &SharedBuffer, kind: &'static str, id: ViewId) -> Self {
+    RecordingView {
+      id,
+      kind,
+      buffer: buffer.clone(),
+      raw_view: Arc::new(Mutex::new(id)),
+      children: vec![],
+    }
+  }
+
+  pub fn id(&self) -> ViewId {
+    self.id
+  }
+
+  fn record<F: FnOnce(&mut CommandBuffer)>(&self, f: F) {
+    f(&mut self.buffer.lock().unwrap())
+  }
+}
+
+fn child_id(c: &PlatformView) -> Result<ViewId, WiredError> {
+  let raw_view = c.get_raw_view()?;
+  let raw_view = raw_view.lock().unwrap();
+  Ok(
+    *raw_view
+      .downcast_ref::<ViewId>()
+      .expect("Not a recording view"),
+  )
+}
+
+impl Drop for RecordingView {
+  fn drop(&mut self) {
+    // The host owns the root
+    if self.id != ROOT_VIEW {
+      let id = self.id;
+      self.record(|b| b.release_view(id));
+    }
+  }
+}
+
+macro_rules! record_set_prop {
+  ($($t:ty),*) => {
+    $(
+      impl SetProp<$t> for RecordingView {
+        fn set_prop(&mut self, k: PropKey, v: $t) -> Result<(), WiredError> {
+          let id = self.id;
+          self.record(|b| b.set_prop(id, k, v.into()));
+          Ok(())
+        }
+      }
+    )*
+  };
+}
+
+record_set_prop!(String, f32, f64, i32, i64, bool, Vec<u8>, Vec<String>);
+
+impl SetProp<EventHandler> for RecordingView {
+  fn set_prop(&mut self, k: PropKey, handler: EventHandler) -> Result<(), WiredError> {
+    let id = self.id;
+    self.record(|b| b.set_callback(id, k, handler));
+    Ok(())
+  }
+}
+
+// Props that come in untyped (like the callbacks widgets register in compose) are recorded as
+// the matching typed command, anything else is an error rather than being dropped.
+impl SetProp<Box<dyn Any + Send>> for RecordingView {
+  fn set_prop(&mut self, k: PropKey, mut v: Box<dyn Any + Send>) -> Result<(), WiredError> {
+    if let Some(handler) = v.downcast_mut::<Option<EventHandler>>() {
+      let handler = handler.take().expect("No Callback?");
+      return self.set_prop(k, handler);
+    }
+    if let Some(cb) = v.downcast_mut::<Option<Callback>>() {
+      let cb: Callback = cb.take().expect("No Callback?");
+      return self.set_prop(k, cb.into_handler());
+    }
+    if let Some(handler) = v.downcast_ref::<EventHandler>() {
+      return self.set_prop(k, handler.clone());
+    }
+
+    let v = match v.downcast::<String>() {
+      Ok(v) => return self.set_prop(k, *v),
+      Err(v) => v,
+    };
+    let v = match v.downcast::<f32>() {
+      Ok(v) => return self.set_prop(k, *v),
+      Err(v) => v,
+    };
+    let v = match v.downcast::<f64>() {
+      Ok(v) => return self.set_prop(k, *v),
+      Err(v) => v,
+    };
+    let v = match v.downcast::<i32>() {
+      Ok(v) => return self.set_prop(k, *v),
+      Err(v) => v,
+    };
+    let v = match v.downcast::<i64>() {
+      Ok(v) => return self.set_prop(k, *v),
+      Err(v) => v,
+    };
+    let v = match v.downcast::<bool>() {
+      Ok(v) => return self.set_prop(k, *v),
+      Err(v) => v,
+    };
+    let v = match v.downcast::<Vec<u8>>() {
+      Ok(v) => return self.set_prop(k, *v),
+      Err(v) => v,
+    };
+    match v.downcast::<Vec<String>>() {
+      Ok(v) => self.set_prop(k, *v),
+      Err(_) => Err(WiredError::UnsupportedPropType {
+        view: self.kind,
+        key: k.name(),
+      }),
+    }
+  }
+}
+
+impl PlatformViewInner for RecordingView {
+  fn append_child(&mut self, c: &PlatformView) -> Result<(), WiredError> {
+    let (id, child) = (self.id, child_id(c)?);
+    self.record(|b| b.append_child(id, child));
+    self.children.push(c.clone());
+    Ok(())
+  }
+
+  fn insert_child_at(&mut self, c: &PlatformView, idx: usize) -> Result<(), WiredError> {
+    let (id, child) = (self.id, child_id(c)?);
+    self.record(|b| b.insert_child_at(id, child, idx));
+    let idx = idx.min(self.children.len());
+    self.children.insert(idx, c.clone());
+    Ok(())
+  }
+
+  fn remove_child(&mut self, c: &PlatformView) -> Result<(), WiredError> {
+    let (id, child) = (self.id, child_id(c)?);
+    self.record(|b| b.remove_child(id, child));
+    // The caller still has it, so it isn't released here
+    self
+      .children
+      .retain(|other| !Arc::ptr_eq(&other.underlying_view, &c.underlying_view));
+    Ok(())
+  }
+
+  fn remove_child_index(&mut self, idx: usize) -> Result<(), WiredError> {
+    let id = self.id;
+    self.record(|b| b.remove_child_index(id, idx));
+    // Released after the removal if it was the last owner
+    if idx < self.children.len() {
+      self.children.remove(idx);
+    }
+    Ok(())
+  }
+
+  fn get_raw_view(&self) -> Result<Arc<Mutex<dyn Any>>, WiredError> {
+    Ok(self.raw_view.clone())
+  }
+}
+
+/// The callbacks a host holds on to, by view and prop. Every host lets go of them at the same
+/// points: when the prop is set again, and when the view is released.
+pub(crate) struct HeldCallbacks<T> {
+  held: HashMap<(ViewId, PropKey), T>,
+}
+
+impl<T> HeldCallbacks<T> {
+  pub(crate) fn new() -> Self {
+    HeldCallbacks {
+      held: HashMap::new(),
+    }
+  }
+
+  /// Returns the callback this one replaces
+  pub(crate) fn hold(&mut self, view: ViewId, key: PropKey, callback: T) -> Option<T> {
+    self.held.insert((view, key), callback)
+  }
+
+  /// Returns all of the view's callbacks
+  pub(crate) fn release(&mut self, view: ViewId) -> Vec<T> {
+    let keys: Vec<_> = self.held.keys().filter(|k| k.0 == view).cloned().collect();
+    keys
+      .into_iter()
+      .filter_map(|k| self.held.remove(&k))
+      .collect()
+  }
+}
+
+/// Applies buffers onto real views, playing the part of the host. `create_view` makes the view
+/// for a create command, it gets the kind, or the class name for native views.
+pub struct Replayer<F> {
+  views: HashMap<ViewId, PlatformView>,
+  callbacks: HeldCallbacks<EventHandler>,
+  create_view: F,
+}
+
+impl<F> Replayer<F>
+where
+  F: FnMut(&str) -> PlatformView,
+{
+  pub fn new(root: PlatformView, create_view: F) -> Self {
+    let mut views = HashMap::new();
+    views.insert(ROOT_VIEW, root);
+    Replayer {
+      views,
+      callbacks: HeldCallbacks::new(),
+      create_view,
+    }
+  }
+
+  pub fn view(&self, id: ViewId) -> Option<&PlatformView> {
+    self.views.get(&id)
+  }
+
+  /// Like the host, a command that fails is skipped and the rest of the frame still applies.
+  /// Returns the first failure, or where the buffer stopped making sense.
+  pub fn replay(&mut self, buffer: CommandBuffer) -> Result<(), WiredError> {
+    let (bytes, handlers) = buffer.into_parts();
+    let mut handlers: Vec<Option<EventHandler>> = handlers.into_iter().map(Some).collect();
+    let mut first_failure = None;
+    for command in CommandBuffer::decode(&bytes) {
+      if let Err(e) = self.apply(command?, &mut handlers) {
+        first_failure.get_or_insert(e);
+      }
+    }
+    match first_failure {
+      Some(e) => Err(e),
+      None => Ok(()),
+    }
+  }
+
+  fn apply(
+    &mut self,
+    command: Command,
+    handlers: &mut [Option<EventHandler>],
+  ) -> Result<(), WiredError> {
+    match command {
+      Command::CreateView { view, kind } => {
+        let new_view = (self.create_view)(&kind);
+        self.views.insert(view, new_view);
+      }
+      Command::CreateNativeView { view, class_name } => {
+        let new_view = (self.create_view)(&class_name);
+        self.views.insert(view, new_view);
+      }
+      Command::ReleaseView { view } => {
+        self.views.remove(&view);
+        self.callbacks.release(view);
+      }
+      Command::SetProp { view, key, value } => {
+        let view = self.get(view)?;
+        match value {
+          PropValue::F32(v) => view.set_prop(key, v)?,
+          PropValue::F64(v) => view.set_prop(key, v)?,
+          PropValue::I32(v) => view.set_prop(key, v)?,
+          PropValue::I64(v) => view.set_prop(key, v)?,
+          PropValue::Bool(v) => view.set_prop(key, v)?,
+          PropValue::String(v) => view.set_prop(key, v)?,
+          PropValue::Bytes(v) => view.set_prop(key, v)?,
+          PropValue::Strings(v) => view.set_prop(key, v)?,
+        }
+      }
+      Command::SetCallback { view, key, handler } => {
+        let handler = handlers
+          .get_mut(handler)
+          .and_then(|h| h.take())
+          .ok_or_else(|| bad_reference("handler", handler as u32))?;
+        // Like Android's RustCallback objects, the view's handle stops working once the
+        // handler is let go of, whatever the view does with it
+        self.get(view)?.set_prop(key, handler.downgrade())?;
+        self.callbacks.hold(view, key, handler);
+      }
+      Command::AppendChild { parent, child } => {
+        let child = self.get(child)?.clone();
+        self.get(parent)?.append_child(&child)?;
+      }
+      Command::InsertChildAt { parent, child, idx } => {
+        let child = self.get(child)?.clone();
+        self.get(parent)?.insert_child_at(&child, idx)?;
+      }
+      Command::RemoveChild { parent, child } => {
+        let child = self.get(child)?.clone();
+        self.get(parent)?.remove_child(&child)?;
+      }
+      Command::RemoveChildIndex { parent, idx } => {
+        self.get(parent)?.remove_child_index(idx)?;
+      }
+      Command::MoveChild { parent, child, idx } => {
+        let child = self.get(child)?.clone();
+        let parent = self.get(parent)?;
+        parent.remove_child(&child)?;
+        parent.insert_child_at(&child, idx)?;
+      }
+    }
+    Ok(())
+  }
+
+  fn get(&mut self, id: ViewId) -> Result<&mut PlatformView, WiredError> {
+    self
+      .views
+      .get_mut(&id)
+      .ok_or_else(|| bad_reference("view", id))
+  }
+}
+
+fn bad_reference(what: &str, id: u32) -> WiredError {
+  WiredError::BadCommandBuffer {
+    offset: 0,
+    reason: format!("no {} {}", what, id),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn round_trips_every_command() {
+    let text = PropKey::intern("text").unwrap();
+    let on_press = PropKey::intern("on_press").unwrap();
+    let mut buffer = CommandBuffer::new();
+    let a = buffer.create_view("TextView");
+    let b = buffer.create_native_view("android.widget.RatingBar");
+    buffer.set_prop(a, text, PropValue::String("héllo".into()));
+    buffer.set_prop(a, text, PropValue::F32(1.5));
+    buffer.set_prop(a, text, PropValue::F64(-2.25));
+    buffer.set_prop(a, text, PropValue::I32(-7));
+    buffer.set_prop(a, text, PropValue::I64(1 << 40));
+    buffer.set_prop(a, text, PropValue::Bool(true));
+    buffer.set_prop(a, text, PropValue::Bytes(vec![0, 255]));
+    buffer.set_prop(a, text, PropValue::Strings(vec!["x".into(), "".into()]));
+    buffer.set_callback(b, on_press, Callback::new(|()| {}).into_handler());
+    buffer.append_child(ROOT_VIEW, a);
+    buffer.insert_child_at(ROOT_VIEW, b, 0);
+    buffer.move_child(ROOT_VIEW, b, 1);
+    buffer.remove_child(ROOT_VIEW, a);
+    buffer.remove_child_index(ROOT_VIEW, 0);
+    buffer.release_view(a);
+
+    let commands: Vec<Command> = buffer.commands().map(|c| c.unwrap()).collect();
+    assert_eq!(
+      commands,
+      vec![
+        Command::CreateView {
+          view: 1,
+          kind: "TextView".into()
+        },
+        Command::CreateNativeView {
+          view: 2,
+          class_name: "android.widget.RatingBar".into()
+        },
+        Command::SetProp {
+          view: 1,
+          key: text,
+          value: PropValue::String("héllo".into())
+        },
+        Command::SetProp {
+          view: 1,
+          key: text,
+          value: PropValue::F32(1.5)
+        },
+        Command::SetProp {
+          view: 1,
+          key: text,
+          value: PropValue::F64(-2.25)
+        },
+        Command::SetProp {
+          view: 1,
+          key: text,
+          value: PropValue::I32(-7)
+        },
+        Command::SetProp {
+          view: 1,
+          key: text,
+          value: PropValue::I64(1 << 40)
+        },
+        Command::SetProp {
+          view: 1,
+          key: text,
+          value: PropValue::Bool(true)
+        },
+        Command::SetProp {
+          view: 1,
+          key: text,
+          value: PropValue::Bytes(vec![0, 255])
+        },
+        Command::SetProp {
+          view: 1,
+          key: text,
+          value: PropValue::Strings(vec!["x".into(), "".into()]),
+        },
+        Command::SetCallback {
+          view: 2,
+          key: on_press,
+          handler: 0
+        },
+        Command::AppendChild {
+          parent: 0,
+          child: 1
+        },
+        Command::InsertChildAt {
+          parent: 0,
+          child: 2,
+          idx: 0
+        },
+        Command::MoveChild {
+          parent: 0,
+          child: 2,
+          idx: 1
+        },
+        Command::RemoveChild {
+          parent: 0,
+          child: 1
+        },
+        Command::RemoveChildIndex { parent: 0, idx: 0 },
+        Command::ReleaseView { view: 1 },
+      ]
+    );
+    assert_eq!(buffer.handlers().len(), 1);
+
+    // IDs carry on into the next frame
+    let mut next = buffer.take();
+    assert!(buffer.is_empty());
+    assert_eq!(buffer.create_view("TextView"), 3);
+    assert_eq!(next.create_view("TextView"), 3);
+  }
+
+  // The host reads commands with the layout in wired_codegen's table, so walking a buffer with
+  // it has to land on the same command boundaries as decoding it
+  #[test]
+  fn matches_the_codegen_layout() {
+    use wired_codegen::commands::{Field, COMMANDS};
+
+    let key = PropKey::intern("text").unwrap();
+    let mut buffer = CommandBuffer::new();
+    let a = buffer.create_view("TextView");
+    let b = buffer.create_native_view("android.widget.RatingBar");
+    buffer.set_prop(a, key, PropValue::F32(1.5));
+    buffer.set_prop(a, key, PropValue::F64(-2.25));
+    buffer.set_prop(a, key, PropValue::I32(-7));
+    buffer.set_prop(a, key, PropValue::I64(1 << 40));
+    buffer.set_prop(a, key, PropValue::Bool(true));
+    buffer.set_prop(a, key, PropValue::String("héllo".into()));
+    buffer.set_prop(a, key, PropValue::Bytes(vec![0, 255]));
+    buffer.set_prop(a, key, PropValue::Strings(vec!["x".into(), "".into()]));
+    buffer.set_callback(b, key, Callback::new(|()| {}).into_handler());
+    buffer.append_child(ROOT_VIEW, a);
+    buffer.insert_child_at(ROOT_VIEW, b, 0);
+    buffer.move_child(ROOT_VIEW, b, 1);
+    buffer.remove_child(ROOT_VIEW, a);
+    buffer.remove_child_index(ROOT_VIEW, 0);
+    buffer.release_view(a);
+
+    let bytes = buffer.bytes();
+    let u32_at = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()) as usize;
+    let mut decoded = CommandBuffer::decode(bytes);
+    let mut seen = vec![];
+    let mut at = 0;
+    while at < bytes.len() {
+      assert_eq!(decoded.offset(), at);
+      decoded.next().unwrap().unwrap();
+      let command = COMMANDS
+        .iter()
+        .find(|c| c.opcode == bytes[at])
+        .expect("Opcode is in the table");
+      seen.push(command.name);
+      at += 5;
+      for (_, field) in command.fields.iter() {
+        at += match field.size() {
+          Some(size) => size,
+          None if *field == Field::Strings => {
+            let count = u32_at(at);
+            at += 4;
+            for _ in 0..count {
+              at += 4 + u32_at(at);
+            }
+            0
+          }
+          None => 4 + u32_at(at),
+        };
+      }
+    }
+    assert_eq!(at, bytes.len());
+    assert!(decoded.next().is_none());
+    // Every command the table has was checked
+    seen.sort_unstable();
+    seen.dedup();
+    assert_eq!(seen.len(), COMMANDS.len());
+  }
+
+  #[test]
+  fn records_remove_then_insert_as_a_move() {
+    let mut buffer = CommandBuffer::new();
+    buffer.remove_child(ROOT_VIEW, 1);
+    buffer.insert_child_at(ROOT_VIEW, 1, 2);
+    buffer.remove_child(ROOT_VIEW, 1);
+    buffer.insert_child_at(ROOT_VIEW, 3, 0);
+
+    let commands: Vec<Command> = buffer.commands().map(|c| c.unwrap()).collect();
+    assert_eq!(
+      commands,
+      vec![
+        Command::MoveChild {
+          parent: 0,
+          child: 1,
+          idx: 2
+        },
+        Command::RemoveChild {
+          parent: 0,
+          child: 1
+        },
+        Command::InsertChildAt {
+          parent: 0,
+          child: 3,
+          idx: 0
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn releases_views_once_they_leave_the_tree() {
+    let buffer: SharedBuffer = Arc::new(Mutex::new(CommandBuffer::new()));
+    let mut root = PlatformView::new(RecordingView::root(&buffer, "Root"));
+    let child = PlatformView::new(RecordingView::create(&buffer, "TextView"));
+    root.append_child(&child).unwrap();
+    // The parent still has it
+    mem::drop(child);
+    root.remove_child_index(0).unwrap();
+
+    let buffer = buffer.lock().unwrap();
+    let commands: Vec<Command> = buffer.commands().map(|c| c.unwrap()).collect();
+    assert_eq!(
+      commands,
+      vec![
+        Command::CreateView {
+          view: 1,
+          kind: "TextView".into()
+        },
+        Command::AppendChild {
+          parent: 0,
+          child: 1
+        },
+        Command::RemoveChildIndex { parent: 0, idx: 0 },
+        Command::ReleaseView { view: 1 },
+      ]
+    );
+  }
+
+  #[test]
+  fn rejects_malformed_buffers() {
+    let mut buffer = CommandBuffer::new();
+    buffer.create_view("TextView");
+    let bytes = buffer.bytes().to_vec();
+
+    let results: Vec<_> = CommandBuffer::decode(&bytes[..bytes.len() - 1]).collect();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].is_err());
+
+    let unknown_opcode = [99u8, 0, 0, 0, 0];
+    let mut commands = CommandBuffer::decode(&unknown_opcode);
+    assert!(commands.next().unwrap().is_err());
+    assert!(commands.next().is_none());
+  }
+}
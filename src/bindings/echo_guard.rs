@@ -0,0 +1,39 @@
+use std::sync::{Arc, Mutex};
+
+/// Remembers the last value a view reported about itself, so that when the value comes back
+/// around through a bound signal we don't push it into the view again (and lose the cursor,
+/// retrigger listeners, ...).
+pub struct EchoGuard<T> {
+  from_view: Arc<Mutex<Option<T>>>,
+}
+
+impl<T> Clone for EchoGuard<T> {
+  fn clone(&self) -> Self {
+    EchoGuard {
+      from_view: self.from_view.clone(),
+    }
+  }
+}
+
+impl<T: PartialEq + Clone> EchoGuard<T> {
+  pub fn new() -> Self {
+    EchoGuard {
+      from_view: Arc::new(Mutex::new(None)),
+    }
+  }
+
+  /// The view changed on its own. Call this before handing the value to Rust.
+  pub fn from_view(&self, v: &T) {
+    let mut lock = self.from_view.lock().unwrap();
+    *lock = Some(v.clone());
+  }
+
+  /// Whether a value coming from Rust should be sent to the view.
+  pub fn should_forward(&self, v: &T) -> bool {
+    let mut lock = self.from_view.lock().unwrap();
+    match lock.take() {
+      Some(ref last) if last == v => false,
+      _ => true,
+    }
+  }
+}
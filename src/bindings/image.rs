@@ -0,0 +1,26 @@
+use crate::bindings::prop_key::PropKey;
+use crate::bindings::view_helpers::UpdateProp;
+use crate::error::WiredError;
+use crate::ui_tree::PlatformView;
+
+/// Where an Image gets its pixels from
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImageSource {
+  /// Encoded image data (png, jpeg, ...), sent to the host as a byte array
+  Bytes(Vec<u8>),
+  /// Path to an encoded image on the device
+  File(String),
+  /// Name of a drawable bundled with the app
+  Resource(String),
+}
+
+impl ImageSource {
+  // Each kind of source is its own prop so the host doesn't have to guess what a string is
+  pub(crate) fn apply(self, view: &mut PlatformView) -> Result<(), WiredError> {
+    match self {
+      ImageSource::Bytes(bytes) => view.update_prop(PropKey::SOURCE_BYTES, bytes),
+      ImageSource::File(path) => view.update_prop(PropKey::SOURCE_FILE, path),
+      ImageSource::Resource(name) => view.update_prop(PropKey::SOURCE_RESOURCE, name),
+    }
+  }
+}
@@ -1,4 +1,9 @@
 pub mod android;
 pub mod view_helpers;
 pub mod callback;
-pub(crate) mod test;
\ No newline at end of file
+pub mod command_buffer;
+pub mod echo_guard;
+pub mod image;
+pub mod prop_key;
+pub(crate) mod test;
+pub mod virtual_list;
@@ -0,0 +1,89 @@
+//! Prop keys cross the bridge as small integer IDs instead of strings. The known keys and their
+//! IDs come from the widget spec, which the host's `PropKeys` is generated from as well.
+
+use crate::error::WiredError;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+include!(concat!(env!("OUT_DIR"), "/prop_keys.rs"));
+
+thread_local! {
+  static REGISTRY: RefCell<Registry> = RefCell::new(Registry::new());
+}
+
+struct Registry {
+  ids: HashMap<String, PropKey>,
+  names: Vec<String>,
+}
+
+impl Registry {
+  fn new() -> Self {
+    let mut registry = Registry {
+      ids: HashMap::new(),
+      names: vec![],
+    };
+    for key in PROP_KEYS.iter() {
+      registry.register(key);
+    }
+    registry
+  }
+
+  fn register(&mut self, name: &str) -> PropKey {
+    if let Some(key) = self.ids.get(name) {
+      return *key;
+    }
+    let key = PropKey(self.names.len() as i32);
+    self.names.push(name.into());
+    self.ids.insert(name.into(), key);
+    key
+  }
+}
+
+/// An interned prop key
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PropKey(i32);
+
+impl PropKey {
+  /// The key for `name`. Keys the host doesn't know fail here, instead of being dropped by it.
+  pub fn intern(name: &str) -> Result<PropKey, WiredError> {
+    REGISTRY
+      .with(|r| r.borrow().ids.get(name).cloned())
+      .ok_or_else(|| WiredError::UnknownProp { key: name.into() })
+  }
+
+  /// Adds a key that isn't in the spec, like a NativeView setter. The host only knows the spec's
+  /// keys, so whoever registers one has to tell it about the new ID.
+  pub fn register(name: &str) -> PropKey {
+    REGISTRY.with(|r| r.borrow_mut().register(name))
+  }
+
+  /// The key with this ID, if one was interned or registered
+  pub fn from_id(id: i32) -> Option<PropKey> {
+    let registered = REGISTRY.with(|r| r.borrow().names.len());
+    if id >= 0 && (id as usize) < registered {
+      Some(PropKey(id))
+    } else {
+      None
+    }
+  }
+
+  pub fn id(self) -> i32 {
+    self.0
+  }
+
+  pub fn name(self) -> String {
+    REGISTRY.with(|r| r.borrow().names[self.0 as usize].clone())
+  }
+
+  /// Whether it's one of the spec's keys, which the host knows about up front
+  pub fn is_static(self) -> bool {
+    (self.0 as usize) < PROP_KEYS.len()
+  }
+}
+
+impl Display for PropKey {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
@@ -1,16 +1,26 @@
 #![allow(dead_code)]
-use crate::bindings::callback::Callback;
+use crate::bindings::android::frame;
+use crate::bindings::callback::{Event, EventHandler, PressEvent};
+use crate::bindings::command_buffer::{RecordingView, Replayer};
+use crate::bindings::echo_guard::EchoGuard;
+use crate::bindings::image::ImageSource;
+use crate::bindings::prop_key::PropKey;
+use crate::bindings::virtual_list::{Row, VirtualList, VirtualListHandle};
+use crate::error::WiredError;
+use crate::navigation::{RouteState, Router};
+use crate::style::Orientation;
 use crate::bindings::view_helpers::*;
 use crate::ui_tree::{
-  spawn_future, with_parent, Composable, Composer, PlatformView, PlatformViewInner,
+  set_root_view, spawn_future, with_parent, Composable, Composer, PlatformView,
+  PlatformViewInner,
 };
 use discard::DiscardOnDrop;
 use futures::future::ready;
-use futures_signals::signal::{Signal, SignalExt};
+use futures_signals::signal::{Mutable, Signal, SignalExt};
 use futures_signals::CancelableFutureHandle;
 use std::any::Any;
-use std::error::Error;
 use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
 pub struct StackLayout {
@@ -42,17 +52,100 @@ pub struct DummyPlatformView {
 
 impl DummyPlatformView {
   pub fn new(el_type: &'static str) -> PlatformView {
+    let props: DummyProps = Arc::new(Mutex::new(vec![]));
     PlatformView {
       underlying_view: Arc::new(Mutex::new(DummyPlatformView {
         el_type,
-        props: Arc::new(Mutex::new(vec![])),
+        props: props.clone(),
         children: vec![],
-        raw_view: Arc::new(Mutex::new("dummy")),
+        // The raw view is the props, so that tests can reach the callbacks a view was given
+        raw_view: props,
       })),
     }
   }
 }
 
+/// Acts like the host view firing the callback stored under `key`
+pub fn send_event(view: &PlatformView, key: &str, e: Event) {
+  let raw_view = view.get_raw_view().expect("view is there");
+  let handler = {
+    let raw_view = raw_view.lock().unwrap();
+    let props = raw_view
+      .downcast_ref::<Vec<(String, Box<dyn Any + Send>)>>()
+      .expect("Not a dummy view");
+    let (_, handler) = props
+      .iter()
+      .find(|(k, _)| k == key)
+      .expect("No callback for key");
+    handler
+      .downcast_ref::<EventHandler>()
+      .expect("Not a callback")
+      .clone()
+  };
+  // Like a real host, this only queues the callback. Run the executor to deliver it.
+  handler.dispatch(e);
+}
+
+type CreateView = Box<dyn FnMut(&str) -> PlatformView>;
+
+/// Plays the part of the Android host for the real widgets. They record into this thread's
+/// frame, and `flush` replays it onto dummy views.
+pub struct FrameHost {
+  pub root: PlatformView,
+  created: Arc<Mutex<Vec<(String, PlatformView)>>>,
+  replayer: Replayer<CreateView>,
+}
+
+impl FrameHost {
+  /// Also makes the frame's root the composer's root
+  pub fn new() -> Self {
+    let root = DummyPlatformView::new("Root");
+    let created = Arc::new(Mutex::new(vec![]));
+    let created_clone = created.clone();
+    let create_view: CreateView = Box::new(move |kind: &str| {
+      let view = DummyPlatformView::new(Box::leak(kind.to_string().into_boxed_str()));
+      created_clone
+        .lock()
+        .unwrap()
+        .push((kind.to_string(), view.clone()));
+      view
+    });
+    set_root_view(PlatformView::new(RecordingView::root(
+      &frame::buffer(),
+      "Root",
+    )));
+    FrameHost {
+      root: root.clone(),
+      created,
+      replayer: Replayer::new(root, create_view),
+    }
+  }
+
+  /// Applies everything recorded since the last flush
+  pub fn flush(&mut self) {
+    let frame = frame::buffer().lock().unwrap().take();
+    self.replayer.replay(frame).expect("Couldn't replay frame");
+  }
+
+  /// Whether nothing was recorded since the last flush
+  pub fn is_idle(&self) -> bool {
+    frame::buffer().lock().unwrap().is_empty()
+  }
+
+  /// The last view the host made of this kind
+  pub fn view(&self, kind: &str) -> PlatformView {
+    self
+      .created
+      .lock()
+      .unwrap()
+      .iter()
+      .rev()
+      .find(|(k, _)| k == kind)
+      .map(|(_, view)| view.clone())
+      .expect("No view of that kind")
+  }
+}
+
 impl Debug for DummyPlatformView {
   fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
     write!(
@@ -64,10 +157,7 @@ impl Debug for DummyPlatformView {
         .lock()
         .unwrap()
         .iter()
-        .map(|(k, v)| (
-          k.clone(),
-          v.downcast_ref::<String>().expect("Not a string").clone()
-        ))
+        .map(|(k, v)| (k.clone(), prop_to_string(v)))
         .collect::<Vec<(String, String)>>()
     )?;
     if !self.children.is_empty() {
@@ -77,6 +167,30 @@ impl Debug for DummyPlatformView {
   }
 }
 
+fn prop_to_string(v: &Box<dyn Any + Send>) -> String {
+  if let Some(s) = v.downcast_ref::<String>() {
+    s.clone()
+  } else if let Some(f) = v.downcast_ref::<f32>() {
+    f.to_string()
+  } else if let Some(f) = v.downcast_ref::<f64>() {
+    f.to_string()
+  } else if let Some(i) = v.downcast_ref::<i32>() {
+    i.to_string()
+  } else if let Some(i) = v.downcast_ref::<i64>() {
+    i.to_string()
+  } else if let Some(b) = v.downcast_ref::<bool>() {
+    b.to_string()
+  } else if let Some(bytes) = v.downcast_ref::<Vec<u8>>() {
+    format!("{} bytes", bytes.len())
+  } else if let Some(strings) = v.downcast_ref::<Vec<String>>() {
+    format!("{:?}", strings)
+  } else if v.is::<EventHandler>() {
+    "<callback>".into()
+  } else {
+    panic!("Not a printable prop")
+  }
+}
+
 pub struct Text {
   underlying_view: Option<PlatformView>,
 }
@@ -106,7 +220,7 @@ impl Text {
     t.underlying_view
       .as_mut()
       .unwrap()
-      .update_prop("text", text.into())
+      .update_prop(PropKey::TEXT, text.into())
       .unwrap();
     t
   }
@@ -119,7 +233,7 @@ impl Text {
     let f = s.for_each(move |string| {
       let any: Box<dyn Any + Send> = Box::new(string.clone());
       platform_view
-        .update_prop("text", any)
+        .update_prop(PropKey::TEXT, any)
         .expect("view is there");
       ready(())
     });
@@ -129,6 +243,19 @@ impl Text {
     self
   }
 
+  /// The keys of every prop the view has been sent
+  pub fn prop_keys(&self) -> Vec<String> {
+    let view = self.underlying_view.as_ref().expect("view is there");
+    let raw_view = view.get_raw_view().expect("view is there");
+    let raw_view = raw_view.lock().unwrap();
+    raw_view
+      .downcast_ref::<Vec<(String, Box<dyn Any + Send>)>>()
+      .expect("Not a dummy view")
+      .iter()
+      .map(|(k, _)| k.clone())
+      .collect()
+  }
+
   fn with_view(self, v: PlatformView) -> Text {
     Text {
       underlying_view: Some(v),
@@ -140,45 +267,124 @@ impl Text {
   }
 }
 
-impl UpdateProp<String> for DummyPlatformView {
-  fn update_prop(&mut self, s: &str, v: String) -> Result<(), Box<dyn Error>> {
-    let any: Box<dyn Any + Send> = Box::new(v);
-    self.update_prop(s, any)?;
-    Ok(())
+impl<T> UpdateProp<T> for Text
+where
+  PlatformView: UpdateProp<T>,
+{
+  fn update_prop(&mut self, k: PropKey, v: T) -> Result<(), WiredError> {
+    self
+      .underlying_view
+      .as_mut()
+      .expect("view is there")
+      .update_prop(k, v)
   }
 }
 
-impl UpdateProp<f32> for DummyPlatformView {
-  fn update_prop(&mut self, s: &str, v: f32) -> Result<(), Box<dyn Error>> {
-    let any: Box<dyn Any + Send> = Box::new(v);
-    self.update_prop(s, any)?;
+impl<T> UpdatePropSignal<T> for Text
+where
+  T: 'static,
+  PlatformView: UpdateProp<T>,
+{
+  fn update_prop_signal<S>(&mut self, k: PropKey, s: S) -> Result<(), WiredError>
+  where
+    S: 'static + Signal<Item = T> + Send,
+  {
+    let mut platform_view = self.underlying_view.clone().expect("view is there");
+    let f = s.for_each(move |v| {
+      platform_view.update_prop(k, v).expect("view is there");
+      ready(())
+    });
+
+    DiscardOnDrop::leak(spawn_future(f));
     Ok(())
   }
 }
 
-impl UpdateProp<Callback> for DummyPlatformView {
-  fn update_prop(&mut self, s: &str, v: Callback) -> Result<(), Box<dyn Error>> {
-    let any: Box<dyn Any + Send> = Box::new(v);
-    self.update_prop(s, any)?;
-    Ok(())
-  }
+// Every helper, so tests can see what they send
+impl Padding for Text {}
+impl Margin for Text {}
+impl SetXY for Text {}
+impl SetText for Text {}
+impl SetTextSize for Text {}
+impl SetHeightWidth for Text {}
+impl SetOrientation for Text {}
+impl SetFling for Text {}
+impl OnPress for Text {}
+
+// The dummy view keeps every prop around as an Any, whatever its type
+macro_rules! boxed_update_prop {
+  ($($t:ty),*) => {
+    $(
+      impl SetProp<$t> for DummyPlatformView {
+        fn set_prop(&mut self, k: PropKey, v: $t) -> Result<(), WiredError> {
+          let any: Box<dyn Any + Send> = Box::new(v);
+          self.set_prop(k, any)?;
+          Ok(())
+        }
+      }
+    )*
+  };
 }
 
-impl UpdateProp<Box<dyn Any + Send>> for DummyPlatformView {
-  fn update_prop(&mut self, s: &str, v: Box<dyn Any + Send>) -> Result<(), Box<dyn Error>> {
+boxed_update_prop!(String, f32, f64, i32, i64, bool, Vec<u8>, Vec<String>, EventHandler);
+
+// Props are kept by name so tests can read them
+impl SetProp<Box<dyn Any + Send>> for DummyPlatformView {
+  fn set_prop(&mut self, k: PropKey, v: Box<dyn Any + Send>) -> Result<(), WiredError> {
+    let s = k.name();
     println!("Updating {} on {:?} with {:?}", s, self, &v);
-    let mut props = self.props.lock().unwrap();
-    if let Some(i) = props.iter().position(|(p, _)| p == s) {
-      props[i] = (s.into(), v);
-    } else {
-      props.push((s.into(), v));
+    let is_source = s.starts_with("source_");
+    let empty_source = is_source && is_empty_source(&v);
+    {
+      let mut props = self.props.lock().unwrap();
+      // Like the host's image, a view only has one source
+      if is_source {
+        props.retain(|(p, _)| !p.starts_with("source_"));
+      }
+      if let Some(i) = props.iter().position(|(p, _)| *p == s) {
+        props[i] = (s, v);
+      } else {
+        props.push((s, v));
+      }
+    }
+    // And it loads the source right away. Only empty ones fail here.
+    if is_source {
+      if empty_source {
+        self.fire("on_error", Event::Failed("Empty source".into()));
+      } else {
+        self.fire("on_load", Event::Press(PressEvent { x: 0.0, y: 0.0 }));
+      }
     }
     Ok(())
   }
 }
 
+fn is_empty_source(v: &Box<dyn Any + Send>) -> bool {
+  if let Some(bytes) = v.downcast_ref::<Vec<u8>>() {
+    bytes.is_empty()
+  } else if let Some(s) = v.downcast_ref::<String>() {
+    s.is_empty()
+  } else {
+    false
+  }
+}
+
+impl DummyPlatformView {
+  // Dispatches the callback under `key`, if the view has one
+  fn fire(&self, key: &str, e: Event) {
+    let props = self.props.lock().unwrap();
+    let handler = props
+      .iter()
+      .find(|(k, _)| k == key)
+      .and_then(|(_, handler)| handler.downcast_ref::<EventHandler>());
+    if let Some(handler) = handler {
+      handler.dispatch(e);
+    }
+  }
+}
+
 impl PlatformViewInner for DummyPlatformView {
-  // fn update_prop_string(&mut self, s: &str, v: String) -> Result<(), Box<dyn Error>> {
+  // fn update_prop_string(&mut self, s: &str, v: String) -> Result<(), WiredError> {
   //   println!("Updating {} on {:?} with {:?}", s, self, &v);
   //   let mut props = self.props.lock().unwrap();
   //   if let Some(i) = props.iter().position(|(p, _)| p == s) {
@@ -189,7 +395,7 @@ impl PlatformViewInner for DummyPlatformView {
   //   Ok(())
   // }
 
-  // fn update_prop(&mut self, s: &str, v: Box<dyn Any + Send>) -> Result<(), Box<dyn Error>> {
+  // fn update_prop(&mut self, s: &str, v: Box<dyn Any + Send>) -> Result<(), WiredError> {
   //   println!("Updating {} on {:?} with {:?}", s, self, &v);
   //   let mut props = self.props.lock().unwrap();
   //   if let Some(i) = props.iter().position(|(p, _)| p == s) {
@@ -200,20 +406,20 @@ impl PlatformViewInner for DummyPlatformView {
   //   Ok(())
   // }
   /// If you append a child that is attached somewhere else, you should move the child.
-  fn append_child(&mut self, c: &PlatformView) -> Result<(), Box<dyn Error>> {
+  fn append_child(&mut self, c: &PlatformView) -> Result<(), WiredError> {
     println!("Appending Child {:?} to {:?}", c, self);
     self.children.push(c.clone());
     Ok(())
   }
 
-  fn insert_child_at(&mut self, c: &PlatformView, idx: usize) -> Result<(), Box<dyn Error>> {
+  fn insert_child_at(&mut self, c: &PlatformView, idx: usize) -> Result<(), WiredError> {
     println!("Appending Child {:?} to {:?} at idx: {}", c, self, idx);
     self.children.insert(idx, c.clone());
     Ok(())
   }
 
   /// should not tear down the child! since it may be placed somewhere else later
-  fn remove_child(&mut self, c: &PlatformView) -> Result<(), Box<dyn Error>> {
+  fn remove_child(&mut self, c: &PlatformView) -> Result<(), WiredError> {
     println!("Removing Child {:?} From {:?}", c, self);
     self.children = self
       .children
@@ -224,13 +430,13 @@ impl PlatformViewInner for DummyPlatformView {
     Ok(())
   }
   /// Should not tear down the child (same as remove_child)
-  fn remove_child_index(&mut self, idx: usize) -> Result<(), Box<dyn Error>> {
+  fn remove_child_index(&mut self, idx: usize) -> Result<(), WiredError> {
     println!("Removing Child at {:?} From {:?}", idx, self);
     self.children.remove(idx);
     Ok(())
   }
 
-  fn get_raw_view(&self) -> Result<Arc<Mutex<dyn Any>>, Box<dyn Error>> {
+  fn get_raw_view(&self) -> Result<Arc<Mutex<dyn Any>>, WiredError> {
     Ok(self.raw_view.clone())
   }
 }
@@ -255,6 +461,11 @@ where
   }
 }
 
+// The real button calls it text, only this one has a label
+fn label_key() -> PropKey {
+  PropKey::register("label")
+}
+
 impl<F> Button<F>
 where
   F: Fn(),
@@ -281,7 +492,7 @@ where
     let mut platform_view = self.platform_view.clone();
     let f = s.for_each(move |i| {
       platform_view
-        .update_prop("label", i.clone())
+        .update_prop(label_key(), i.clone())
         .expect("view is there");
       ready(())
     });
@@ -293,7 +504,7 @@ where
   pub fn label(&mut self, label: String) {
     self
       .platform_view
-      .update_prop("label", label.clone())
+      .update_prop(label_key(), label.clone())
       .unwrap();
     self.label = Some(label)
   }
@@ -305,6 +516,243 @@ impl<F> Composable for Button<F> {
   }
 }
 
+pub struct TextInput {
+  platform_view: PlatformView,
+  on_change: Option<Arc<dyn Fn(String) + Send + Sync>>,
+  echo_guard: EchoGuard<String>,
+  // How many times Rust pushed a value into the view
+  value_pushes: Arc<AtomicUsize>,
+  on_remove: Vec<DiscardOnDrop<CancelableFutureHandle>>,
+}
+
+/// Stands in for the user typing into the input
+pub struct TextInputHandle {
+  platform_view: PlatformView,
+  on_change: Option<Arc<dyn Fn(String) + Send + Sync>>,
+  echo_guard: EchoGuard<String>,
+  value_pushes: Arc<AtomicUsize>,
+}
+
+impl TextInputHandle {
+  pub fn type_text<S: Into<String>>(&self, text: S) {
+    let text = text.into();
+    let mut platform_view = self.platform_view.clone();
+    platform_view.update_prop(PropKey::VALUE, text.clone()).unwrap();
+    if let Some(on_change) = self.on_change.as_ref() {
+      self.echo_guard.from_view(&text);
+      on_change(text);
+    }
+  }
+
+  pub fn value_pushes(&self) -> usize {
+    self.value_pushes.load(Ordering::SeqCst)
+  }
+}
+
+impl TextInput {
+  pub fn new() -> Self {
+    TextInput {
+      platform_view: DummyPlatformView::new("TextInput"),
+      on_change: None,
+      echo_guard: EchoGuard::new(),
+      value_pushes: Arc::new(AtomicUsize::new(0)),
+      on_remove: vec![],
+    }
+  }
+
+  pub fn bind(self, value: Mutable<String>) -> Self {
+    let value_clone = value.clone();
+    self
+      .value_signal(value.signal_cloned())
+      .on_change(move |text| {
+        let mut lock = value_clone.lock_mut();
+        *lock = text;
+      })
+  }
+
+  pub fn value_signal<S>(mut self, s: S) -> Self
+  where
+    S: 'static + Signal<Item = String>,
+  {
+    let mut platform_view = self.platform_view.clone();
+    let echo_guard = self.echo_guard.clone();
+    let value_pushes = self.value_pushes.clone();
+    let f = s.for_each(move |text| {
+      if echo_guard.should_forward(&text) {
+        value_pushes.fetch_add(1, Ordering::SeqCst);
+        platform_view
+          .update_prop(PropKey::VALUE, text)
+          .expect("view is there");
+      }
+      ready(())
+    });
+
+    self.on_remove.push(spawn_future(f));
+    self
+  }
+
+  pub fn on_change<F>(mut self, f: F) -> Self
+  where
+    F: Fn(String) + Send + Sync + 'static,
+  {
+    self.on_change = Some(Arc::new(f));
+    self
+  }
+
+  pub fn handle(&self) -> TextInputHandle {
+    TextInputHandle {
+      platform_view: self.platform_view.clone(),
+      on_change: self.on_change.clone(),
+      echo_guard: self.echo_guard.clone(),
+      value_pushes: self.value_pushes.clone(),
+    }
+  }
+}
+
+impl Composable for TextInput {
+  fn compose(&mut self, composer: &mut Composer) {
+    let on_remove = std::mem::replace(&mut self.on_remove, vec![]);
+    composer
+      .add_view_with_futures(&mut self.platform_view, Some(on_remove))
+      .unwrap();
+  }
+}
+
+pub struct Image {
+  platform_view: PlatformView,
+}
+
+impl Image {
+  pub fn new(source: ImageSource) -> Self {
+    let mut platform_view = DummyPlatformView::new("Image");
+    source.apply(&mut platform_view).unwrap();
+    Image { platform_view }
+  }
+}
+
+impl Composable for Image {
+  fn compose(&mut self, composer: &mut Composer) {
+    composer.add_view(&mut self.platform_view).unwrap();
+  }
+}
+
+pub struct ScrollView {
+  pub underlying_view: PlatformView,
+  scroll_offset: Mutable<f32>,
+}
+
+/// Both the programmatic scrolling the real handle has, and the user dragging the view
+#[derive(Clone)]
+pub struct ScrollViewHandle {
+  underlying_view: PlatformView,
+  scroll_offset: Mutable<f32>,
+}
+
+impl ScrollViewHandle {
+  pub fn scroll_to(&self, offset: f32, animated: bool) {
+    let key = if animated {
+      PropKey::SMOOTH_SCROLL_TO
+    } else {
+      PropKey::SCROLL_TO
+    };
+    self
+      .underlying_view
+      .clone()
+      .update_prop(key, offset)
+      .unwrap();
+    self.user_scroll(offset);
+  }
+
+  pub fn user_scroll(&self, offset: f32) {
+    let mut lock = self.scroll_offset.lock_mut();
+    *lock = offset;
+  }
+}
+
+impl ScrollView {
+  pub fn new() -> Self {
+    ScrollView {
+      underlying_view: DummyPlatformView::new("ScrollView"),
+      scroll_offset: Mutable::new(0.0),
+    }
+  }
+
+  pub fn with<F>(mut self, f: F) -> Self
+  where
+    F: FnOnce(),
+  {
+    with_parent(&mut self.underlying_view, f);
+    self
+  }
+
+  pub fn scroll_offset_signal(&self) -> impl Signal<Item = f32> + Send {
+    self.scroll_offset.signal()
+  }
+
+  pub fn handle(&self) -> ScrollViewHandle {
+    ScrollViewHandle {
+      underlying_view: self.underlying_view.clone(),
+      scroll_offset: self.scroll_offset.clone(),
+    }
+  }
+}
+
+impl Composable for ScrollView {
+  fn compose(&mut self, composer: &mut Composer) {
+    composer.add_view(&mut self.underlying_view).unwrap();
+  }
+}
+
+impl<T> VirtualList<T>
+where
+  T: Clone + PartialEq + Send + Sync + 'static,
+{
+  /// Same as the Android build, with a handle to simulate the user scrolling
+  pub fn build_dummy<B>(self, builder: B) -> (VirtualListHandle, ScrollViewHandle)
+  where
+    B: Fn(Row<T>),
+  {
+    let row_height = self.row_height.resolve(Orientation::Vertical);
+    let scroll_view = ScrollView::new();
+    let scroll_handle = scroll_view.handle();
+    let scroll_offset = scroll_view.scroll_offset_signal();
+
+    let mut visible_range = None;
+    scroll_view.with(|| {
+      StackLayout::new().with(|| {
+        visible_range = Some(self.compose_rows(
+          scroll_offset,
+          || DummyPlatformView::new("StackLayout"),
+          builder,
+        ));
+      });
+    });
+
+    let scroll_handle_clone = scroll_handle.clone();
+    let list_handle = VirtualListHandle::new(
+      visible_range.expect("Rows were composed"),
+      row_height,
+      Box::new(move |offset, animated| scroll_handle_clone.scroll_to(offset, animated)),
+    );
+    (list_handle, scroll_handle)
+  }
+}
+
+impl<R> Router<R>
+where
+  R: Clone + Send + Sync + 'static,
+{
+  /// Same as the Android build
+  pub fn build_dummy<F>(&self, render: F) -> StackLayout
+  where
+    F: Fn(&R, &RouteState) + Send + 'static,
+  {
+    let layout = StackLayout::new();
+    self.compose_routes(layout.underlying_view.clone(), render);
+    layout
+  }
+}
+
 impl Composable for StackLayout {
   fn compose(&mut self, composer: &mut Composer) {
     info!("Composing stack layout");
@@ -316,4 +764,7 @@ impl Composable for StackLayout {
 
 auto_compose!(StackLayout);
 auto_compose!(Text);
+auto_compose!(TextInput);
+auto_compose!(Image);
+auto_compose!(ScrollView);
 auto_compose_T!(Button<T>);
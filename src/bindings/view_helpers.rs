@@ -1,23 +1,32 @@
 use crate::bindings::callback::Callback;
-use crate::style;
-use futures_signals::signal::{Mutable, Signal, SignalExt};
-use std::error::Error;
+use crate::bindings::prop_key::PropKey;
+use crate::error::WiredError;
+use crate::lifecycle::configuration_signal;
+use crate::style::{Dimension, EdgeInsets, Fling, Orientation, PropEnum};
+use futures_signals::signal::{Broadcaster, Signal, SignalExt};
 
+/// Keys are the `PropKey` constants generated from the widget spec, so a key the host doesn't
+/// know about can't be used by accident
 pub trait UpdateProp<T> {
-  fn update_prop(&mut self, k: &str, v: T) -> Result<(), Box<dyn Error>>;
+  fn update_prop(&mut self, k: PropKey, v: T) -> Result<(), WiredError>;
+}
+
+/// What platform views implement
+pub trait SetProp<T> {
+  fn set_prop(&mut self, k: PropKey, v: T) -> Result<(), WiredError>;
 }
 
 pub trait UpdatePropSignal<T> {
-  fn update_prop_signal<S>(&mut self, k: &'static str, s: S) -> Result<(), Box<dyn Error>>
+  fn update_prop_signal<S>(&mut self, k: PropKey, s: S) -> Result<(), WiredError>
   where
     S: 'static + Signal<Item = T> + Send;
 }
 
 macro_rules! prop_method {
-    ($i:ident, $t:ty) => {
+    ($i:ident, $key:ident, $t:ty) => {
       fn $i(mut self, f: $t) -> Self {
         self
-          .update_prop(stringify!($i), f)
+          .update_prop(PropKey::$key, f)
           .expect(stringify!("Couldn't update", stringify($i)));
         self
       }
@@ -25,13 +34,82 @@ macro_rules! prop_method {
 }
 
 macro_rules! prop_method_signal {
-  ($i:ident, $t:ty) => {
+  ($i:ident, $key:ident, $t:ty) => {
     paste::item! {
       fn [<$i _signal>] <S>(mut self, s: S) -> Self
       where
         S: 'static + Signal<Item = $t> + Send {
         self
-          .update_prop_signal(stringify!($i), s)
+          .update_prop_signal(PropKey::$key, s)
+          .expect(stringify!("Couldn't update from signal: ", stringify($i)));
+        self
+      }
+    }
+  };
+}
+
+macro_rules! enum_prop_method {
+    ($i:ident, $key:ident, $t:ty) => {
+      fn $i(mut self, v: $t) -> Self {
+        self
+          .update_prop(PropKey::$key, v.code())
+          .expect(stringify!("Couldn't update", stringify($i)));
+        self
+      }
+    };
+}
+
+macro_rules! enum_prop_method_signal {
+  ($i:ident, $key:ident, $t:ty) => {
+    paste::item! {
+      fn [<$i _signal>] <S>(mut self, s: S) -> Self
+      where
+        S: 'static + Signal<Item = $t> + Send {
+        self
+          .update_prop_signal(PropKey::$key, s.map(|v| v.code()))
+          .expect(stringify!("Couldn't update from signal: ", stringify($i)));
+        self
+      }
+    }
+  };
+}
+
+// Dimensions are resolved to physical pixels on the Rust side, so the view only ever sees an f32.
+// The axis is what a Percent is relative to. Anything but Px is resolved again when the screen
+// changes (e.g. on rotation).
+macro_rules! dimension_method {
+    ($i:ident, $key:ident, $axis:expr) => {
+      fn $i<D: Into<Dimension>>(mut self, d: D) -> Self {
+        let d: Dimension = d.into();
+        self
+          .update_prop(PropKey::$key, d.resolve($axis))
+          .expect(stringify!("Couldn't update", stringify($i)));
+        if let Dimension::Px(_) = d {
+          return self;
+        }
+        self
+          .update_prop_signal(
+            PropKey::$key,
+            configuration_signal().map(move |metrics| d.resolve_with(&metrics, $axis)),
+          )
+          .expect(stringify!("Couldn't update from configuration: ", stringify($i)));
+        self
+      }
+    };
+}
+
+macro_rules! dimension_method_signal {
+  ($i:ident, $key:ident, $axis:expr) => {
+    paste::item! {
+      fn [<$i _signal>] <S, D>(mut self, s: S) -> Self
+      where
+        S: 'static + Signal<Item = D> + Send,
+        D: Into<Dimension> {
+        self
+          .update_prop_signal(PropKey::$key, s.switch(|d| {
+            let d: Dimension = d.into();
+            configuration_signal().map(move |metrics| d.resolve_with(&metrics, $axis))
+          }))
           .expect(stringify!("Couldn't update from signal: ", stringify($i)));
         self
       }
@@ -40,61 +118,153 @@ macro_rules! prop_method_signal {
 }
 
 pub trait Padding: UpdateProp<f32> + UpdatePropSignal<f32> + Sized {
-  prop_method_signal!(pad_left, f32);
-  prop_method_signal!(pad_top, f32);
-  prop_method_signal!(pad_right, f32);
-  prop_method_signal!(pad_bottom, f32);
+  dimension_method_signal!(pad_left, PAD_LEFT, Orientation::Horizontal);
+  dimension_method_signal!(pad_top, PAD_TOP, Orientation::Vertical);
+  dimension_method_signal!(pad_right, PAD_RIGHT, Orientation::Horizontal);
+  dimension_method_signal!(pad_bottom, PAD_BOTTOM, Orientation::Vertical);
 
-  prop_method!(pad_left, f32);
-  prop_method!(pad_top, f32);
-  prop_method!(pad_right, f32);
-  prop_method!(pad_bottom, f32);
+  dimension_method!(pad_left, PAD_LEFT, Orientation::Horizontal);
+  dimension_method!(pad_top, PAD_TOP, Orientation::Vertical);
+  dimension_method!(pad_right, PAD_RIGHT, Orientation::Horizontal);
+  dimension_method!(pad_bottom, PAD_BOTTOM, Orientation::Vertical);
+
+  fn padding(self, insets: EdgeInsets) -> Self {
+    self
+      .pad_left(insets.left)
+      .pad_top(insets.top)
+      .pad_right(insets.right)
+      .pad_bottom(insets.bottom)
+  }
+
+  fn padding_signal<S>(self, s: S) -> Self
+  where
+    S: 'static + Signal<Item = EdgeInsets> + Send,
+  {
+    let insets = split_insets_signal(s);
+    self
+      .pad_left_signal(insets.signal().map(|i| i.left))
+      .pad_top_signal(insets.signal().map(|i| i.top))
+      .pad_right_signal(insets.signal().map(|i| i.right))
+      .pad_bottom_signal(insets.signal().map(|i| i.bottom))
+  }
+}
+
+pub trait Margin: UpdateProp<f32> + UpdatePropSignal<f32> + Sized {
+  dimension_method_signal!(margin_left, MARGIN_LEFT, Orientation::Horizontal);
+  dimension_method_signal!(margin_top, MARGIN_TOP, Orientation::Vertical);
+  dimension_method_signal!(margin_right, MARGIN_RIGHT, Orientation::Horizontal);
+  dimension_method_signal!(margin_bottom, MARGIN_BOTTOM, Orientation::Vertical);
+
+  dimension_method!(margin_left, MARGIN_LEFT, Orientation::Horizontal);
+  dimension_method!(margin_top, MARGIN_TOP, Orientation::Vertical);
+  dimension_method!(margin_right, MARGIN_RIGHT, Orientation::Horizontal);
+  dimension_method!(margin_bottom, MARGIN_BOTTOM, Orientation::Vertical);
+
+  fn margin(self, insets: EdgeInsets) -> Self {
+    self
+      .margin_left(insets.left)
+      .margin_top(insets.top)
+      .margin_right(insets.right)
+      .margin_bottom(insets.bottom)
+  }
+
+  fn margin_signal<S>(self, s: S) -> Self
+  where
+    S: 'static + Signal<Item = EdgeInsets> + Send,
+  {
+    let insets = split_insets_signal(s);
+    self
+      .margin_left_signal(insets.signal().map(|i| i.left))
+      .margin_top_signal(insets.signal().map(|i| i.top))
+      .margin_right_signal(insets.signal().map(|i| i.right))
+      .margin_bottom_signal(insets.signal().map(|i| i.bottom))
+  }
+}
+
+// Each edge is its own prop, so every edge watches a broadcast of the one signal. The edges only
+// see a value once the signal has one, and it's polled by the edges' own futures, so it stops
+// with them when the view is removed.
+fn split_insets_signal<S>(s: S) -> Broadcaster<S>
+where
+  S: 'static + Signal<Item = EdgeInsets> + Send,
+{
+  Broadcaster::new(s)
 }
 
 pub trait SetText: UpdateProp<String> + UpdatePropSignal<String> + Sized {
   fn text<T: Into<String>>(mut self, s: T) -> Self {
     self
-      .update_prop("text", s.into())
+      .update_prop(PropKey::TEXT, s.into())
       .expect("Couldn't update string");
     self
   }
-  prop_method_signal!(text, String);
+  prop_method_signal!(text, TEXT, String);
 }
 
 pub trait SetTextSize: UpdateProp<f32> + UpdatePropSignal<f32> + Sized {
-  prop_method!(text_size, f32);
-  prop_method_signal!(text_size, f32);
+  dimension_method!(text_size, TEXT_SIZE, Orientation::Vertical);
+  dimension_method_signal!(text_size, TEXT_SIZE, Orientation::Vertical);
 }
 
 pub trait SetXY: UpdateProp<f32> + UpdatePropSignal<f32> + Sized {
-  prop_method!(set_x, f32);
-  prop_method!(set_y, f32);
+  dimension_method!(set_x, SET_X, Orientation::Horizontal);
+  dimension_method!(set_y, SET_Y, Orientation::Vertical);
 
-  prop_method_signal!(set_x, f32);
-  prop_method_signal!(set_y, f32);
+  dimension_method_signal!(set_x, SET_X, Orientation::Horizontal);
+  dimension_method_signal!(set_y, SET_Y, Orientation::Vertical);
 }
 
-pub trait OnPress: UpdateProp<Callback> + UpdatePropSignal<Callback> + Sized {
-  prop_method!(on_press, Callback);
-  prop_method_signal!(on_press, Callback);
+/// `E` is the payload the callback wants, e.g. `()` or `PressEvent` for where the press happened
+pub trait OnPress: Sized {
+  fn on_press<E>(mut self, cb: Callback<E>) -> Self
+  where
+    Self: UpdateProp<Callback<E>>,
+  {
+    self
+      .update_prop(PropKey::ON_PRESS, cb)
+      .expect("Couldn't update on_press");
+    self
+  }
+
+  fn on_press_signal<E, S>(mut self, s: S) -> Self
+  where
+    E: 'static,
+    Self: UpdatePropSignal<Callback<E>>,
+    S: 'static + Signal<Item = Callback<E>> + Send,
+  {
+    self
+      .update_prop_signal(PropKey::ON_PRESS, s)
+      .expect("Couldn't update on_press from signal");
+    self
+  }
+
+  fn on_long_press(mut self, cb: Callback) -> Self
+  where
+    Self: UpdateProp<Callback>,
+  {
+    self
+      .update_prop(PropKey::ON_LONG_PRESS, cb)
+      .expect("Couldn't update on_long_press");
+    self
+  }
 }
 
 pub trait SetHeightWidth: UpdateProp<f32> + UpdatePropSignal<f32> + Sized {
-  prop_method!(height, f32);
-  prop_method!(width, f32);
+  dimension_method!(height, HEIGHT, Orientation::Vertical);
+  dimension_method!(width, WIDTH, Orientation::Horizontal);
 
-  prop_method_signal!(height, f32);
-  prop_method_signal!(width, f32);
+  dimension_method_signal!(height, HEIGHT, Orientation::Vertical);
+  dimension_method_signal!(width, WIDTH, Orientation::Horizontal);
 }
 
-pub trait SetOrientation: UpdateProp<String> + Sized {
-  fn orientation(mut self, o: style::Orientation) -> Self {
-    let string: String = o.to_string();
-    self
-      .update_prop("orientation", string)
-      .expect("Couldn't update orientation");
-    self
-  }
+pub trait SetOrientation: UpdateProp<i32> + UpdatePropSignal<i32> + Sized {
+  enum_prop_method!(orientation, ORIENTATION, Orientation);
+  enum_prop_method_signal!(orientation, ORIENTATION, Orientation);
+}
+
+pub trait SetFling: UpdateProp<i32> + UpdatePropSignal<i32> + Sized {
+  enum_prop_method!(fling, FLING, Fling);
+  enum_prop_method_signal!(fling, FLING, Fling);
 }
 
 pub trait ParentWith: Sized {
@@ -0,0 +1,322 @@
+#[cfg(target_os = "android")]
+use crate::android_executor::spawn_future;
+use crate::bindings::prop_key::PropKey;
+use crate::bindings::view_helpers::UpdateProp;
+use crate::helpers::apply_vec_diff;
+use crate::style::{Dimension, Orientation};
+#[cfg(not(target_os = "android"))]
+use crate::ui_tree::spawn_future;
+use crate::ui_tree::{with_parent, AttachedFutures, PlatformView, COMPOSER};
+use futures::future::{ready, BoxFuture, FutureExt};
+use futures_signals::signal::{Mutable, ReadOnlyMutable, Signal, SignalExt};
+use futures_signals::signal_vec::{SignalVec, SignalVecExt};
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+/// Which rows should be composed, given where the list is scrolled to.
+/// `buffer` rows are kept on either side of the viewport so that small scrolls don't show gaps.
+pub fn visible_range(
+  offset: f32,
+  row_height: f32,
+  viewport_height: f32,
+  buffer: usize,
+  count: usize,
+) -> Range<usize> {
+  if row_height <= 0.0 || count == 0 {
+    return 0..0;
+  }
+  let offset = offset.max(0.0);
+  let first = (offset / row_height).floor() as usize;
+  let last = ((offset + viewport_height) / row_height).ceil() as usize;
+  let start = first.saturating_sub(buffer).min(count);
+  let end = (last + buffer).min(count);
+  start..end
+}
+
+/// How many row views we need so that any visible range fits
+fn slot_count(row_height: f32, viewport_height: f32, buffer: usize) -> usize {
+  if row_height <= 0.0 {
+    return 0;
+  }
+  (viewport_height / row_height).ceil() as usize + 1 + 2 * buffer
+}
+
+/// What the row builder gets. A row view is reused for whatever item scrolls into its place,
+/// so build it from these signals rather than from a fixed value.
+pub struct Row<T> {
+  item: Mutable<Option<(usize, T)>>,
+}
+
+impl<T: Clone + Send + Sync + 'static> Row<T> {
+  /// The item this row is showing, None while the row is unused
+  pub fn item_signal(&self) -> impl Signal<Item = Option<T>> + Send {
+    self.item.signal_cloned().map(|item| item.map(|(_, item)| item))
+  }
+
+  pub fn index_signal(&self) -> impl Signal<Item = Option<usize>> + Send {
+    self.item.signal_cloned().map(|item| item.map(|(idx, _)| idx))
+  }
+}
+
+pub struct VirtualListHandle {
+  visible_range: ReadOnlyMutable<Range<usize>>,
+  row_height: f32,
+  scroll_to: Box<dyn Fn(f32, bool) + Send + Sync>,
+}
+
+impl VirtualListHandle {
+  pub(crate) fn new(
+    visible_range: ReadOnlyMutable<Range<usize>>,
+    row_height: f32,
+    scroll_to: Box<dyn Fn(f32, bool) + Send + Sync>,
+  ) -> Self {
+    VirtualListHandle {
+      visible_range,
+      row_height,
+      scroll_to,
+    }
+  }
+
+  /// Indices of the rows that are currently composed
+  pub fn visible_range_signal(&self) -> impl Signal<Item = Range<usize>> + Send {
+    self.visible_range.signal_cloned()
+  }
+
+  pub fn scroll_to_index(&self, idx: usize, animated: bool) {
+    (self.scroll_to)(idx as f32 * self.row_height, animated)
+  }
+}
+
+struct Window<T> {
+  items: Vec<T>,
+  offset: f32,
+}
+
+// Everything the source and scroll futures share
+struct VirtualListState<T> {
+  window: Mutex<Window<T>>,
+  slots: Vec<(Mutable<Option<(usize, T)>>, PlatformView)>,
+  top_spacer: PlatformView,
+  bottom_spacer: PlatformView,
+  visible_range: Mutable<Range<usize>>,
+  row_height: f32,
+  viewport_height: f32,
+  buffer: usize,
+}
+
+impl<T: Clone + PartialEq> VirtualListState<T> {
+  // The list is laid out as [top spacer, slots.., bottom spacer]. The spacers stand in for the
+  // rows that aren't composed, and slot n always shows item range.start + n.
+  fn refresh(&self) {
+    let window = self.window.lock().unwrap();
+    let count = window.items.len();
+    let range = visible_range(
+      window.offset,
+      self.row_height,
+      self.viewport_height,
+      self.buffer,
+      count,
+    );
+
+    self
+      .top_spacer
+      .clone()
+      .update_prop(PropKey::HEIGHT, range.start as f32 * self.row_height)
+      .expect("Couldn't update spacer");
+    self
+      .bottom_spacer
+      .clone()
+      .update_prop(
+        PropKey::HEIGHT,
+        (count - range.end) as f32 * self.row_height,
+      )
+      .expect("Couldn't update spacer");
+
+    for (n, (item, view)) in self.slots.iter().enumerate() {
+      let idx = range.start + n;
+      let next = if idx < range.end {
+        Some((idx, window.items[idx].clone()))
+      } else {
+        None
+      };
+      if *item.lock_ref() == next {
+        continue;
+      }
+      let was_used = item.lock_ref().is_some();
+      if was_used != next.is_some() {
+        let height = if next.is_some() { self.row_height } else { 0.0 };
+        view
+          .clone()
+          .update_prop(PropKey::HEIGHT, height)
+          .expect("Couldn't update row height");
+      }
+      let mut lock = item.lock_mut();
+      *lock = next;
+    }
+
+    if *self.visible_range.lock_ref() != range {
+      let mut lock = self.visible_range.lock_mut();
+      *lock = range;
+    }
+  }
+}
+
+type ItemsSource<T> = Box<dyn FnOnce(Arc<VirtualListState<T>>) -> BoxFuture<'static, ()>>;
+
+/// A list that only composes the rows around the viewport. Every row has the same height.
+/// Build it with the `build` of the backend you're using.
+pub struct VirtualList<T> {
+  source: ItemsSource<T>,
+  pub(crate) row_height: Dimension,
+  pub(crate) viewport_height: Dimension,
+  buffer: usize,
+}
+
+impl VirtualList<usize> {
+  /// Rows are just indices, for when the data lives elsewhere
+  pub fn with_count<S>(count: S) -> Self
+  where
+    S: 'static + Signal<Item = usize> + Send,
+  {
+    VirtualList::new(Box::new(move |state: Arc<VirtualListState<usize>>| {
+      count
+        .for_each(move |count| {
+          state.window.lock().unwrap().items = (0..count).collect();
+          state.refresh();
+          ready(())
+        })
+        .boxed()
+    }))
+  }
+}
+
+impl<T> VirtualList<T>
+where
+  T: Clone + PartialEq + Send + Sync + 'static,
+{
+  fn new(source: ItemsSource<T>) -> Self {
+    VirtualList {
+      source,
+      row_height: Dimension::Dp(48.0),
+      viewport_height: Dimension::Percent(100.0),
+      buffer: 4,
+    }
+  }
+
+  pub fn from_signal_vec<S>(items: S) -> Self
+  where
+    S: 'static + SignalVec<Item = T> + Send,
+  {
+    VirtualList::new(Box::new(move |state: Arc<VirtualListState<T>>| {
+      items
+        .for_each(move |diff| {
+          apply_vec_diff(&mut state.window.lock().unwrap().items, diff);
+          state.refresh();
+          ready(())
+        })
+        .boxed()
+    }))
+  }
+
+  pub fn row_height<D: Into<Dimension>>(mut self, d: D) -> Self {
+    self.row_height = d.into();
+    self
+  }
+
+  /// How tall the scrolling area is
+  pub fn viewport_height<D: Into<Dimension>>(mut self, d: D) -> Self {
+    self.viewport_height = d.into();
+    self
+  }
+
+  /// Rows composed past each edge of the viewport
+  pub fn buffer(mut self, buffer: usize) -> Self {
+    self.buffer = buffer;
+    self
+  }
+
+  /// Composes the spacers and row slots into the current parent, and keeps them up to date as
+  /// the items and the scroll offset change. `make_container` creates the backend's plain layout.
+  pub(crate) fn compose_rows<O, C, B>(
+    self,
+    scroll_offset: O,
+    make_container: C,
+    builder: B,
+  ) -> ReadOnlyMutable<Range<usize>>
+  where
+    O: 'static + Signal<Item = f32> + Send,
+    C: Fn() -> PlatformView,
+    B: Fn(Row<T>),
+  {
+    let row_height = self.row_height.resolve(Orientation::Vertical);
+    let viewport_height = self.viewport_height.resolve(Orientation::Vertical);
+    let slots = (0..slot_count(row_height, viewport_height, self.buffer))
+      .map(|_| {
+        let mut view = make_container();
+        view
+          .update_prop(PropKey::HEIGHT, 0.0f32)
+          .expect("Couldn't update row height");
+        (Mutable::new(None), view)
+      })
+      .collect();
+
+    let state = Arc::new(VirtualListState {
+      window: Mutex::new(Window {
+        items: vec![],
+        offset: 0.0,
+      }),
+      slots,
+      top_spacer: make_container(),
+      bottom_spacer: make_container(),
+      visible_range: Mutable::new(0..0),
+      row_height,
+      viewport_height,
+      buffer: self.buffer,
+    });
+
+    add_to_current_parent(&mut state.top_spacer.clone(), vec![]);
+    for (item, view) in state.slots.iter() {
+      let mut view = view.clone();
+      let row = Row { item: item.clone() };
+      with_parent(&mut view, || builder(row));
+      add_to_current_parent(&mut view, vec![]);
+    }
+
+    let state_clone = state.clone();
+    let f = scroll_offset.for_each(move |offset| {
+      state_clone.window.lock().unwrap().offset = offset;
+      state_clone.refresh();
+      ready(())
+    });
+    // The list goes away with its views, the last one takes the futures along
+    let futures = vec![
+      spawn_future(f),
+      spawn_future((self.source)(state.clone())),
+    ];
+    add_to_current_parent(&mut state.bottom_spacer.clone(), futures);
+
+    state.visible_range.read_only()
+  }
+}
+
+fn add_to_current_parent(view: &mut PlatformView, futures: AttachedFutures) {
+  COMPOSER.with(|c| {
+    let mut composer = c.borrow_mut();
+    composer
+      .add_view_with_futures(view, Some(futures))
+      .expect("Couldn't add view");
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn visible_range_includes_buffer() {
+    assert_eq!(visible_range(0.0, 10.0, 100.0, 2, 10_000), 0..12);
+    assert_eq!(visible_range(500.0, 10.0, 100.0, 2, 10_000), 48..62);
+    assert_eq!(visible_range(99_950.0, 10.0, 100.0, 2, 10_000), 9993..10_000);
+    assert_eq!(visible_range(0.0, 10.0, 100.0, 2, 0), 0..0);
+  }
+}
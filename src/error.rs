@@ -0,0 +1,103 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+/// What the bindings asked Java to do, for errors about it
+#[derive(Clone, Debug, PartialEq)]
+pub struct JavaOp {
+  pub method: String,
+  /// The kind of view it was called for
+  pub view: Option<String>,
+  pub key: Option<String>,
+}
+
+impl JavaOp {
+  pub fn call<S: Into<String>>(method: S) -> Self {
+    JavaOp {
+      method: method.into(),
+      view: None,
+      key: None,
+    }
+  }
+
+  pub fn on_view<S: Into<String>>(mut self, view: S) -> Self {
+    self.view = Some(view.into());
+    self
+  }
+
+  pub fn with_key<S: Into<String>>(mut self, key: S) -> Self {
+    self.key = Some(key.into());
+    self
+  }
+}
+
+impl Display for JavaOp {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}", self.method)?;
+    if let Some(key) = &self.key {
+      write!(f, "({})", key)?;
+    }
+    if let Some(view) = &self.view {
+      write!(f, " on {}", view)?;
+    }
+    Ok(())
+  }
+}
+
+/// Everything the bindings and the composer can fail with, so callers can match on what went wrong
+#[derive(Debug)]
+pub enum WiredError {
+  /// Views were composed before `set_root_view`, so there's no parent to add them to
+  NoRootView,
+  /// A view was made before `init` set up the view factory
+  NoViewFactory,
+  /// A JNI call failed without Java throwing, e.g. a bad signature or a null where an object was
+  /// expected
+  Jni(jni::errors::Error),
+  /// A prop value was handed to a view that doesn't know how to send that type across the bridge
+  UnsupportedPropType { view: &'static str, key: String },
+  /// A prop key that isn't in the widget spec, so the host would ignore it
+  UnknownProp { key: String },
+  /// A view command buffer that doesn't decode, or refers to a view or handler it never made
+  BadCommandBuffer { offset: usize, reason: String },
+  /// Java threw while doing `op`. The exception has been logged and cleared.
+  JavaException {
+    class: String,
+    message: Option<String>,
+    op: JavaOp,
+  },
+  /// The composer's position bookkeeping doesn't match the views it added, which is a bug
+  ComposerInvariant(String),
+}
+
+impl Display for WiredError {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      WiredError::NoRootView => write!(f, "No root view set"),
+      WiredError::NoViewFactory => write!(f, "No view factory, call init first"),
+      WiredError::Jni(e) => write!(f, "JNI call failed: {}", e),
+      WiredError::UnsupportedPropType { view, key } => {
+        write!(f, "Unsupported value type for prop {} on {}", key, view)
+      }
+      WiredError::UnknownProp { key } => write!(f, "Unknown prop {}", key),
+      WiredError::BadCommandBuffer { offset, reason } => {
+        write!(f, "Bad command buffer at byte {}: {}", offset, reason)
+      }
+      WiredError::JavaException { class, message, op } => {
+        write!(f, "{} threw {}", op, class)?;
+        match message {
+          Some(message) => write!(f, ": {}", message),
+          None => Ok(()),
+        }
+      }
+      WiredError::ComposerInvariant(reason) => write!(f, "Composer invariant violated: {}", reason),
+    }
+  }
+}
+
+impl Error for WiredError {}
+
+impl From<jni::errors::Error> for WiredError {
+  fn from(e: jni::errors::Error) -> Self {
+    WiredError::Jni(e)
+  }
+}
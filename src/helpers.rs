@@ -2,10 +2,13 @@
 use crate::android_executor::spawn_future;
 #[cfg(not(target_os = "android"))]
 use crate::ui_tree::spawn_future;
-use crate::ui_tree::{Composer, COMPOSER};
+use crate::ui_tree::{AttachedFutures, Composer, COMPOSER};
 use discard::DiscardOnDrop;
 use futures::future::ready;
 use futures_signals::signal::{Mutable, ReadOnlyMutable, Signal, SignalExt};
+use futures_signals::signal_vec::{SignalVec, SignalVecExt, VecDiff};
+
+pub use crate::saved_state::{use_saved_state, SavedMutable};
 
 pub fn if_signal<S, F>(s: S, f: F)
 where
@@ -55,6 +58,144 @@ where
   DiscardOnDrop::leak(spawn_future(fut));
 }
 
+/// Runs `f` for every item. A diff only composes the items it adds and removes the ones it takes
+/// out, the rest of the list is left alone.
+pub fn for_signal_vec<S, F, T>(s: S, f: F)
+where
+  S: SignalVec<Item = T> + Send + 'static,
+  F: Fn(T) + Send + 'static,
+  T: Clone + Send + Sync + 'static,
+{
+  // Like match_signal, the list's views count towards the frame it's in, and what comes after
+  // the list goes in a new one
+  let composer = COMPOSER.with(|c| {
+    let mut composer = c.borrow_mut();
+    let list_composer = composer.clone();
+    composer.position_context.push_new_frame();
+    list_composer
+  });
+  let mut list = ComposedList {
+    composer,
+    items: vec![],
+    f,
+  };
+  let fut = s.for_each(move |diff| {
+    list.apply(diff);
+    ready(())
+  });
+
+  let handle = spawn_future(fut);
+  COMPOSER.with(|c| c.borrow_mut().attach_futures(vec![handle]));
+}
+
+// What one item of a for_signal_vec composed
+struct ListItem<T> {
+  value: T,
+  views: usize,
+  // Stop with the item
+  _futures: AttachedFutures,
+}
+
+struct ComposedList<T, F> {
+  composer: Composer,
+  items: Vec<ListItem<T>>,
+  f: F,
+}
+
+impl<T: Clone, F: Fn(T)> ComposedList<T, F> {
+  fn apply(&mut self, diff: VecDiff<T>) {
+    match diff {
+      VecDiff::Replace { values } => {
+        self.clear();
+        for value in values {
+          self.insert(self.items.len(), value);
+        }
+      }
+      VecDiff::InsertAt { index, value } => self.insert(index, value),
+      VecDiff::UpdateAt { index, value } => {
+        self.remove(index);
+        self.insert(index, value);
+      }
+      VecDiff::RemoveAt { index } => {
+        self.remove(index);
+      }
+      VecDiff::Move {
+        old_index,
+        new_index,
+      } => {
+        let value = self.remove(old_index);
+        self.insert(new_index, value);
+      }
+      VecDiff::Push { value } => self.insert(self.items.len(), value),
+      VecDiff::Pop {} => {
+        self.remove(self.items.len() - 1);
+      }
+      VecDiff::Clear {} => self.clear(),
+    }
+  }
+
+  // Where the item at `idx` starts in the parent
+  fn start(&self, idx: usize) -> usize {
+    let views = |items: &[ListItem<T>]| items.iter().map(|item| item.views).sum::<usize>();
+    self.composer.next_idx() - views(&self.items) + views(&self.items[..idx])
+  }
+
+  fn insert(&mut self, idx: usize, value: T) {
+    let start = self.start(idx);
+    let f = &self.f;
+    let item_value = value.clone();
+    let (views, futures) = self.composer.compose_at(start, || f(item_value));
+    self.items.insert(
+      idx,
+      ListItem {
+        value,
+        views,
+        _futures: futures,
+      },
+    );
+  }
+
+  fn remove(&mut self, idx: usize) -> T {
+    let start = self.start(idx);
+    let ListItem { value, views, .. } = self.items.remove(idx);
+    self
+      .composer
+      .remove_views_at(start, views)
+      .expect("Couldn't remove list item");
+    value
+  }
+
+  fn clear(&mut self) {
+    while !self.items.is_empty() {
+      self.remove(self.items.len() - 1);
+    }
+  }
+}
+
+/// Keeps a plain Vec in sync with a SignalVec, one diff at a time
+pub(crate) fn apply_vec_diff<T>(values: &mut Vec<T>, diff: VecDiff<T>) {
+  match diff {
+    VecDiff::Replace { values: new_values } => *values = new_values,
+    VecDiff::InsertAt { index, value } => values.insert(index, value),
+    VecDiff::UpdateAt { index, value } => values[index] = value,
+    VecDiff::RemoveAt { index } => {
+      values.remove(index);
+    }
+    VecDiff::Move {
+      old_index,
+      new_index,
+    } => {
+      let value = values.remove(old_index);
+      values.insert(new_index, value);
+    }
+    VecDiff::Push { value } => values.push(value),
+    VecDiff::Pop {} => {
+      values.pop();
+    }
+    VecDiff::Clear {} => values.clear(),
+  }
+}
+
 pub struct ReadOnlyState<T>(ReadOnlyMutable<T>);
 impl<T: Copy> ReadOnlyState<T> {
   pub fn get(&self) -> T {
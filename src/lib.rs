@@ -1,6 +1,5 @@
 // #![feature(trace_macros)]
 #![allow(dead_code, unused_imports)]
-pub mod ui_tree;
 
 #[macro_use]
 mod macros {
@@ -17,6 +16,44 @@ mod macros {
         };
     }
 
+    /// Declares an enum whose variants cross the bridge as stable integer codes.
+    /// Codes are part of the protocol with the host, never renumber a variant.
+    macro_rules! prop_enum {
+        ($(#[$meta:meta])* pub enum $name:ident { $($variant:ident = $code:expr),* $(,)? }) => {
+            $(#[$meta])*
+            #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+            pub enum $name {
+                $($variant),*
+            }
+
+            impl crate::style::PropEnum for $name {
+                const NAME: &'static str = stringify!($name);
+                const VARIANTS: &'static [(&'static str, i32)] = &[$((stringify!($variant), $code)),*];
+
+                fn code(self) -> i32 {
+                    match self {
+                        $($name::$variant => $code),*
+                    }
+                }
+
+                fn from_code(code: i32) -> Option<Self> {
+                    match code {
+                        $(c if c == $code => Some($name::$variant),)*
+                        _ => None,
+                    }
+                }
+            }
+        };
+    }
+
+    /// `wired_macros::view!`, with this crate's path filled in so the expansion finds the helpers
+    /// wherever it's used.
+    macro_rules! view {
+        ($($tokens:tt)*) => {
+            ::wired_macros::view! { $crate; $($tokens)* }
+        };
+    }
+
     macro_rules! auto_compose_T {
         ($e:ty) => {
             impl<T> Drop for $e {
@@ -31,16 +68,22 @@ mod macros {
     }
 }
 
+pub mod ui_tree;
+
 pub mod android_executor;
 mod app;
 pub mod bindings;
+pub mod error;
 pub mod helpers;
+pub mod lifecycle;
+pub mod navigation;
+pub mod saved_state;
 pub mod style;
 
 mod slides;
 
 #[macro_use]
-extern crate update_prop_derive;
+extern crate wired_macros;
 
 #[macro_use]
 extern crate log;
@@ -55,13 +98,12 @@ use discard::DiscardOnDrop;
 use futures_signals::signal::{Mutable, Signal, SignalExt};
 use jni::errors::Error as JNIError;
 use jni::objects::{GlobalRef, JClass, JObject, JString, JValue};
-use jni::sys::jstring;
+use jni::sys::{jbyteArray, jfloat, jint, jstring};
 use jni::{JNIEnv, JavaVM};
 use log::Level;
 use std::ffi::{CStr, CString};
 use std::panic::{catch_unwind, RefUnwindSafe, UnwindSafe};
 
-use std::error::Error;
 use std::fmt::{self, Display, Formatter};
 
 use std::sync::Arc;
@@ -74,6 +116,8 @@ use std::cell::RefCell;
 use std::time::Duration;
 
 use android_executor::spawn_future;
+use bindings::callback::panic_message;
+use error::WiredError;
 use ui_tree::PlatformView;
 
 #[no_mangle]
@@ -93,13 +137,23 @@ pub unsafe extern "C" fn Java_dev_fruit_androiddemo_MainActivity_hello(
     output.into_inner()
 }
 
+#[cfg(target_os = "android")]
+thread_local! {
+    /// What the app's main returned. Its saved state has to be around whenever the host asks.
+    static APP: RefCell<Option<slides::App>> = RefCell::new(None);
+}
+
 #[cfg(target_os = "android")]
 #[no_mangle]
 pub unsafe extern "C" fn Java_dev_fruit_androiddemo_MainActivity_init(
     env: JNIEnv,
     _class: JClass,
     view_factory: JObject,
-    root_view: JObject,
+    density: jfloat,
+    scaled_density: jfloat,
+    width_px: jint,
+    height_px: jint,
+    saved_state: jbyteArray,
 ) {
     android_logger::init_once(
         Config::default()
@@ -108,28 +162,38 @@ pub unsafe extern "C" fn Java_dev_fruit_androiddemo_MainActivity_init(
     );
     info!("Started init");
 
-    let result = catch_unwind(move || {
-        let view_factory = env
-            .new_global_ref(view_factory)
-            .expect("Creating global ref should work");
+    lifecycle::set_configuration(style::DisplayMetrics {
+        density,
+        scaled_density,
+        width_px: width_px as f32,
+        height_px: height_px as f32,
+    });
+
+    // Null unless the activity is being recreated
+    if !saved_state.is_null() {
+        match env.convert_byte_array(saved_state) {
+            Ok(blob) => saved_state::restore_state(&blob),
+            Err(e) => warn!("Couldn't read saved state: {}", e),
+        }
+    }
+
+    let result = catch_unwind(move || -> Result<(), WiredError> {
+        android::jni_cache::init(&env)?;
 
-        let root_view = env
-            .new_global_ref(root_view)
-            .expect("Creating global ref should work");
+        let view_factory = env.new_global_ref(view_factory)?;
 
-        let jvm = Arc::new(env.get_java_vm().unwrap());
-        let jvm_clone = jvm.clone();
+        let jvm = Arc::new(env.get_java_vm()?);
         views::VIEWFACTORY.with(move |view_factory_ref| {
             *view_factory_ref.borrow_mut() = Some(views::ViewFactory::new(view_factory, jvm));
         });
 
-        let root_view = android::views::WiredNativeView {
-            kind: "StackLayout",
-            jvm: jvm_clone,
-            native_view: android::views::wrap_native_view(root_view),
-        };
+        // The host registered the root as ROOT_VIEW before calling us
+        let root_view =
+            bindings::command_buffer::RecordingView::root(&android::frame::buffer(), "StackLayout");
         ui_tree::set_root_view(PlatformView::new(root_view));
-        let _app_root = slides::main();
+        let app = slides::main();
+        APP.with(|app_ref| *app_ref.borrow_mut() = Some(app));
+        android::frame::end_frame(&env);
 
         // env.call_method(
         //     root_view.as_obj(),
@@ -138,27 +202,13 @@ pub unsafe extern "C" fn Java_dev_fruit_androiddemo_MainActivity_init(
         //     &[JValue::Object(app_root.get_native_view().unwrap().as_obj())],
         // )
         // .unwrap();
+        Ok(())
     });
 
+    // Errors from the bindings come back typed, anything else is a panic from app code
     match result {
-        Err(cause) => {
-            info!("I failed");
-            if let Some(reason) = cause.downcast_ref::<Box<dyn Error>>() {
-                info!("Failed because err: {}", reason);
-            }
-            if let Some(reason) = cause.downcast_ref::<std::cell::BorrowError>() {
-                info!("Failed because borrow err {}", reason);
-            }
-            if let Some(reason) = cause.downcast_ref::<std::cell::BorrowMutError>() {
-                info!("Failed because borrow mut err {}", reason);
-            }
-            if let Some(reason) = cause.downcast_ref::<String>() {
-                info!("Failed because str: {}", reason);
-            }
-            if let Some(reason) = cause.downcast_ref::<Box<dyn Display>>() {
-                info!("Failed because {}", reason);
-            }
-        }
-        _ => {}
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => error!("Init failed: {}", e),
+        Err(cause) => error!("Init panicked: {}", panic_message(&cause)),
     }
 }
@@ -0,0 +1,144 @@
+use crate::style::{display_metrics, set_display_metrics, DisplayMetrics};
+use discard::{Discard, DiscardOnDrop};
+use futures_signals::signal::{Mutable, Signal};
+use std::cell::{Cell, RefCell};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::rc::Rc;
+
+prop_enum! {
+  /// Where the host activity is in its lifecycle
+  pub enum LifecycleState {
+    Created = 0,
+    Resumed = 1,
+    Paused = 2,
+    Destroyed = 3,
+  }
+}
+
+type BackHandlerFn = Rc<dyn Fn() -> bool>;
+
+// The host calls in on its main thread, which is also where the executor runs
+thread_local! {
+  static LIFECYCLE: Mutable<LifecycleState> = Mutable::new(LifecycleState::Created);
+  static CONFIGURATION: Mutable<DisplayMetrics> = Mutable::new(display_metrics());
+  static BACK_HANDLERS: RefCell<Vec<(usize, BackHandlerFn)>> = RefCell::new(vec![]);
+  static NEXT_BACK_HANDLER_ID: Cell<usize> = Cell::new(0);
+}
+
+pub fn lifecycle_state() -> LifecycleState {
+  LIFECYCLE.with(|l| l.get())
+}
+
+pub fn lifecycle_signal() -> impl Signal<Item = LifecycleState> + Send {
+  LIFECYCLE.with(|l| l.signal())
+}
+
+/// Fires when the screen changes size or density, e.g. on rotation
+pub fn configuration_signal() -> impl Signal<Item = DisplayMetrics> + Send {
+  CONFIGURATION.with(|c| c.signal())
+}
+
+/// Keeps a back handler registered until it's dropped
+pub struct BackHandler {
+  id: usize,
+}
+
+impl Discard for BackHandler {
+  fn discard(self) {
+    BACK_HANDLERS.with(|handlers| handlers.borrow_mut().retain(|(id, _)| *id != self.id));
+  }
+}
+
+/// `handler` returns whether it consumed the back press. The newest handler is asked first,
+/// and if none of them consume it the host does its default (usually closing the activity).
+pub fn on_back_pressed<F>(handler: F) -> DiscardOnDrop<BackHandler>
+where
+  F: Fn() -> bool + 'static,
+{
+  let id = NEXT_BACK_HANDLER_ID.with(|next| {
+    let id = next.get();
+    next.set(id + 1);
+    id
+  });
+  BACK_HANDLERS.with(|handlers| handlers.borrow_mut().push((id, Rc::new(handler))));
+  DiscardOnDrop::new(BackHandler { id })
+}
+
+// These are what the host calls into. Tests call them directly to stand in for the host.
+
+pub fn set_lifecycle_state(state: LifecycleState) {
+  LIFECYCLE.with(|l| {
+    if l.get() != state {
+      let mut lock = l.lock_mut();
+      *lock = state;
+    }
+  })
+}
+
+pub fn set_configuration(metrics: DisplayMetrics) {
+  set_display_metrics(metrics);
+  CONFIGURATION.with(|c| {
+    let mut lock = c.lock_mut();
+    *lock = metrics;
+  });
+}
+
+/// Returns whether Rust consumed the back press. Handlers run right away, since the host needs
+/// the answer, so they should only do quick things like popping a router.
+pub fn dispatch_back_pressed() -> bool {
+  // Cloned so that handlers can register or drop handlers while we're going through them
+  let handlers: Vec<BackHandlerFn> =
+    BACK_HANDLERS.with(|handlers| handlers.borrow().iter().map(|(_, h)| h.clone()).collect());
+  for handler in handlers.iter().rev() {
+    match catch_unwind(AssertUnwindSafe(|| handler())) {
+      Ok(true) => return true,
+      Ok(false) => {}
+      Err(_) => error!("Back handler panicked"),
+    }
+  }
+  false
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn newest_back_handler_goes_first() {
+    let calls = Rc::new(RefCell::new(vec![]));
+
+    let calls_clone = calls.clone();
+    let outer = on_back_pressed(move || {
+      calls_clone.borrow_mut().push("outer");
+      true
+    });
+    let calls_clone = calls.clone();
+    let inner = on_back_pressed(move || {
+      calls_clone.borrow_mut().push("inner");
+      false
+    });
+
+    assert!(dispatch_back_pressed());
+    assert_eq!(*calls.borrow(), vec!["inner", "outer"]);
+
+    std::mem::drop(inner);
+    std::mem::drop(outer);
+    assert!(!dispatch_back_pressed());
+  }
+
+  #[test]
+  fn host_drives_lifecycle() {
+    assert_eq!(lifecycle_state(), LifecycleState::Created);
+    set_lifecycle_state(LifecycleState::Resumed);
+    set_lifecycle_state(LifecycleState::Paused);
+    assert_eq!(lifecycle_state(), LifecycleState::Paused);
+
+    let metrics = DisplayMetrics {
+      width_px: 1920.0,
+      height_px: 1080.0,
+      ..DisplayMetrics::default()
+    };
+    set_configuration(metrics);
+    assert_eq!(display_metrics(), metrics);
+  }
+}
@@ -0,0 +1,210 @@
+use crate::bindings::prop_key::PropKey;
+use crate::bindings::view_helpers::UpdateProp;
+use crate::helpers::match_signal;
+use crate::lifecycle::{on_back_pressed, BackHandler};
+use crate::style::PropEnum;
+use crate::ui_tree::{with_parent, PlatformView};
+use discard::DiscardOnDrop;
+use futures_signals::signal::{Mutable, Signal, SignalExt};
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+prop_enum! {
+  /// How the container animates a route in. Sent as the "enter_transition" prop.
+  pub enum EnterTransition {
+    None = 0,
+    SlideFromRight = 1,
+    SlideFromLeft = 2,
+    Fade = 3,
+  }
+}
+
+/// What kind of animation the router uses when the top route changes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transition {
+  Slide,
+  Fade,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum NavKind {
+  Push,
+  Pop,
+  Replace,
+  Reset,
+}
+
+/// State that belongs to one entry on the stack. It lives as long as the entry does, so a
+/// route's views can be torn down while it's covered and pick up where they left off.
+#[derive(Clone, Default)]
+pub struct RouteState {
+  values: Arc<Mutex<HashMap<&'static str, Box<dyn Any + Send>>>>,
+}
+
+impl RouteState {
+  /// The Mutable stored under `key`, created with `init` the first time it's asked for
+  pub fn mutable<T, F>(&self, key: &'static str, init: F) -> Mutable<T>
+  where
+    T: Send + Sync + 'static,
+    F: FnOnce() -> T,
+  {
+    let mut values = self.values.lock().unwrap();
+    values
+      .entry(key)
+      .or_insert_with(|| Box::new(Mutable::new(init())))
+      .downcast_ref::<Mutable<T>>()
+      .expect("Route state key used with a different type")
+      .clone()
+  }
+}
+
+#[derive(Clone)]
+struct Entry<R> {
+  route: R,
+  state: RouteState,
+}
+
+impl<R> Entry<R> {
+  fn new(route: R) -> Self {
+    Entry {
+      route,
+      state: RouteState::default(),
+    }
+  }
+}
+
+// Every change replaces the top entry, so each one recomposes the top route
+struct Stack<R> {
+  entries: Vec<Entry<R>>,
+  last_change: NavKind,
+}
+
+/// A stack of routes, where only the top one is composed. Clones share the same stack, so hand
+/// them to whatever needs to navigate.
+pub struct Router<R> {
+  stack: Mutable<Stack<R>>,
+  transition: Option<Transition>,
+}
+
+impl<R> Clone for Router<R> {
+  fn clone(&self) -> Self {
+    Router {
+      stack: self.stack.clone(),
+      transition: self.transition,
+    }
+  }
+}
+
+impl<R> Router<R>
+where
+  R: Clone + Send + Sync + 'static,
+{
+  pub fn new(root: R) -> Self {
+    Router {
+      stack: Mutable::new(Stack {
+        entries: vec![Entry::new(root)],
+        last_change: NavKind::Reset,
+      }),
+      transition: None,
+    }
+  }
+
+  /// Animate routes in when the top route changes
+  pub fn transition(mut self, transition: Transition) -> Self {
+    self.transition = Some(transition);
+    self
+  }
+
+  pub fn push(&self, route: R) {
+    let entry = Entry::new(route);
+    let mut stack = self.stack.lock_mut();
+    stack.entries.push(entry);
+    stack.last_change = NavKind::Push;
+  }
+
+  /// Removes the top route, unless it's the only one left. Its state goes with it.
+  pub fn pop(&self) -> Option<R> {
+    if self.stack.lock_ref().entries.len() <= 1 {
+      return None;
+    }
+    let mut stack = self.stack.lock_mut();
+    stack.last_change = NavKind::Pop;
+    stack.entries.pop().map(|entry| entry.route)
+  }
+
+  /// Swaps the top route for `route`
+  pub fn replace(&self, route: R) {
+    let entry = Entry::new(route);
+    let mut stack = self.stack.lock_mut();
+    stack.entries.pop();
+    stack.entries.push(entry);
+    stack.last_change = NavKind::Replace;
+  }
+
+  /// Drops the whole stack and starts again from `route`
+  pub fn reset(&self, route: R) {
+    let entry = Entry::new(route);
+    let mut stack = self.stack.lock_mut();
+    stack.entries = vec![entry];
+    stack.last_change = NavKind::Reset;
+  }
+
+  /// Pops the router when the back button is pressed, until only the root route is left.
+  /// Keep the returned handler around for as long as the router should respond.
+  pub fn handle_back(&self) -> DiscardOnDrop<BackHandler> {
+    let router = self.clone();
+    on_back_pressed(move || router.pop().is_some())
+  }
+
+  pub fn depth(&self) -> usize {
+    self.stack.lock_ref().entries.len()
+  }
+
+  /// The routes on the stack, bottom first
+  pub fn routes_signal(&self) -> impl Signal<Item = Vec<R>> + Send {
+    self
+      .stack
+      .signal_ref(|stack| stack.entries.iter().map(|e| e.route.clone()).collect())
+  }
+
+  fn enter_transition(&self, change: NavKind) -> EnterTransition {
+    match (self.transition, change) {
+      (None, _) => EnterTransition::None,
+      (Some(Transition::Fade), _) => EnterTransition::Fade,
+      (Some(Transition::Slide), NavKind::Push) | (Some(Transition::Slide), NavKind::Replace) => {
+        EnterTransition::SlideFromRight
+      }
+      (Some(Transition::Slide), NavKind::Pop) | (Some(Transition::Slide), NavKind::Reset) => {
+        EnterTransition::SlideFromLeft
+      }
+    }
+  }
+
+  /// Composes the top route into `container`, and recomposes it whenever the top route changes.
+  /// The caller adds `container` to the tree. Use the `build` of your backend rather than this.
+  pub(crate) fn compose_routes<F>(&self, mut container: PlatformView, render: F)
+  where
+    F: Fn(&R, &RouteState) + Send + 'static,
+  {
+    let router = self.clone();
+    let top = self.stack.signal_ref(|stack| {
+      let top = stack.entries.last().expect("Router stack is never empty");
+      (top.clone(), stack.last_change)
+    });
+
+    let container_clone = container.clone();
+    with_parent(&mut container, move || {
+      match_signal(top, move |(entry, change): (Entry<R>, NavKind)| {
+        container_clone
+          .clone()
+          .update_prop(
+            PropKey::ENTER_TRANSITION,
+            router.enter_transition(change).code(),
+          )
+          .expect("Couldn't update enter_transition");
+        render(&entry.route, &entry.state);
+      });
+    });
+  }
+}
@@ -0,0 +1,281 @@
+//! State that survives the host tearing down and recreating the app (e.g. when Android
+//! recreates the activity). The host asks for a blob with `save_state` before it goes away, and
+//! hands it back to `restore_state` before the app is composed again.
+
+use futures_signals::signal::Mutable;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::ops::Deref;
+
+// Bump this if the layout below changes, old blobs are then ignored rather than misread
+const FORMAT_VERSION: u8 = 1;
+
+/// A value that can be written into the saved state blob
+pub trait SavedValue: Sized {
+  fn encode(&self, out: &mut Vec<u8>);
+  /// None if the bytes don't hold a value of this type
+  fn decode(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! saved_value_le_bytes {
+  ($($t:ty),*) => {
+    $(
+      impl SavedValue for $t {
+        fn encode(&self, out: &mut Vec<u8>) {
+          out.extend_from_slice(&self.to_le_bytes());
+        }
+
+        fn decode(bytes: &[u8]) -> Option<Self> {
+          Some(<$t>::from_le_bytes(bytes.try_into().ok()?))
+        }
+      }
+    )*
+  };
+}
+
+saved_value_le_bytes!(i32, i64, u32, u64);
+
+// usize is saved as a u64 so blobs don't depend on the pointer width
+impl SavedValue for usize {
+  fn encode(&self, out: &mut Vec<u8>) {
+    (*self as u64).encode(out)
+  }
+
+  fn decode(bytes: &[u8]) -> Option<Self> {
+    u64::decode(bytes).map(|v| v as usize)
+  }
+}
+
+impl SavedValue for f32 {
+  fn encode(&self, out: &mut Vec<u8>) {
+    self.to_bits().encode(out)
+  }
+
+  fn decode(bytes: &[u8]) -> Option<Self> {
+    u32::decode(bytes).map(f32::from_bits)
+  }
+}
+
+impl SavedValue for f64 {
+  fn encode(&self, out: &mut Vec<u8>) {
+    self.to_bits().encode(out)
+  }
+
+  fn decode(bytes: &[u8]) -> Option<Self> {
+    u64::decode(bytes).map(f64::from_bits)
+  }
+}
+
+impl SavedValue for bool {
+  fn encode(&self, out: &mut Vec<u8>) {
+    out.push(*self as u8)
+  }
+
+  fn decode(bytes: &[u8]) -> Option<Self> {
+    match bytes {
+      [0] => Some(false),
+      [1] => Some(true),
+      _ => None,
+    }
+  }
+}
+
+impl SavedValue for String {
+  fn encode(&self, out: &mut Vec<u8>) {
+    out.extend_from_slice(self.as_bytes())
+  }
+
+  fn decode(bytes: &[u8]) -> Option<Self> {
+    String::from_utf8(bytes.to_vec()).ok()
+  }
+}
+
+#[derive(Default)]
+struct SavedState {
+  /// What the host handed back, waiting for its `use_saved_state` call
+  restored: HashMap<String, Vec<u8>>,
+  /// Reads the current value of every saved Mutable, along with the id of its SavedMutable
+  live: HashMap<String, (usize, Box<dyn Fn(&mut Vec<u8>)>)>,
+  next_id: usize,
+}
+
+thread_local! {
+  static SAVED_STATE: RefCell<SavedState> = RefCell::new(SavedState::default());
+}
+
+/// What `use_saved_state` returns. Use it like the Mutable it derefs to, the value stops being
+/// saved once it's dropped.
+pub struct SavedMutable<T> {
+  key: &'static str,
+  id: usize,
+  state: Mutable<T>,
+}
+
+impl<T> Deref for SavedMutable<T> {
+  type Target = Mutable<T>;
+
+  fn deref(&self) -> &Mutable<T> {
+    &self.state
+  }
+}
+
+impl<T> Drop for SavedMutable<T> {
+  fn drop(&mut self) {
+    // Another use_saved_state may have taken the key over since
+    let _ = SAVED_STATE.try_with(|saved| {
+      let mut saved = saved.borrow_mut();
+      if matches!(saved.live.get(self.key), Some((id, _)) if *id == self.id) {
+        saved.live.remove(self.key);
+      }
+    });
+  }
+}
+
+/// A Mutable whose value is kept across the app being recreated. It starts from the restored
+/// value if there is one, `default` otherwise. Keys must be unique across the app.
+pub fn use_saved_state<T>(key: &'static str, default: T) -> SavedMutable<T>
+where
+  T: SavedValue + 'static,
+{
+  SAVED_STATE.with(|saved| {
+    let mut saved = saved.borrow_mut();
+    let initial = saved
+      .restored
+      .remove(key)
+      .and_then(|bytes| T::decode(&bytes))
+      .unwrap_or(default);
+    let state = Mutable::new(initial);
+    let id = saved.next_id;
+    saved.next_id += 1;
+
+    let state_clone = state.clone();
+    saved.live.insert(
+      key.into(),
+      (id, Box::new(move |out| state_clone.lock_ref().encode(out))),
+    );
+    SavedMutable { key, id, state }
+  })
+}
+
+/// Writes every saved value into a blob for the host to keep
+pub fn save_state() -> Vec<u8> {
+  SAVED_STATE.with(|saved| {
+    let saved = saved.borrow();
+    let mut blob = vec![FORMAT_VERSION];
+    let mut value = vec![];
+    for (key, (_, read)) in saved.live.iter() {
+      value.clear();
+      read(&mut value);
+      write_chunk(&mut blob, key.as_bytes());
+      write_chunk(&mut blob, &value);
+    }
+    blob
+  })
+}
+
+/// Loads a blob from `save_state`. Call it before composing, so that `use_saved_state` sees it.
+/// A blob that can't be read is dropped as a whole and everything starts from its default.
+pub fn restore_state(blob: &[u8]) {
+  let restored = match parse_blob(blob) {
+    Some(restored) => restored,
+    None => {
+      warn!("Ignoring saved state that couldn't be read");
+      return;
+    }
+  };
+  SAVED_STATE.with(|saved| saved.borrow_mut().restored = restored);
+}
+
+// Each chunk is a little endian u32 length followed by that many bytes
+fn write_chunk(out: &mut Vec<u8>, bytes: &[u8]) {
+  out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+  out.extend_from_slice(bytes);
+}
+
+fn read_chunk<'a>(bytes: &mut &'a [u8]) -> Option<&'a [u8]> {
+  if bytes.len() < 4 {
+    return None;
+  }
+  let (len, rest) = bytes.split_at(4);
+  let len = u32::from_le_bytes(len.try_into().ok()?) as usize;
+  if rest.len() < len {
+    return None;
+  }
+  let (chunk, rest) = rest.split_at(len);
+  *bytes = rest;
+  Some(chunk)
+}
+
+fn parse_blob(blob: &[u8]) -> Option<HashMap<String, Vec<u8>>> {
+  let (version, mut rest) = blob.split_first()?;
+  if *version != FORMAT_VERSION {
+    return None;
+  }
+  let mut values = HashMap::new();
+  while !rest.is_empty() {
+    let key = String::from_utf8(read_chunk(&mut rest)?.to_vec()).ok()?;
+    let value = read_chunk(&mut rest)?.to_vec();
+    values.insert(key, value);
+  }
+  Some(values)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn round_trips_through_a_blob() {
+    let slide = use_saved_state("slide", 0usize);
+    let name = use_saved_state("name", String::new());
+    let ratio = use_saved_state("ratio", 0.0f32);
+    *slide.lock_mut() = 3;
+    *name.lock_mut() = "Ada".into();
+    *ratio.lock_mut() = 0.25;
+
+    let blob = save_state();
+
+    // As if the app was recreated
+    SAVED_STATE.with(|saved| *saved.borrow_mut() = SavedState::default());
+    restore_state(&blob);
+
+    assert_eq!(use_saved_state("slide", 0usize).get(), 3);
+    assert_eq!(*use_saved_state("name", String::new()).lock_ref(), "Ada");
+    assert_eq!(use_saved_state("ratio", 0.0f32).get(), 0.25);
+    // Not in the blob, so it's the default
+    assert!(use_saved_state("missing", true).get());
+  }
+
+  #[test]
+  fn dropped_state_isnt_saved() {
+    let kept = use_saved_state("kept", 1i32);
+    let dropped = use_saved_state("dropped", 2i32);
+    std::mem::drop(dropped);
+    // Dropping an old state doesn't touch the one that took its key over
+    let old = use_saved_state("replaced", 3i32);
+    let new = use_saved_state("replaced", 4i32);
+    std::mem::drop(old);
+
+    let restored = parse_blob(&save_state()).unwrap();
+    assert!(restored.contains_key("kept"));
+    assert!(!restored.contains_key("dropped"));
+    assert_eq!(restored.get("replaced"), Some(&4i32.to_le_bytes().to_vec()));
+    assert_eq!(kept.get() + new.get(), 5);
+  }
+
+  #[test]
+  fn bad_blobs_are_ignored() {
+    restore_state(&[FORMAT_VERSION, 200, 0, 0, 0, 1]);
+    restore_state(&[FORMAT_VERSION + 1]);
+    restore_state(&[]);
+    assert_eq!(use_saved_state("count", 7i32).get(), 7);
+
+    // A value of the wrong type falls back to the default too
+    let mut blob = vec![FORMAT_VERSION];
+    write_chunk(&mut blob, b"flag");
+    write_chunk(&mut blob, &[7]);
+    restore_state(&blob);
+    assert!(!use_saved_state("flag", false).get());
+  }
+}
@@ -1,8 +1,8 @@
 #![allow(dead_code)]
 use crate::bindings::android::views::*;
 use crate::bindings::view_helpers::*;
-use crate::helpers::match_signal;
-use crate::style::Orientation;
+use crate::helpers::{use_saved_state, SavedMutable};
+use crate::style::{dp, percent, sp, Orientation};
 use futures_signals::signal::{Mutable, SignalExt};
 
 fn build_slides() -> Vec<BasicSlideInfo> {
@@ -33,47 +33,61 @@ fn build_slides() -> Vec<BasicSlideInfo> {
   .collect()
 }
 
-pub fn main() {
-  let slide_number: Mutable<usize> = Mutable::new(0);
+/// What has to outlive the views, the caller keeps it for as long as the app runs
+pub struct App {
+  _slide_number: SavedMutable<usize>,
+}
+
+pub fn main() -> App {
+  // Kept if the activity is recreated, so the talk doesn't start over. Handlers only get
+  // clones of the Mutable, so it's saved for as long as the app holds on to it.
+  let slide_number: SavedMutable<usize> = use_saved_state("slide_number", 0);
   let slide_sig = slide_number.signal();
   let slides = build_slides();
 
-  let slide_number_clone = slide_number.clone();
+  let next_number: Mutable<usize> = (*slide_number).clone();
   let on_next = move || {
-    let mut lock = slide_number_clone.lock_mut();
+    let mut lock = next_number.lock_mut();
     *lock += 1;
   };
+  let prev_number: Mutable<usize> = (*slide_number).clone();
   let on_prev = move || {
-    let mut lock = slide_number.lock_mut();
+    let mut lock = prev_number.lock_mut();
     if *lock > 0 {
       *lock -= 1;
     }
   };
 
-  PhysicsLayout::new()
-    .with(move || {
-      match_signal(slide_sig, move |slide_idx| {
-        basic_slide(&slides[(slide_idx % slides.len())]);
-      });
-      StackLayout::new()
-        .with(|| {
-          Button::new(on_prev).text("Previous");
-          Button::new(on_next).text("Next");
-        })
-        .orientation(Orientation::Horizontal);
-    })
-    .orientation(Orientation::Vertical)
-    .height(1820.0)
-    .width(1080.0);
+  view! {
+    PhysicsLayout(
+      orientation = Orientation::Vertical,
+      height = percent(100.0),
+      width = percent(100.0),
+    ) {
+      match {slide_sig} {
+        slide_idx => {
+          { basic_slide(&slides[slide_idx % slides.len()]); }
+        }
+      }
+      StackLayout(orientation = Orientation::Horizontal) {
+        Button(on_prev, text = "Previous");
+        Button(on_next, text = "Next");
+      }
+    }
+  }
+
+  App {
+    _slide_number: slide_number,
+  }
 }
 
 fn basic_slide(info: &BasicSlideInfo) {
   Text::new(info.title)
-    .text_size(32.0)
-    .pad_left(20.0)
-    .pad_top(20.0);
+    .text_size(sp(32.0))
+    .pad_left(dp(20.0))
+    .pad_top(dp(20.0));
   for reason in info.reasons.iter() {
-    Text::new(*reason).text_size(20.0).pad_top(20.0).pad_left(20.0);
+    Text::new(*reason).text_size(sp(20.0)).pad_top(dp(20.0)).pad_left(dp(20.0));
   }
 }
 
@@ -0,0 +1,126 @@
+use super::Orientation;
+use std::cell::Cell;
+
+thread_local! {
+  static DISPLAY_METRICS: Cell<DisplayMetrics> = Cell::new(DisplayMetrics::default());
+}
+
+/// What we know about the screen, handed to us by the host in `init`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DisplayMetrics {
+  /// Physical pixels per dp
+  pub density: f32,
+  /// Physical pixels per sp. Same as density, scaled by the user's font size setting
+  pub scaled_density: f32,
+  pub width_px: f32,
+  pub height_px: f32,
+}
+
+impl Default for DisplayMetrics {
+  // 1:1 densities so that tests can reason in pixels
+  fn default() -> Self {
+    DisplayMetrics {
+      density: 1.0,
+      scaled_density: 1.0,
+      width_px: 1080.0,
+      height_px: 1920.0,
+    }
+  }
+}
+
+pub fn set_display_metrics(metrics: DisplayMetrics) {
+  DISPLAY_METRICS.with(|m| m.set(metrics));
+}
+
+pub fn display_metrics() -> DisplayMetrics {
+  DISPLAY_METRICS.with(|m| m.get())
+}
+
+/// A size that gets resolved to physical pixels right before it's sent to the view.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dimension {
+  Px(f32),
+  Dp(f32),
+  Sp(f32),
+  /// Percent of the screen along the axis the prop applies to
+  Percent(f32),
+}
+
+impl Dimension {
+  pub fn resolve(self, axis: Orientation) -> f32 {
+    self.resolve_with(&display_metrics(), axis)
+  }
+
+  pub fn resolve_with(self, metrics: &DisplayMetrics, axis: Orientation) -> f32 {
+    match self {
+      Dimension::Px(v) => v,
+      Dimension::Dp(v) => v * metrics.density,
+      Dimension::Sp(v) => v * metrics.scaled_density,
+      Dimension::Percent(v) => match axis {
+        Orientation::Horizontal => v / 100.0 * metrics.width_px,
+        Orientation::Vertical => v / 100.0 * metrics.height_px,
+      },
+    }
+  }
+}
+
+/// Bare numbers are physical pixels, which is what every size prop used to take.
+impl From<f32> for Dimension {
+  fn from(v: f32) -> Self {
+    Dimension::Px(v)
+  }
+}
+
+pub fn px(v: f32) -> Dimension {
+  Dimension::Px(v)
+}
+
+pub fn dp(v: f32) -> Dimension {
+  Dimension::Dp(v)
+}
+
+pub fn sp(v: f32) -> Dimension {
+  Dimension::Sp(v)
+}
+
+pub fn percent(v: f32) -> Dimension {
+  Dimension::Percent(v)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn resolves_against_metrics() {
+    let metrics = DisplayMetrics {
+      density: 2.0,
+      scaled_density: 3.0,
+      width_px: 800.0,
+      height_px: 1600.0,
+    };
+
+    assert_eq!(px(10.0).resolve_with(&metrics, Orientation::Vertical), 10.0);
+    assert_eq!(dp(10.0).resolve_with(&metrics, Orientation::Vertical), 20.0);
+    assert_eq!(sp(10.0).resolve_with(&metrics, Orientation::Vertical), 30.0);
+    assert_eq!(
+      percent(50.0).resolve_with(&metrics, Orientation::Horizontal),
+      400.0
+    );
+    assert_eq!(
+      percent(50.0).resolve_with(&metrics, Orientation::Vertical),
+      800.0
+    );
+  }
+
+  #[test]
+  fn resolves_against_mocked_metrics() {
+    set_display_metrics(DisplayMetrics {
+      density: 2.625,
+      ..DisplayMetrics::default()
+    });
+    assert_eq!(dp(8.0).resolve(Orientation::Horizontal), 21.0);
+    assert_eq!(Dimension::from(8.0).resolve(Orientation::Horizontal), 8.0);
+    set_display_metrics(DisplayMetrics::default());
+  }
+}
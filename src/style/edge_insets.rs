@@ -0,0 +1,67 @@
+use super::Dimension;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Edge {
+  Left,
+  Top,
+  Right,
+  Bottom,
+}
+
+/// Spacing on each side of a view, used for both padding and margins.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EdgeInsets {
+  pub left: Dimension,
+  pub top: Dimension,
+  pub right: Dimension,
+  pub bottom: Dimension,
+}
+
+impl Default for EdgeInsets {
+  fn default() -> Self {
+    EdgeInsets::all(0.0)
+  }
+}
+
+impl EdgeInsets {
+  pub fn all<D: Into<Dimension>>(d: D) -> Self {
+    let d = d.into();
+    EdgeInsets {
+      left: d,
+      top: d,
+      right: d,
+      bottom: d,
+    }
+  }
+
+  pub fn symmetric<H, V>(horizontal: H, vertical: V) -> Self
+  where
+    H: Into<Dimension>,
+    V: Into<Dimension>,
+  {
+    let horizontal = horizontal.into();
+    let vertical = vertical.into();
+    EdgeInsets {
+      left: horizontal,
+      top: vertical,
+      right: horizontal,
+      bottom: vertical,
+    }
+  }
+
+  /// Only the given edge is set, the rest are zero. Chain `and` to set more edges.
+  pub fn only<D: Into<Dimension>>(edge: Edge, d: D) -> Self {
+    EdgeInsets::default().and(edge, d)
+  }
+
+  pub fn and<D: Into<Dimension>>(mut self, edge: Edge, d: D) -> Self {
+    let d = d.into();
+    match edge {
+      Edge::Left => self.left = d,
+      Edge::Top => self.top = d,
+      Edge::Right => self.right = d,
+      Edge::Bottom => self.bottom = d,
+    }
+    self
+  }
+}
@@ -1,17 +1,90 @@
-use std::string::ToString;
+mod dimension;
+mod edge_insets;
 
-pub enum Orientation {
-  Vertical,
-  Horizontal,
+pub use dimension::*;
+pub use edge_insets::*;
+use crate::bindings::callback::EventKind;
+use crate::lifecycle::LifecycleState;
+use crate::navigation::EnterTransition;
+
+/// An enum prop that is sent to the host as an integer code rather than a string.
+/// Declare these with `prop_enum!`.
+pub trait PropEnum: Copy + Sized {
+  const NAME: &'static str;
+  /// (variant name, code) for every variant
+  const VARIANTS: &'static [(&'static str, i32)];
+
+  fn code(self) -> i32;
+  fn from_code(code: i32) -> Option<Self>;
+}
+
+prop_enum! {
+  pub enum Orientation {
+    Vertical = 0,
+    Horizontal = 1,
+  }
 }
 
+prop_enum! {
+  pub enum Fling {
+    Disabled = 0,
+    Enabled = 1,
+  }
+}
+
+prop_enum! {
+  pub enum KeyboardType {
+    Text = 0,
+    Number = 1,
+    Email = 2,
+    Phone = 3,
+  }
+}
 
-// TODO intern
-impl ToString for Orientation {
-    fn to_string(&self) -> String {
-      match self {
-        Orientation::Vertical => "Vertical".into(),
-        Orientation::Horizontal => "Horizontal".into(),
-      }
+prop_enum! {
+  pub enum ScaleMode {
+    /// Scale to fit inside the view, keeping the aspect ratio
+    Fit = 0,
+    /// Stretch to fill the view
+    Fill = 1,
+    /// Scale to cover the view, keeping the aspect ratio and cropping the rest
+    Crop = 2,
+    /// No scaling, centered in the view
+    Center = 3,
+  }
+}
+
+/// Every enum prop and its codes, so the host side can be checked against us.
+pub fn prop_enum_registry() -> Vec<(&'static str, &'static [(&'static str, i32)])> {
+  vec![
+    (Orientation::NAME, Orientation::VARIANTS),
+    (Fling::NAME, Fling::VARIANTS),
+    (KeyboardType::NAME, KeyboardType::VARIANTS),
+    (ScaleMode::NAME, ScaleMode::VARIANTS),
+    (EventKind::NAME, EventKind::VARIANTS),
+    (EnterTransition::NAME, EnterTransition::VARIANTS),
+    (LifecycleState::NAME, LifecycleState::VARIANTS),
+  ]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn prop_enum_codes_round_trip() {
+    for (_, variants) in prop_enum_registry() {
+      let mut codes: Vec<i32> = variants.iter().map(|(_, code)| *code).collect();
+      codes.sort();
+      codes.dedup();
+      assert_eq!(codes.len(), variants.len(), "Prop enum codes must be unique");
     }
+
+    assert_eq!(
+      Orientation::from_code(Orientation::Horizontal.code()),
+      Some(Orientation::Horizontal)
+    );
+    assert_eq!(Fling::from_code(Fling::Enabled.code()), Some(Fling::Enabled));
+    assert_eq!(Orientation::from_code(42), None);
+  }
 }
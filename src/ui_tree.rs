@@ -1,6 +1,8 @@
 #![allow(dead_code)]
-use crate::bindings::callback::Callback;
+use crate::bindings::callback::{Callback, EventHandler, FromEvent};
+use crate::bindings::prop_key::PropKey;
 use crate::bindings::view_helpers::*;
+use crate::error::WiredError;
 use discard::DiscardOnDrop;
 use futures::executor::{LocalPool, LocalSpawner};
 use futures::prelude::*;
@@ -12,7 +14,6 @@ use std::mem;
 use std::sync::Arc;
 
 use futures_signals::signal::Mutable;
-use std::error::Error;
 use std::fmt::{Debug, Formatter};
 
 thread_local! {
@@ -53,22 +54,28 @@ impl PlatformView {
 
 // pub trait Prop: Debug + Any {}
 pub trait PlatformViewInner:
-  UpdateProp<String>
-  + UpdateProp<f32>
-  + UpdateProp<Callback>
-  + UpdateProp<Box<dyn Any + Send>>
+  SetProp<String>
+  + SetProp<f32>
+  + SetProp<f64>
+  + SetProp<i32>
+  + SetProp<i64>
+  + SetProp<bool>
+  + SetProp<Vec<u8>>
+  + SetProp<Vec<String>>
+  + SetProp<EventHandler>
+  + SetProp<Box<dyn Any + Send>>
   + Debug
   + Send
 {
   /// If you append a child that is attached somewhere else, you should move the child.
-  fn append_child(&mut self, c: &PlatformView) -> Result<(), Box<dyn Error>>;
+  fn append_child(&mut self, c: &PlatformView) -> Result<(), WiredError>;
   /// Do not insert a child that is already there! undefined behavior!
-  fn insert_child_at(&mut self, c: &PlatformView, idx: usize) -> Result<(), Box<dyn Error>>;
+  fn insert_child_at(&mut self, c: &PlatformView, idx: usize) -> Result<(), WiredError>;
   /// should not tear down the child! since it may be placed somewhere else later
-  fn remove_child(&mut self, c: &PlatformView) -> Result<(), Box<dyn Error>>;
+  fn remove_child(&mut self, c: &PlatformView) -> Result<(), WiredError>;
   /// Should not tear down the child (same as remove_child)
-  fn remove_child_index(&mut self, idx: usize) -> Result<(), Box<dyn Error>>;
-  fn get_raw_view(&self) -> Result<Arc<Mutex<dyn Any>>, Box<dyn Error>>;
+  fn remove_child_index(&mut self, idx: usize) -> Result<(), WiredError>;
+  fn get_raw_view(&self) -> Result<Arc<Mutex<dyn Any>>, WiredError>;
 }
 
 // impl Prop for String {}
@@ -89,14 +96,14 @@ where
   F: FnOnce(),
 {
   let mut position_context = PositionContext::new();
+  // Children are removed along with their parent, so a rewind only needs their futures
+  let mut child_transactions = vec![];
   COMPOSER.with(|composer| {
     let mut composer = composer.borrow_mut();
-    std::mem::swap(
-      parent,
-      composer.curent_parent.as_mut().expect("No Root View set"),
-    );
+    std::mem::swap(parent, composer.parent().expect("Couldn't compose children"));
 
     std::mem::swap(&mut position_context, &mut composer.position_context);
+    std::mem::swap(&mut child_transactions, &mut composer.transactions);
   });
 
   f();
@@ -106,6 +113,14 @@ where
     std::mem::swap(parent, composer.curent_parent.as_mut().unwrap());
 
     std::mem::swap(&mut position_context, &mut composer.position_context);
+    std::mem::swap(&mut child_transactions, &mut composer.transactions);
+    let futures = child_transactions
+      .into_iter()
+      .flat_map(|t| match t {
+        Transaction::Add(_, futures) | Transaction::Futures(futures) => futures,
+      })
+      .collect();
+    composer.attach_futures(futures);
   });
 }
 
@@ -146,9 +161,17 @@ pub fn with_composer<F>(mut composer: Composer, f: F)
 where
   F: FnOnce(),
 {
-  swap_composer_with_active(&mut composer);
+  with_composer_mut(&mut composer, f);
+}
+
+// Like with_composer, but the caller gets the composer back to see what was composed
+fn with_composer_mut<F>(composer: &mut Composer, f: F)
+where
+  F: FnOnce(),
+{
+  swap_composer_with_active(composer);
   f();
-  swap_composer_with_active(&mut composer);
+  swap_composer_with_active(composer);
 }
 
 fn current_idx() -> usize {
@@ -182,6 +205,10 @@ impl PositionContext {
       .fold(0, |acc, v| acc + v.read_only().get())
   }
 
+  fn added_in_frame(&self) -> usize {
+    self.children_count_stack[self.children_count_stack.len() - 1].get()
+  }
+
   fn inc(&mut self) {
     let mut lock = self.children_count_stack[self.children_count_stack.len() - 1].lock_mut();
     *lock += 1;
@@ -198,6 +225,8 @@ pub type AttachedFutures = Vec<DiscardOnDrop<CancelableFutureHandle>>;
 #[derive(Debug)]
 pub enum Transaction {
   Add(usize, AttachedFutures),
+  /// Futures that aren't tied to a view of their own, dropped with the rest of the transaction
+  Futures(AttachedFutures),
 }
 
 #[derive(Debug)]
@@ -249,7 +278,14 @@ impl Composer {
       self.transactions
     );
 
-    let total_item_count = self.transactions.len();
+    let total_item_count = self
+      .transactions
+      .iter()
+      .filter(|t| match t {
+        Transaction::Add(..) => true,
+        Transaction::Futures(_) => false,
+      })
+      .count();
     if !self.transactions.is_empty() {
       // self.position_context.dec();
     }
@@ -282,7 +318,7 @@ impl Composer {
     &mut self,
     view: &mut PlatformView,
     cancel_future_handles: Option<Vec<DiscardOnDrop<CancelableFutureHandle>>>,
-  ) -> Result<(), Box<dyn Error>> {
+  ) -> Result<(), WiredError> {
     if let Some(mut curent_parent) = self.curent_parent.take() {
       let res = if self.in_transaction {
         debug!(
@@ -299,6 +335,8 @@ impl Composer {
           self.position_context.get_current_idx(),
           cancel_future_handles.unwrap_or_else(|| vec![]),
         ));
+      } else {
+        self.attach_futures(cancel_future_handles.unwrap_or_else(|| vec![]));
       }
       self.position_context.inc();
       self.curent_parent = Some(curent_parent);
@@ -307,15 +345,81 @@ impl Composer {
     Ok(())
   }
 
-  pub fn add_view(&mut self, view: &mut PlatformView) -> Result<(), Box<dyn Error>> {
+  pub fn add_view(&mut self, view: &mut PlatformView) -> Result<(), WiredError> {
     self.add_view_with_futures(view, None)
   }
 
-  fn remove_view_at(&mut self, idx_to_remove: usize) -> Result<(), Box<dyn Error>> {
-    let parent = self
-      .curent_parent
-      .as_mut()
-      .expect("A parent is set to work on");
+  /// Where the next view added to the parent goes
+  pub(crate) fn next_idx(&self) -> usize {
+    self.position_context.get_current_idx()
+  }
+
+  /// Composes `f` into this composer's parent starting at `idx`, and counts the views it added
+  /// towards this composer's frame. Returns how many there were, along with their futures.
+  pub(crate) fn compose_at<F: FnOnce()>(&mut self, idx: usize, f: F) -> (usize, AttachedFutures) {
+    let mut composer = Composer {
+      curent_parent: self.curent_parent.clone(),
+      position_context: PositionContext {
+        children_count_stack: vec![Mutable::new(idx)],
+      },
+      transactions: vec![],
+      in_transaction: true,
+    };
+    with_composer_mut(&mut composer, f);
+
+    let mut views = 0;
+    let mut futures = vec![];
+    for transaction in composer.transactions {
+      match transaction {
+        Transaction::Add(_, added) => {
+          views += 1;
+          futures.extend(added);
+        }
+        Transaction::Futures(added) => futures.extend(added),
+      }
+    }
+    for _ in 0..views {
+      self.position_context.inc();
+    }
+    (views, futures)
+  }
+
+  /// Takes out `count` views starting at `idx`, that were added with `compose_at`
+  pub(crate) fn remove_views_at(&mut self, idx: usize, count: usize) -> Result<(), WiredError> {
+    for _ in 0..count {
+      self.remove_view_at(idx)?;
+    }
+    Ok(())
+  }
+
+  /// Keeps futures running until the current transaction is rewound. Outside of a transaction
+  /// nothing is ever removed, so they run forever.
+  pub fn attach_futures(&mut self, futures: AttachedFutures) {
+    if futures.is_empty() {
+      return;
+    }
+    if self.in_transaction {
+      self.transactions.push(Transaction::Futures(futures));
+    } else {
+      for handle in futures {
+        DiscardOnDrop::leak(handle);
+      }
+    }
+  }
+
+  fn parent(&mut self) -> Result<&mut PlatformView, WiredError> {
+    self.curent_parent.as_mut().ok_or(WiredError::NoRootView)
+  }
+
+  fn remove_view_at(&mut self, idx_to_remove: usize) -> Result<(), WiredError> {
+    // Views are only removed by rewinding ones this frame added
+    if self.position_context.added_in_frame() == 0 {
+      return Err(WiredError::ComposerInvariant(format!(
+        "removing child {} but none were added in this frame",
+        idx_to_remove
+      )));
+    }
+    let parent = self.parent()?;
     debug!("Got parent");
     debug!("Calling remove child index {}", idx_to_remove);
     debug!(
@@ -342,53 +446,67 @@ impl Debug for PlatformView {
   }
 }
 
-impl UpdateProp<f32> for PlatformView {
-  fn update_prop(&mut self, s: &str, v: f32) -> Result<(), Box<dyn Error>> {
-    self.underlying_view.lock().unwrap().update_prop(s, v)
-  }
-}
-
-impl UpdateProp<String> for PlatformView {
-  fn update_prop(&mut self, s: &str, v: String) -> Result<(), Box<dyn Error>> {
-    self.underlying_view.lock().unwrap().update_prop(s, v)
-  }
-}
+macro_rules! forward_update_prop {
+  ($($t:ty),*) => {
+    $(
+      impl SetProp<$t> for PlatformView {
+        fn set_prop(&mut self, k: PropKey, v: $t) -> Result<(), WiredError> {
+          self.underlying_view.lock().unwrap().set_prop(k, v)
+        }
+      }
 
-impl UpdateProp<Callback> for PlatformView {
-  fn update_prop(&mut self, s: &str, v: Callback) -> Result<(), Box<dyn Error>> {
-    self.underlying_view.lock().unwrap().update_prop(s, v)
-  }
+      impl UpdateProp<$t> for PlatformView {
+        fn update_prop(&mut self, k: PropKey, v: $t) -> Result<(), WiredError> {
+          self.set_prop(k, v)
+        }
+      }
+    )*
+  };
 }
 
-impl UpdateProp<Box<dyn Any + Send>> for PlatformView {
-  fn update_prop(&mut self, s: &str, v: Box<dyn Any + Send>) -> Result<(), Box<dyn Error>> {
-    self.underlying_view.lock().unwrap().update_prop(s, v)
+forward_update_prop!(
+  String,
+  f32,
+  f64,
+  i32,
+  i64,
+  bool,
+  Vec<u8>,
+  Vec<String>,
+  EventHandler,
+  Box<dyn Any + Send>
+);
+
+// Views only ever see handlers, the payload type is erased here
+impl<E: FromEvent + 'static> UpdateProp<Callback<E>> for PlatformView {
+  fn update_prop(&mut self, k: PropKey, v: Callback<E>) -> Result<(), WiredError> {
+    self.update_prop(k, v.into_handler())
   }
 }
 
 impl PlatformViewInner for PlatformView {
-  // fn update_prop(&mut self, s: &str, v: Box<dyn Any + Send>) -> Result<(), Box<dyn Error>> {
+  // fn update_prop(&mut self, s: &str, v: Box<dyn Any + Send>) -> Result<(), WiredError> {
   //   self.underlying_view.lock().unwrap().update_prop(s, v)
   // }
   /// If you append a child that is attached somewhere else, you should move the child.
-  fn append_child(&mut self, c: &PlatformView) -> Result<(), Box<dyn Error>> {
+  fn append_child(&mut self, c: &PlatformView) -> Result<(), WiredError> {
     self.underlying_view.lock().unwrap().append_child(c)
   }
 
-  fn insert_child_at(&mut self, c: &PlatformView, idx: usize) -> Result<(), Box<dyn Error>> {
+  fn insert_child_at(&mut self, c: &PlatformView, idx: usize) -> Result<(), WiredError> {
     self.underlying_view.lock().unwrap().insert_child_at(c, idx)
   }
 
   /// should not tear down the child! since it may be placed somewhere else later
-  fn remove_child(&mut self, c: &PlatformView) -> Result<(), Box<dyn Error>> {
+  fn remove_child(&mut self, c: &PlatformView) -> Result<(), WiredError> {
     self.underlying_view.lock().unwrap().remove_child(c)
   }
   /// Should not tear down the child (same as remove_child)
-  fn remove_child_index(&mut self, idx: usize) -> Result<(), Box<dyn Error>> {
+  fn remove_child_index(&mut self, idx: usize) -> Result<(), WiredError> {
     self.underlying_view.lock().unwrap().remove_child_index(idx)
   }
 
-  fn get_raw_view(&self) -> Result<Arc<Mutex<dyn Any>>, Box<dyn Error>> {
+  fn get_raw_view(&self) -> Result<Arc<Mutex<dyn Any>>, WiredError> {
     // TODO fix
     let tmp = self.underlying_view.clone();
     let underlying_view = tmp.lock().unwrap();
@@ -424,15 +542,23 @@ impl PlatformViewInner for PlatformView {
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::bindings::callback::{live_callbacks, Callback, Event, PressEvent};
+  use crate::bindings::image::ImageSource;
   use crate::bindings::test::*;
-  use crate::helpers::{if_signal, use_state, use_state_reducer};
+  use crate::bindings::virtual_list::VirtualList;
+  use crate::helpers::{for_signal_vec, if_signal, use_state, use_state_reducer};
+  use crate::lifecycle::{dispatch_back_pressed, set_configuration};
+  use crate::navigation::{Router, Transition};
+  use crate::style::{dp, percent, px, DisplayMetrics, Edge, EdgeInsets, Fling, Orientation};
+  use futures::future::ready;
   // use futures::future::ready;
   // use futures_timer::{Delay, Interval};
   // use std::panic::{catch_unwind, RefUnwindSafe, UnwindSafe};
   // use std::rc::Rc;
   // use std::time::Duration;
 
-  use futures_signals::signal::{Mutable, SignalExt};
+  use futures_signals::signal::{Mutable, Signal, SignalExt};
+  use futures_signals::signal_vec::MutableVec;
 
   use simple_logger;
 
@@ -603,6 +729,804 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_padding_and_margin() {
+    simple_logger::init().unwrap_or(());
+    set_root_view(DummyPlatformView::new("Root"));
+
+    let insets = Mutable::new(EdgeInsets::all(px(1.0)));
+    let root = StackLayout::new().with(|| {
+      Text::new("Padded")
+        .padding(EdgeInsets::symmetric(px(4.0), px(8.0)))
+        .margin(EdgeInsets::only(Edge::Left, dp(2.0)).and(Edge::Bottom, px(3.0)));
+      Text::new("Watching").padding_signal(insets.signal());
+    });
+
+    run_until_stalled();
+    assert_eq!(
+      format!("{:?}", root.underlying_view),
+      "StackLayout View (props = [])[\n    Text View (props = [(\"text\", \"Padded\"), (\"pad_left\", \"4\"), (\"pad_top\", \"8\"), (\"pad_right\", \"4\"), (\"pad_bottom\", \"8\"), (\"margin_left\", \"2\"), (\"margin_top\", \"0\"), (\"margin_right\", \"0\"), (\"margin_bottom\", \"3\")]),\n    Text View (props = [(\"text\", \"Watching\"), (\"pad_left\", \"1\"), (\"pad_top\", \"1\"), (\"pad_right\", \"1\"), (\"pad_bottom\", \"1\")]),\n]"
+    );
+
+    {
+      let mut lock = insets.lock_mut();
+      *lock = EdgeInsets::only(Edge::Top, px(6.0));
+    }
+    run_until_stalled();
+    assert_eq!(
+      format!("{:?}", root.underlying_view),
+      "StackLayout View (props = [])[\n    Text View (props = [(\"text\", \"Padded\"), (\"pad_left\", \"4\"), (\"pad_top\", \"8\"), (\"pad_right\", \"4\"), (\"pad_bottom\", \"8\"), (\"margin_left\", \"2\"), (\"margin_top\", \"0\"), (\"margin_right\", \"0\"), (\"margin_bottom\", \"3\")]),\n    Text View (props = [(\"text\", \"Watching\"), (\"pad_left\", \"0\"), (\"pad_top\", \"6\"), (\"pad_right\", \"0\"), (\"pad_bottom\", \"0\")]),\n]"
+    );
+  }
+
+  #[test]
+  fn test_dimensions_follow_the_configuration() {
+    set_root_view(DummyPlatformView::new("Root"));
+
+    let size = Mutable::new(percent(50.0));
+    let root = StackLayout::new().with(|| {
+      Text::new("Fixed").width(px(10.0)).height(percent(10.0));
+      Text::new("Watching").width_signal(size.signal());
+    });
+    run_until_stalled();
+    assert_eq!(
+      format!("{:?}", root.underlying_view),
+      "StackLayout View (props = [])[\n    Text View (props = [(\"text\", \"Fixed\"), (\"width\", \"10\"), (\"height\", \"192\")]),\n    Text View (props = [(\"text\", \"Watching\"), (\"width\", \"540\")]),\n]"
+    );
+
+    // As if the screen was rotated
+    set_configuration(DisplayMetrics {
+      width_px: 1920.0,
+      height_px: 1080.0,
+      ..DisplayMetrics::default()
+    });
+    run_until_stalled();
+    assert_eq!(
+      format!("{:?}", root.underlying_view),
+      "StackLayout View (props = [])[\n    Text View (props = [(\"text\", \"Fixed\"), (\"width\", \"10\"), (\"height\", \"108\")]),\n    Text View (props = [(\"text\", \"Watching\"), (\"width\", \"960\")]),\n]"
+    );
+
+    // A new value is resolved against the new screen
+    {
+      let mut lock = size.lock_mut();
+      *lock = percent(25.0);
+    }
+    run_until_stalled();
+    assert_eq!(
+      format!("{:?}", root.underlying_view),
+      "StackLayout View (props = [])[\n    Text View (props = [(\"text\", \"Fixed\"), (\"width\", \"10\"), (\"height\", \"108\")]),\n    Text View (props = [(\"text\", \"Watching\"), (\"width\", \"480\")]),\n]"
+    );
+  }
+
+  // Skips the signal's Nones, so it has no value until the first Some
+  struct Somes<S>(S);
+
+  impl<S, T> Signal for Somes<S>
+  where
+    S: Signal<Item = Option<T>> + Unpin,
+  {
+    type Item = T;
+
+    fn poll_change(
+      mut self: std::pin::Pin<&mut Self>,
+      cx: &mut std::task::Context,
+    ) -> std::task::Poll<Option<T>> {
+      use std::task::Poll;
+      loop {
+        match std::pin::Pin::new(&mut self.0).poll_change(cx) {
+          Poll::Ready(Some(None)) => continue,
+          Poll::Ready(Some(Some(v))) => return Poll::Ready(Some(v)),
+          Poll::Ready(None) => return Poll::Ready(None),
+          Poll::Pending => return Poll::Pending,
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_insets_signal_waits_for_a_value() {
+    set_root_view(DummyPlatformView::new("Root"));
+
+    let insets = Mutable::new(None);
+    let root = StackLayout::new().with(|| {
+      Text::new("Waiting")
+        .padding_signal(Somes(insets.signal()))
+        .margin_signal(Somes(insets.signal()));
+    });
+
+    // No zeros while the signal has nothing to say
+    run_until_stalled();
+    assert_eq!(
+      format!("{:?}", root.underlying_view),
+      "StackLayout View (props = [])[\n    Text View (props = [(\"text\", \"Waiting\")]),\n]"
+    );
+
+    *insets.lock_mut() = Some(EdgeInsets::all(px(2.0)));
+    run_until_stalled();
+    assert_eq!(
+      format!("{:?}", root.underlying_view),
+      "StackLayout View (props = [])[\n    Text View (props = [(\"text\", \"Waiting\"), (\"pad_left\", \"2\"), (\"pad_top\", \"2\"), (\"pad_right\", \"2\"), (\"pad_bottom\", \"2\"), (\"margin_left\", \"2\"), (\"margin_top\", \"2\"), (\"margin_right\", \"2\"), (\"margin_bottom\", \"2\")]),\n]"
+    );
+  }
+
+  #[test]
+  fn test_typed_props() {
+    // Not in the spec, the dummy host takes them anyway
+    let mut view = DummyPlatformView::new("Typed");
+    view
+      .update_prop(PropKey::register("enabled"), true)
+      .unwrap();
+    view.update_prop(PropKey::register("count"), 3i64).unwrap();
+    view
+      .update_prop(PropKey::register("ratio"), 0.5f64)
+      .unwrap();
+    view
+      .update_prop(PropKey::register("image"), vec![1u8, 2, 3])
+      .unwrap();
+    view
+      .update_prop(PropKey::OPTIONS, vec![String::from("a"), String::from("b")])
+      .unwrap();
+
+    assert_eq!(
+      format!("{:?}", view),
+      "Typed View (props = [(\"enabled\", \"true\"), (\"count\", \"3\"), (\"ratio\", \"0.5\"), (\"image\", \"3 bytes\"), (\"options\", \"[\\\"a\\\", \\\"b\\\"]\")])"
+    );
+  }
+
+  #[test]
+  fn test_prop_keys() {
+    use crate::bindings::prop_key::PROP_KEYS;
+    use crate::error::WiredError;
+
+    // Same IDs as the host's PropKeys, both are numbered by the spec
+    for (id, name) in PROP_KEYS.iter().enumerate() {
+      let key = PropKey::intern(name).unwrap();
+      assert_eq!(key.id(), id as i32);
+      assert_eq!(key.name(), *name);
+      assert!(key.is_static());
+    }
+
+    // The constants are the spec's IDs too
+    assert_eq!(PropKey::SET_X.id(), 0);
+    assert_eq!(PropKey::TEXT, PropKey::intern("text").unwrap());
+    assert_eq!(PropKey::ON_SCROLL.name(), "on_scroll");
+
+    let err = PropKey::intern("txet").unwrap_err();
+    match err {
+      WiredError::UnknownProp { ref key } => assert_eq!(key, "txet"),
+      _ => panic!("Expected an unknown prop error, got {}", err),
+    }
+
+    let mut view = DummyPlatformView::new("Typed");
+    let key = PropKey::register("setRating");
+    assert!(!key.is_static());
+    assert_eq!(PropKey::register("setRating"), key);
+    assert_eq!(PropKey::intern("setRating").unwrap(), key);
+    view.update_prop(key, 3.5f32).unwrap();
+    assert_eq!(
+      format!("{:?}", view),
+      "Typed View (props = [(\"setRating\", \"3.5\")])"
+    );
+  }
+
+  #[test]
+  fn test_composer_errors() {
+    use crate::error::WiredError;
+
+    // Nothing was added in this frame, so there's nothing to rewind
+    let mut composer = Composer::new();
+    match composer.remove_view_at(0) {
+      Err(WiredError::ComposerInvariant(_)) => {}
+      other => panic!("Expected a composer invariant error, got {:?}", other),
+    }
+
+    composer.position_context.inc();
+    match composer.remove_view_at(0) {
+      Err(WiredError::NoRootView) => {}
+      other => panic!("Expected a missing root view error, got {:?}", other),
+    }
+
+    let mut composer = Composer::new();
+    composer.curent_parent = Some(DummyPlatformView::new("Root"));
+    composer.add_view(&mut DummyPlatformView::new("Child")).unwrap();
+    composer.remove_view_at(0).unwrap();
+    assert_eq!(composer.position_context.get_current_idx(), 0);
+  }
+
+  #[test]
+  fn test_typed_callbacks() {
+    let mut view = DummyPlatformView::new("Btn");
+    let presses = Mutable::new(vec![]);
+    let presses_clone = presses.clone();
+    view
+      .update_prop(
+        PropKey::ON_PRESS,
+        Callback::new(move |p: PressEvent| presses_clone.lock_mut().push((p.x, p.y))),
+      )
+      .unwrap();
+    assert_eq!(
+      format!("{:?}", view),
+      "Btn View (props = [(\"on_press\", \"<callback>\")])"
+    );
+
+    send_event(&view, "on_press", Event::Press(PressEvent { x: 1.0, y: 2.0 }));
+    // Wrong payload for this callback, it's dropped
+    send_event(&view, "on_press", Event::Toggled(true));
+    assert_eq!(*presses.lock_ref(), vec![]);
+    run_until_stalled();
+    assert_eq!(*presses.lock_ref(), vec![(1.0, 2.0)]);
+  }
+
+  #[test]
+  fn test_callback_dispatch_is_deferred_and_panic_safe() {
+    let mut view = DummyPlatformView::new("Btn");
+    let presses = Mutable::new(0);
+    let presses_clone = presses.clone();
+    view
+      .update_prop(
+        PropKey::ON_PRESS,
+        Callback::new(move |()| {
+          // Would panic with a BorrowMutError if this ran inside the borrow below
+          COMPOSER.with(|c| mem::drop(c.borrow_mut()));
+          *presses_clone.lock_mut() += 1;
+        }),
+      )
+      .unwrap();
+    view
+      .update_prop(PropKey::ON_LONG_PRESS, Callback::new(|()| panic!("oops")))
+      .unwrap();
+
+    COMPOSER.with(|c| {
+      let _composer = c.borrow_mut();
+      send_event(&view, "on_press", Event::Press(PressEvent { x: 0.0, y: 0.0 }));
+    });
+    send_event(&view, "on_long_press", Event::LongPress);
+    send_event(&view, "on_press", Event::Press(PressEvent { x: 0.0, y: 0.0 }));
+    run_until_stalled();
+
+    // The panicking callback didn't stop the others
+    assert_eq!(*presses.lock_ref(), 2);
+  }
+
+  #[test]
+  fn test_callbacks_are_freed() {
+    use crate::bindings::command_buffer::{CommandBuffer, RecordingView, Replayer, SharedBuffer};
+
+    let live_before = live_callbacks();
+    let buffer: SharedBuffer = Arc::new(Mutex::new(CommandBuffer::new()));
+    let mut host = Replayer::new(DummyPlatformView::new("Root"), |kind: &str| {
+      DummyPlatformView::new(Box::leak(kind.to_string().into_boxed_str()))
+    });
+    let mut root = PlatformView::new(RecordingView::root(&buffer, "Root"));
+    let mut button = PlatformView::new(RecordingView::create(&buffer, "BtnView"));
+    button
+      .update_prop(PropKey::ON_PRESS, Callback::new(|()| {}))
+      .unwrap();
+    button
+      .update_prop(PropKey::ON_LONG_PRESS, Callback::new(|()| {}))
+      .unwrap();
+    root.append_child(&button).unwrap();
+    host.replay(buffer.lock().unwrap().take()).unwrap();
+    assert_eq!(live_callbacks(), live_before + 2);
+
+    // The host lets go of a callback when the prop is set again
+    button
+      .update_prop(PropKey::ON_PRESS, Callback::new(|()| {}))
+      .unwrap();
+    host.replay(buffer.lock().unwrap().take()).unwrap();
+    assert_eq!(live_callbacks(), live_before + 2);
+
+    // And of all of them when the view is released
+    root.remove_child(&button).unwrap();
+    mem::drop(button);
+    host.replay(buffer.lock().unwrap().take()).unwrap();
+    assert_eq!(live_callbacks(), live_before);
+  }
+
+  #[test]
+  fn test_command_buffer_replay() {
+    use crate::bindings::command_buffer::{CommandBuffer, RecordingView, Replayer, SharedBuffer};
+
+    let live_before = live_callbacks();
+    let buffer: SharedBuffer = Arc::new(Mutex::new(CommandBuffer::new()));
+    let host_root = DummyPlatformView::new("StackLayoutView");
+    let mut host = Replayer::new(host_root.clone(), |kind: &str| {
+      DummyPlatformView::new(Box::leak(kind.to_string().into_boxed_str()))
+    });
+
+    let mut root = PlatformView::new(RecordingView::root(&buffer, "StackLayoutView"));
+    let mut title = PlatformView::new(RecordingView::create(&buffer, "TextView"));
+    let button_view = RecordingView::create(&buffer, "BtnView");
+    let button_id = button_view.id();
+    let mut button = PlatformView::new(button_view);
+
+    let presses = Mutable::new(0);
+    let presses_clone = presses.clone();
+    title
+      .update_prop(PropKey::TEXT, String::from("Title"))
+      .unwrap();
+    title.update_prop(PropKey::CHECKED, true).unwrap();
+    button
+      .update_prop(
+        PropKey::ON_PRESS,
+        Callback::new(move |()| *presses_clone.lock_mut() += 1),
+      )
+      .unwrap();
+    root.append_child(&title).unwrap();
+    root.append_child(&button).unwrap();
+    // Recorded as a single move
+    root.remove_child(&button).unwrap();
+    root.insert_child_at(&button, 0).unwrap();
+
+    // The host doesn't see anything until it gets the frame
+    assert_eq!(
+      format!("{:?}", host_root),
+      "StackLayoutView View (props = [])"
+    );
+    host.replay(buffer.lock().unwrap().take()).unwrap();
+    assert_eq!(
+      format!("{:?}", host_root),
+      "StackLayoutView View (props = [])[\n    BtnView View (props = [(\"on_press\", \"<callback>\")]),\n    TextView View (props = [(\"text\", \"Title\"), (\"checked\", \"true\")]),\n]"
+    );
+
+    let press = Event::Press(PressEvent { x: 0.0, y: 0.0 });
+    send_event(host.view(button_id).unwrap(), "on_press", press);
+    run_until_stalled();
+    assert_eq!(*presses.lock_ref(), 1);
+
+    // Releasing the view lets the host drop it, and the callback with it
+    root.remove_child(&button).unwrap();
+    mem::drop(button);
+    host.replay(buffer.lock().unwrap().take()).unwrap();
+    assert!(host.view(button_id).is_none());
+    assert_eq!(live_callbacks(), live_before);
+    assert_eq!(
+      format!("{:?}", host_root),
+      "StackLayoutView View (props = [])[\n    TextView View (props = [(\"text\", \"Title\"), (\"checked\", \"true\")]),\n]"
+    );
+  }
+
+  #[test]
+  fn test_replay_skips_failed_commands() {
+    use crate::bindings::command_buffer::{CommandBuffer, PropValue, Replayer, ROOT_VIEW};
+
+    let host_root = DummyPlatformView::new("Root");
+    let mut host = Replayer::new(host_root.clone(), |kind: &str| {
+      DummyPlatformView::new(Box::leak(kind.to_string().into_boxed_str()))
+    });
+    let mut frame = CommandBuffer::new();
+    let title = frame.create_view("TextView");
+    frame.append_child(ROOT_VIEW, 999);
+    frame.append_child(ROOT_VIEW, title);
+    frame.set_prop(title, PropKey::TEXT, PropValue::String("Title".into()));
+
+    // The frame reports the failure, but what came after it still happened
+    match host.replay(frame) {
+      Err(WiredError::BadCommandBuffer { reason, .. }) => assert_eq!(reason, "no view 999"),
+      other => panic!("Expected a bad reference, got {:?}", other),
+    }
+    assert!(host.view(title).is_some());
+    assert_eq!(
+      format!("{:?}", host_root),
+      "Root View (props = [])[\n    TextView View (props = [(\"text\", \"Title\")]),\n]"
+    );
+  }
+
+  #[test]
+  fn test_text_input_binding() {
+    simple_logger::init().unwrap_or(());
+    set_root_view(DummyPlatformView::new("Root"));
+
+    let name = Mutable::new(String::from("Ada"));
+    let input = TextInput::new().bind(name.clone());
+    let input_handle = input.handle();
+
+    let root = StackLayout::new().with(move || {
+      mem::drop(input);
+    });
+
+    run_until_stalled();
+    assert_eq!(
+      format!("{:?}", root.underlying_view),
+      "StackLayout View (props = [])[\n    TextInput View (props = [(\"value\", \"Ada\")]),\n]"
+    );
+    assert_eq!(input_handle.value_pushes(), 1);
+
+    // Typing updates the bound state, but isn't echoed back into the view
+    input_handle.type_text("Grace");
+    run_until_stalled();
+    assert_eq!(*name.lock_ref(), "Grace");
+    assert_eq!(input_handle.value_pushes(), 1);
+
+    // Changes from Rust still make it to the view
+    {
+      let mut lock = name.lock_mut();
+      *lock = String::from("Hopper");
+    }
+    run_until_stalled();
+    assert_eq!(
+      format!("{:?}", root.underlying_view),
+      "StackLayout View (props = [])[\n    TextInput View (props = [(\"value\", \"Hopper\")]),\n]"
+    );
+    assert_eq!(input_handle.value_pushes(), 2);
+  }
+
+  #[test]
+  fn test_dropped_widgets_keep_their_callbacks() {
+    use crate::bindings::android::views;
+
+    let live_before = live_callbacks();
+    let mut host = FrameHost::new();
+    let presses = Mutable::new(0);
+    let presses_clone = presses.clone();
+    let press = Event::Press(PressEvent { x: 0.0, y: 0.0 });
+    // Nothing keeps the struct, only the tree has the view
+    views::Button::new(move || *presses_clone.lock_mut() += 1);
+
+    run_until_stalled();
+    host.flush();
+    assert_eq!(live_callbacks(), live_before + 1);
+    send_event(&host.view("BtnView"), "on_press", press.clone());
+    run_until_stalled();
+    assert_eq!(*presses.lock_ref(), 1);
+
+    // Leaving the tree is what releases it
+    COMPOSER.with(|c| {
+      let mut composer = c.borrow_mut();
+      let root = composer.curent_parent.as_mut().unwrap();
+      root.remove_child_index(0).unwrap();
+    });
+    host.flush();
+    assert_eq!(live_callbacks(), live_before);
+    send_event(&host.view("BtnView"), "on_press", press.clone());
+    run_until_stalled();
+    assert_eq!(*presses.lock_ref(), 1);
+  }
+
+  #[test]
+  fn test_toggle_binding() {
+    use crate::bindings::android::views::{Checkbox, Switch};
+
+    let mut host = FrameHost::new();
+    let checked = Mutable::new(false);
+    let on = Mutable::new(true);
+    Checkbox::new().bind(checked.clone());
+    Switch::new().checked_signal(on.signal());
+
+    run_until_stalled();
+    host.flush();
+    assert_eq!(
+      format!("{:?}", host.root),
+      "Root View (props = [])[\n    CheckboxView View (props = [(\"on_change\", \"<callback>\"), (\"checked\", \"false\")]),\n    SwitchView View (props = [(\"checked\", \"true\")]),\n]"
+    );
+
+    // A tap updates the bound state, but isn't echoed back into the view
+    send_event(&host.view("CheckboxView"), "on_change", Event::Toggled(true));
+    run_until_stalled();
+    assert!(*checked.lock_ref());
+    assert!(host.is_idle());
+
+    // Changes from Rust still make it to the view
+    *checked.lock_mut() = false;
+    *on.lock_mut() = false;
+    run_until_stalled();
+    assert!(!host.is_idle());
+    host.flush();
+    assert_eq!(
+      format!("{:?}", host.root),
+      "Root View (props = [])[\n    CheckboxView View (props = [(\"on_change\", \"<callback>\"), (\"checked\", \"false\")]),\n    SwitchView View (props = [(\"checked\", \"false\")]),\n]"
+    );
+  }
+
+  #[test]
+  fn test_radio_group_binding() {
+    use crate::bindings::android::views::RadioGroup;
+
+    let mut host = FrameHost::new();
+    let size = Mutable::new("M");
+    RadioGroup::new(vec![("S", "Small"), ("M", "Medium"), ("L", "Large")]).bind(size.clone());
+
+    // The host only sees labels and the index of the selected option
+    run_until_stalled();
+    host.flush();
+    assert_eq!(
+      format!("{:?}", host.root),
+      "Root View (props = [])[\n    RadioGroupView View (props = [(\"options\", \"[\\\"Small\\\", \\\"Medium\\\", \\\"Large\\\"]\"), (\"on_change\", \"<callback>\"), (\"selected\", \"1\")]),\n]"
+    );
+
+    // Picking an option writes it back without echoing the index
+    send_event(&host.view("RadioGroupView"), "on_change", Event::Selected(2));
+    run_until_stalled();
+    assert_eq!(*size.lock_ref(), "L");
+    assert!(host.is_idle());
+
+    // Indexes that aren't an option are ignored
+    send_event(&host.view("RadioGroupView"), "on_change", Event::Selected(7));
+    run_until_stalled();
+    assert_eq!(*size.lock_ref(), "L");
+
+    // Values that aren't an option clear the selection
+    *size.lock_mut() = "XL";
+    run_until_stalled();
+    host.flush();
+    assert_eq!(
+      format!("{:?}", host.root),
+      "Root View (props = [])[\n    RadioGroupView View (props = [(\"options\", \"[\\\"Small\\\", \\\"Medium\\\", \\\"Large\\\"]\"), (\"on_change\", \"<callback>\"), (\"selected\", \"-1\")]),\n]"
+    );
+  }
+
+  #[test]
+  fn test_image_sources() {
+    simple_logger::init().unwrap_or(());
+    set_root_view(DummyPlatformView::new("Root"));
+
+    let root = StackLayout::new().with(|| {
+      Image::new(ImageSource::Bytes(vec![0x89, 0x50, 0x4e, 0x47]));
+      Image::new(ImageSource::File("/sdcard/cat.png".into()));
+      Image::new(ImageSource::Resource("ic_launcher".into()));
+    });
+
+    assert_eq!(
+      format!("{:?}", root.underlying_view),
+      "StackLayout View (props = [])[\n    Image View (props = [(\"source_bytes\", \"4 bytes\")]),\n    Image View (props = [(\"source_file\", \"/sdcard/cat.png\")]),\n    Image View (props = [(\"source_resource\", \"ic_launcher\")]),\n]"
+    );
+  }
+
+  #[test]
+  fn test_image_callbacks() {
+    use crate::bindings::android::views;
+
+    let mut host = FrameHost::new();
+    let loads = Mutable::new(0);
+    let errors = Mutable::new(vec![]);
+    let source = Mutable::new(ImageSource::Resource("ic_launcher".into()));
+    let loads_clone = loads.clone();
+    let errors_clone = errors.clone();
+    views::Image::new(ImageSource::File("/sdcard/cat.png".into()))
+      .on_load(move || *loads_clone.lock_mut() += 1);
+    views::Image::new(ImageSource::Bytes(vec![]))
+      .on_error(move |e| errors_clone.lock_mut().push(e));
+    views::Image::default().source_signal(source.signal_cloned());
+
+    // The host loads the initial sources right away, so the callbacks have to be there already
+    run_until_stalled();
+    host.flush();
+    run_until_stalled();
+    assert_eq!(*loads.lock_ref(), 1);
+    assert_eq!(*errors.lock_ref(), vec![String::from("Empty source")]);
+
+    // Switching kinds replaces the old source
+    *source.lock_mut() = ImageSource::Bytes(vec![0x89, 0x50, 0x4e]);
+    run_until_stalled();
+    host.flush();
+    assert_eq!(
+      format!("{:?}", host.root),
+      "Root View (props = [])[\n    ImageView View (props = [(\"on_load\", \"<callback>\"), (\"source_file\", \"/sdcard/cat.png\")]),\n    ImageView View (props = [(\"on_error\", \"<callback>\"), (\"source_bytes\", \"0 bytes\")]),\n    ImageView View (props = [(\"source_bytes\", \"3 bytes\")]),\n]"
+    );
+  }
+
+  #[test]
+  fn test_scroll_view() {
+    use crate::bindings::android::views::{ScrollView, Text};
+
+    let mut host = FrameHost::new();
+    let scrolled = Mutable::new(vec![]);
+    let scrolled_clone = scrolled.clone();
+    let scroll_view =
+      ScrollView::new().on_scroll(move |offset| scrolled_clone.lock_mut().push(offset));
+    let scroll_handle = scroll_view.handle();
+    let header = scroll_view
+      .scroll_offset_signal()
+      .map(|offset| format!("Scrolled {}", offset));
+    Text::default().text_signal(header);
+    scroll_view.with(|| {
+      Text::new("Row");
+    });
+
+    run_until_stalled();
+    host.flush();
+
+    // The host reports scrolls through the callback ScrollView registered
+    send_event(&host.view("ScrollView"), "on_scroll", Event::ValueChanged(120.0));
+    run_until_stalled();
+    host.flush();
+    assert_eq!(*scrolled.lock_ref(), vec![120.0]);
+    assert_eq!(
+      format!("{:?}", host.root),
+      "Root View (props = [])[\n    TextView View (props = [(\"text\", \"Scrolled 120\")]),\n    ScrollView View (props = [(\"on_scroll\", \"<callback>\")])[\n        TextView View (props = [(\"text\", \"Row\")]),\n    ],\n]"
+    );
+
+    scroll_handle.scroll_to(0.0, true);
+    host.flush();
+    assert_eq!(
+      format!("{:?}", host.root),
+      "Root View (props = [])[\n    TextView View (props = [(\"text\", \"Scrolled 120\")]),\n    ScrollView View (props = [(\"on_scroll\", \"<callback>\"), (\"smooth_scroll_to\", \"0\")])[\n        TextView View (props = [(\"text\", \"Row\")]),\n    ],\n]"
+    );
+  }
+
+  #[test]
+  fn test_virtual_list() {
+    simple_logger::init().unwrap_or(());
+    set_root_view(DummyPlatformView::new("Root"));
+
+    let count = Mutable::new(10_000);
+    let visible_range = Mutable::new(0..0);
+    let visible_range_clone = visible_range.clone();
+
+    let mut handles = None;
+    let root = StackLayout::new().with(|| {
+      let (list_handle, scroll_handle) = VirtualList::with_count(count.signal())
+        .row_height(px(10.0))
+        .viewport_height(px(100.0))
+        .buffer(2)
+        .build_dummy(|row| {
+          Text::default().text_signal(
+            row
+              .item_signal()
+              .map(|i| i.map(|i| format!("Row {}", i)).unwrap_or_default()),
+          );
+        });
+      handles = Some((list_handle, scroll_handle));
+    });
+    let (list_handle, scroll_handle) = handles.unwrap();
+    DiscardOnDrop::leak(spawn_future(
+      list_handle.visible_range_signal().for_each(move |range| {
+        let mut lock = visible_range_clone.lock_mut();
+        *lock = range;
+        ready(())
+      }),
+    ));
+
+    run_until_stalled();
+    let tree = format!("{:?}", root.underlying_view);
+    assert_eq!(*visible_range.lock_ref(), 0..12);
+    // 100 / 10 + 1 rows on screen, 2 buffer rows on each side
+    assert_eq!(tree.matches("Text View").count(), 15);
+    assert!(tree.contains("\"Row 0\""));
+    assert!(tree.contains("\"Row 11\""));
+    assert!(!tree.contains("\"Row 12\""));
+
+    scroll_handle.user_scroll(500.0);
+    run_until_stalled();
+    let tree = format!("{:?}", root.underlying_view);
+    assert_eq!(*visible_range.lock_ref(), 48..62);
+    assert_eq!(tree.matches("Text View").count(), 15);
+    assert!(!tree.contains("\"Row 47\""));
+    assert!(tree.contains("\"Row 48\""));
+    assert!(tree.contains("\"Row 61\""));
+    // The top spacer stands in for the 48 rows above
+    assert!(tree.contains("(\"height\", \"480\")"));
+
+    list_handle.scroll_to_index(9_999, false);
+    run_until_stalled();
+    assert_eq!(*visible_range.lock_ref(), 9997..10_000);
+  }
+
+  #[test]
+  fn test_virtual_list_removed() {
+    simple_logger::init().unwrap_or(());
+    set_root_view(DummyPlatformView::new("Root"));
+
+    let count = Mutable::new(10_000);
+    let showing = Mutable::new(true);
+    let visible_range = Mutable::new(0..0);
+    let handles = Arc::new(Mutex::new(None));
+
+    let count_clone = count.clone();
+    let handles_clone = handles.clone();
+    let root = StackLayout::new().with(|| {
+      if_signal(showing.signal(), move |showing| {
+        if showing {
+          let built = VirtualList::with_count(count_clone.signal())
+            .row_height(px(10.0))
+            .viewport_height(px(100.0))
+            .buffer(2)
+            .build_dummy(|row| {
+              Text::default().text_signal(row.index_signal().map(|i| format!("{:?}", i)));
+            });
+          *handles_clone.lock().unwrap() = Some(built);
+        }
+      });
+    });
+    run_until_stalled();
+    let (list_handle, scroll_handle) = handles.lock().unwrap().take().unwrap();
+    let visible_range_clone = visible_range.clone();
+    DiscardOnDrop::leak(spawn_future(
+      list_handle.visible_range_signal().for_each(move |range| {
+        *visible_range_clone.lock_mut() = range;
+        ready(())
+      }),
+    ));
+    run_until_stalled();
+    assert_eq!(*visible_range.lock_ref(), 0..12);
+
+    *showing.lock_mut() = false;
+    run_until_stalled();
+    assert_eq!(
+      format!("{:?}", root.underlying_view),
+      "StackLayout View (props = [])"
+    );
+
+    // Neither scrolling nor new items reach the removed list
+    scroll_handle.user_scroll(500.0);
+    *count.lock_mut() = 5;
+    run_until_stalled();
+    assert_eq!(*visible_range.lock_ref(), 0..12);
+  }
+
+  #[test]
+  fn test_router() {
+    simple_logger::init().unwrap_or(());
+    set_root_view(DummyPlatformView::new("Root"));
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Route {
+      Home,
+      Detail(u32),
+    }
+
+    let router = Router::new(Route::Home).transition(Transition::Slide);
+    let routes = Mutable::new(vec![]);
+    let routes_clone = routes.clone();
+    DiscardOnDrop::leak(spawn_future(router.routes_signal().for_each(
+      move |r| {
+        *routes_clone.lock_mut() = r;
+        ready(())
+      },
+    )));
+
+    let root = StackLayout::new().with(|| {
+      router.build_dummy(|route, state| match route {
+        Route::Home => {
+          // Counts how many times Home was composed, kept while Home is covered
+          let visits = state.mutable("visits", || 0);
+          *visits.lock_mut() += 1;
+          Text::new(format!("Home {}", visits.get()));
+        }
+        Route::Detail(n) => {
+          Text::new(format!("Detail {}", n));
+        }
+      });
+    });
+    let tree = || format!("{:?}", root.underlying_view);
+
+    run_until_stalled();
+    assert!(tree().contains("\"Home 1\""));
+    assert!(tree().contains("(\"enter_transition\", \"0\")"));
+
+    router.push(Route::Detail(7));
+    run_until_stalled();
+    assert!(tree().contains("\"Detail 7\""));
+    assert!(!tree().contains("Home"));
+    assert!(tree().contains("(\"enter_transition\", \"1\")"));
+    assert_eq!(*routes.lock_ref(), vec![Route::Home, Route::Detail(7)]);
+
+    router.replace(Route::Detail(8));
+    run_until_stalled();
+    assert!(tree().contains("\"Detail 8\""));
+    assert_eq!(router.depth(), 2);
+
+    assert_eq!(router.pop(), Some(Route::Detail(8)));
+    run_until_stalled();
+    assert!(tree().contains("\"Home 2\""));
+    assert!(tree().contains("(\"enter_transition\", \"2\")"));
+    // The root route stays
+    assert_eq!(router.pop(), None);
+
+    // Reset starts over with fresh state
+    router.reset(Route::Home);
+    run_until_stalled();
+    assert!(tree().contains("\"Home 1\""));
+    assert_eq!(*routes.lock_ref(), vec![Route::Home]);
+
+    // The back button pops until only the root is left
+    let _back = router.handle_back();
+    router.push(Route::Detail(1));
+    assert!(dispatch_back_pressed());
+    assert_eq!(router.depth(), 1);
+    assert!(!dispatch_back_pressed());
+  }
+
   #[test]
   fn test_transaction_in_a_nest() {
     simple_logger::init().unwrap_or(());
@@ -643,4 +1567,121 @@ mod tests {
     warn!("{:?}", root.underlying_view);
     assert_eq!(format!("{:?}", root.underlying_view), "StackLayout View (props = [])[\n    Text View (props = [(\"text\", \"First\")]),\n    StackLayout View (props = [])[\n        Text View (props = [(\"text\", \"First + 1\")]),\n        StackLayout View (props = []),\n    ],\n    Text View (props = [(\"text\", \"last - 1\")]),\n    Text View (props = [(\"text\", \"last\")]),\n]");
   }
+
+  #[test]
+  fn test_view_macro() {
+    simple_logger::init().unwrap_or(());
+    set_root_view(DummyPlatformView::new("Root"));
+
+    let name = Mutable::new(String::from("Ada"));
+    let showing = Mutable::new(true);
+    let count = Mutable::new(0);
+    let items = MutableVec::new_with_values(vec![String::from("a"), String::from("b")]);
+
+    let root = StackLayout::new().with(|| {
+      view! {
+        Text("Hello");
+        Text("", text={name.signal_cloned()});
+        if {showing.signal()} {
+          Text("Shown")
+        } else {
+          Text("Hidden")
+        }
+        match {count.signal()} {
+          0 => Text("None"),
+          n => { Text(format!("{} items", n)) }
+        }
+        StackLayout {
+          for item in {items.signal_vec_cloned()} {
+            Text(item)
+          }
+        }
+      }
+    });
+
+    run_until_stalled();
+    assert_eq!(
+      format!("{:?}", root.underlying_view),
+      "StackLayout View (props = [])[\n    Text View (props = [(\"text\", \"Hello\")]),\n    Text View (props = [(\"text\", \"Ada\")]),\n    Text View (props = [(\"text\", \"Shown\")]),\n    Text View (props = [(\"text\", \"None\")]),\n    StackLayout View (props = [])[\n        Text View (props = [(\"text\", \"a\")]),\n        Text View (props = [(\"text\", \"b\")]),\n    ],\n]"
+    );
+
+    *name.lock_mut() = String::from("Grace");
+    *showing.lock_mut() = false;
+    *count.lock_mut() = 2;
+    items.lock_mut().push_cloned(String::from("c"));
+    run_until_stalled();
+    assert_eq!(
+      format!("{:?}", root.underlying_view),
+      "StackLayout View (props = [])[\n    Text View (props = [(\"text\", \"Hello\")]),\n    Text View (props = [(\"text\", \"Grace\")]),\n    Text View (props = [(\"text\", \"Hidden\")]),\n    Text View (props = [(\"text\", \"2 items\")]),\n    StackLayout View (props = [])[\n        Text View (props = [(\"text\", \"a\")]),\n        Text View (props = [(\"text\", \"b\")]),\n        Text View (props = [(\"text\", \"c\")]),\n    ],\n]"
+    );
+  }
+
+  #[test]
+  fn test_for_signal_vec_only_composes_changes() {
+    set_root_view(DummyPlatformView::new("Root"));
+    let items = MutableVec::new_with_values(vec![1, 2, 3]);
+    let built = Arc::new(Mutex::new(vec![]));
+    let built_clone = built.clone();
+
+    let root = StackLayout::new().with(|| {
+      Text::new("Before");
+      for_signal_vec(items.signal_vec(), move |n| {
+        built_clone.lock().unwrap().push(n);
+        Text::new(format!("{}", n));
+      });
+      Text::new("After");
+    });
+    run_until_stalled();
+    assert_eq!(*built.lock().unwrap(), vec![1, 2, 3]);
+
+    {
+      let mut items = items.lock_mut();
+      items.insert(1, 4);
+      items.remove(0);
+      items.move_from_to(2, 0);
+      items.push(5);
+    }
+    run_until_stalled();
+    // A move composes the item again, the others are left alone
+    assert_eq!(*built.lock().unwrap(), vec![1, 2, 3, 4, 3, 5]);
+    assert_eq!(
+      format!("{:?}", root.underlying_view),
+      "StackLayout View (props = [])[\n    Text View (props = [(\"text\", \"Before\")]),\n    Text View (props = [(\"text\", \"3\")]),\n    Text View (props = [(\"text\", \"4\")]),\n    Text View (props = [(\"text\", \"2\")]),\n    Text View (props = [(\"text\", \"5\")]),\n    Text View (props = [(\"text\", \"After\")]),\n]"
+    );
+
+    items.lock_mut().clear();
+    run_until_stalled();
+    assert_eq!(
+      format!("{:?}", root.underlying_view),
+      "StackLayout View (props = [])[\n    Text View (props = [(\"text\", \"Before\")]),\n    Text View (props = [(\"text\", \"After\")]),\n]"
+    );
+  }
+
+  #[test]
+  fn test_helper_props_match_codegen() {
+    // wired_codegen keeps its own list of what each helper sends to generate the host side
+    set_root_view(DummyPlatformView::new("Root"));
+    let noop = || Callback::new(|()| {});
+    let sent = vec![
+      ("SetXY", Text::default().set_x(px(1.0)).set_y(px(1.0))),
+      ("SetText", SetText::text(Text::default(), "text")),
+      ("SetTextSize", Text::default().text_size(px(1.0))),
+      ("Padding", Text::default().padding(EdgeInsets::all(px(1.0)))),
+      ("Margin", Text::default().margin(EdgeInsets::all(px(1.0)))),
+      ("SetHeightWidth", Text::default().height(px(1.0)).width(px(1.0))),
+      ("SetOrientation", Text::default().orientation(Orientation::Vertical)),
+      ("SetFling", Text::default().fling(Fling::Enabled)),
+      ("OnPress", Text::default().on_press(noop()).on_long_press(noop())),
+    ];
+
+    assert_eq!(sent.len(), wired_codegen::helpers::HELPERS.len());
+    for (name, text) in sent.iter() {
+      let helper = wired_codegen::helpers::helper(name).expect("Helper is known to codegen");
+      let mut expected: Vec<&str> = helper.props.iter().map(|p| p.key).collect();
+      let mut keys = text.prop_keys();
+      expected.sort();
+      keys.sort();
+      assert_eq!(keys, expected, "{} sends different props than codegen thinks", name);
+    }
+  }
 }
@@ -0,0 +1,323 @@
+use std::fmt::Write;
+
+/// A field after a command's opcode and view ID
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Field {
+  /// A prop key ID
+  Key,
+  /// A view ID, an index or a length
+  U32,
+  I32,
+  I64,
+  F32,
+  F64,
+  Bool,
+  /// A u32 byte length then UTF-8
+  String,
+  /// A u32 length then the bytes
+  Bytes,
+  /// A u32 count then the strings
+  Strings,
+  /// A u32 index into the frame's callbacks
+  Callback,
+}
+
+impl Field {
+  /// How many bytes it takes up, None if that depends on the value
+  pub fn size(self) -> Option<usize> {
+    match self {
+      Field::Key | Field::U32 | Field::I32 | Field::F32 | Field::Callback => Some(4),
+      Field::I64 | Field::F64 => Some(8),
+      Field::Bool => Some(1),
+      Field::String | Field::Bytes | Field::Strings => None,
+    }
+  }
+
+  fn kotlin_read(self) -> &'static str {
+    match self {
+      Field::Key | Field::U32 | Field::I32 => "buffer.int",
+      Field::I64 => "buffer.long",
+      Field::F32 => "buffer.float",
+      Field::F64 => "buffer.double",
+      Field::Bool => "buffer.get() != 0.toByte()",
+      Field::String => "buffer.getString()",
+      Field::Bytes => "ByteArray(buffer.int).also { buffer.get(it) }",
+      Field::Strings => "Array(buffer.int) { buffer.getString() }",
+      Field::Callback => "callbacks[buffer.int]",
+    }
+  }
+}
+
+/// A command of the view command buffer. `bindings::command_buffer` writes the fields in this
+/// order, a test over there keeps them honest.
+pub struct Command {
+  pub name: &'static str,
+  pub opcode: u8,
+  pub fields: &'static [(&'static str, Field)],
+  /// Carries it out on the host, with the view's ID as `id` and the fields by name
+  pub kotlin: &'static str,
+}
+
+const fn command(
+  name: &'static str,
+  opcode: u8,
+  fields: &'static [(&'static str, Field)],
+  kotlin: &'static str,
+) -> Command {
+  Command {
+    name,
+    opcode,
+    fields,
+    kotlin,
+  }
+}
+
+/// The host registers its root view under this ID before the first frame
+pub const ROOT_VIEW: u32 = 0;
+
+pub const COMMANDS: &[Command] = &[
+  command(
+    "CREATE_VIEW",
+    1,
+    &[("kind", Field::String)],
+    "views[id] = createView(kind)",
+  ),
+  command(
+    "CREATE_NATIVE_VIEW",
+    2,
+    &[("className", Field::String)],
+    "views[id] = createNativeView(className)",
+  ),
+  command("RELEASE_VIEW", 3, &[], "views.remove(id)"),
+  command(
+    "SET_F32",
+    10,
+    &[("k", Field::Key), ("v", Field::F32)],
+    "view(id).updateProp(k, v)",
+  ),
+  command(
+    "SET_F64",
+    11,
+    &[("k", Field::Key), ("v", Field::F64)],
+    "view(id).updateProp(k, v)",
+  ),
+  command(
+    "SET_I32",
+    12,
+    &[("k", Field::Key), ("v", Field::I32)],
+    "view(id).updateProp(k, v)",
+  ),
+  command(
+    "SET_I64",
+    13,
+    &[("k", Field::Key), ("v", Field::I64)],
+    "view(id).updateProp(k, v)",
+  ),
+  command(
+    "SET_BOOL",
+    14,
+    &[("k", Field::Key), ("v", Field::Bool)],
+    "view(id).updateProp(k, v)",
+  ),
+  command(
+    "SET_STRING",
+    15,
+    &[("k", Field::Key), ("v", Field::String)],
+    "view(id).updateProp(k, v)",
+  ),
+  command(
+    "SET_BYTES",
+    16,
+    &[("k", Field::Key), ("v", Field::Bytes)],
+    "view(id).updateProp(k, v)",
+  ),
+  command(
+    "SET_STRINGS",
+    17,
+    &[("k", Field::Key), ("v", Field::Strings)],
+    "view(id).updateProp(k, v)",
+  ),
+  command(
+    "SET_CALLBACK",
+    20,
+    &[("k", Field::Key), ("v", Field::Callback)],
+    "view(id).updateProp(k, v)",
+  ),
+  command(
+    "APPEND_CHILD",
+    30,
+    &[("child", Field::U32)],
+    "view(id).appendChild(view(child))",
+  ),
+  command(
+    "INSERT_CHILD_AT",
+    31,
+    &[("child", Field::U32), ("idx", Field::U32)],
+    "view(id).insertChildAt(view(child), idx)",
+  ),
+  command(
+    "REMOVE_CHILD",
+    32,
+    &[("child", Field::U32)],
+    "view(id).removeChild(view(child))",
+  ),
+  command(
+    "REMOVE_CHILD_INDEX",
+    33,
+    &[("idx", Field::U32)],
+    "view(id).removeChildIndex(idx)",
+  ),
+  command(
+    "MOVE_CHILD",
+    34,
+    &[("child", Field::U32), ("idx", Field::U32)],
+    "view(id).removeChild(view(child))\nview(id).insertChildAt(view(child), idx)",
+  ),
+];
+
+/// The opcodes as Rust source, for `bindings::command_buffer` to include
+pub fn rust_command_ops() -> String {
+  let mut out = String::new();
+  out.push_str("// Generated by wired_codegen from its command table, edit that instead.\n");
+  out.push_str("/// The host registers its root view under this ID before the first frame\n");
+  writeln!(out, "pub const ROOT_VIEW: ViewId = {};\n", ROOT_VIEW).unwrap();
+  for command in COMMANDS.iter() {
+    writeln!(out, "const {}: u8 = {};", command.name, command.opcode).unwrap();
+  }
+  out
+}
+
+/// The opcodes, for the host's command loop and for `setRootView`
+pub(crate) fn write_kotlin_ops(out: &mut String) {
+  out.push_str("// Opcodes of the view command buffer, Rust numbers them the same way\n");
+  out.push_str("object CommandOp {\n");
+  writeln!(out, "    const val ROOT_VIEW = {}", ROOT_VIEW).unwrap();
+  for command in COMMANDS.iter() {
+    writeln!(out, "    const val {} = {}", command.name, command.opcode).unwrap();
+  }
+  out.push_str("}\n");
+}
+
+/// The host's command loop, as members of the generated view factory. Every command's fields
+/// are read before it's carried out, so one that throws can be skipped and the rest of the
+/// frame still applies.
+pub(crate) fn write_kotlin_apply(out: &mut String) {
+  out.push_str(
+    r#"    // Views by the ID Rust gave them
+    private val views = HashMap<Int, WiredPlatformView>()
+
+    fun setRootView(root: WiredPlatformView) {
+        views[CommandOp.ROOT_VIEW] = root
+    }
+
+    private fun view(id: Int): WiredPlatformView =
+        views[id] ?: throw IllegalStateException("No view $id")
+
+    private fun createView(kind: String): WiredPlatformView =
+        javaClass.getMethod("create$kind").invoke(this) as WiredPlatformView
+
+    abstract fun createNativeView(className: String): WiredPlatformView
+
+    private fun ByteBuffer.getString(): String {
+        val bytes = ByteArray(int)
+        get(bytes)
+        return String(bytes, Charsets.UTF_8)
+    }
+
+    // A frame of view changes from Rust, see bindings/command_buffer.rs for the format. Commands
+    // that throw are skipped, and reported together once the rest of the frame is applied.
+    fun applyCommands(buffer: ByteBuffer, callbacks: Array<RustCallback>) {
+        buffer.order(ByteOrder.LITTLE_ENDIAN)
+        val failed = ArrayList<Int>()
+        val causes = ArrayList<Throwable>()
+        while (buffer.hasRemaining()) {
+            val start = buffer.position()
+            val command = try {
+                readCommand(buffer, callbacks)
+            } catch (e: Exception) {
+                // Nothing after a command that can't be read can be trusted
+                failed.add(start)
+                causes.add(e)
+                throw WiredCommandException(failed.toIntArray(), start, causes.toTypedArray())
+            }
+            try {
+                command()
+            } catch (e: Exception) {
+                failed.add(start)
+                causes.add(e)
+            }
+        }
+        if (failed.isNotEmpty()) {
+            throw WiredCommandException(failed.toIntArray(), buffer.limit(), causes.toTypedArray())
+        }
+    }
+
+    // Returns what carrying the command out does
+    private fun readCommand(buffer: ByteBuffer, callbacks: Array<RustCallback>): () -> Unit {
+        val op = buffer.get().toInt()
+        val id = buffer.int
+        when (op) {
+"#,
+  );
+  for command in COMMANDS.iter() {
+    writeln!(out, "            CommandOp.{} -> {{", command.name).unwrap();
+    for (name, field) in command.fields.iter() {
+      writeln!(
+        out,
+        "                val {} = {}",
+        name,
+        field.kotlin_read()
+      )
+      .unwrap();
+    }
+    let mut lines = command.kotlin.lines();
+    writeln!(out, "                return {{").unwrap();
+    for line in &mut lines {
+      writeln!(out, "                    {}", line).unwrap();
+    }
+    out.push_str("                }\n            }\n");
+  }
+  out.push_str(
+    r#"            else -> throw IllegalStateException("Unknown view command $op")
+        }
+    }
+"#,
+  );
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn opcodes_are_unique() {
+    for (i, a) in COMMANDS.iter().enumerate() {
+      for b in COMMANDS[i + 1..].iter() {
+        assert_ne!(a.opcode, b.opcode, "{} and {}", a.name, b.name);
+        assert_ne!(a.name, b.name);
+      }
+    }
+  }
+
+  #[test]
+  fn generates_ops_and_command_loop() {
+    let rust = rust_command_ops();
+    assert!(rust.contains("pub const ROOT_VIEW: ViewId = 0;\n"));
+    assert!(rust.contains("const SET_F32: u8 = 10;\n"));
+
+    let mut kotlin = String::new();
+    write_kotlin_ops(&mut kotlin);
+    assert!(kotlin.contains(
+      "object CommandOp {\n    const val ROOT_VIEW = 0\n    const val CREATE_VIEW = 1\n"
+    ));
+
+    let mut kotlin = String::new();
+    write_kotlin_apply(&mut kotlin);
+    assert!(kotlin.contains(
+      "            CommandOp.SET_BOOL -> {\n                val k = buffer.int\n                val v = buffer.get() != 0.toByte()\n                return {\n                    view(id).updateProp(k, v)\n                }\n            }\n"
+    ));
+    assert!(kotlin.contains(
+      "                return {\n                    view(id).removeChild(view(child))\n                    view(id).insertChildAt(view(child), idx)\n                }\n"
+    ));
+  }
+}
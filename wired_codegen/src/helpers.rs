@@ -0,0 +1,122 @@
+/// A prop that one of the view_helpers traits sends. The keys have to match what the trait
+/// methods in `bindings::view_helpers` send, a test over there keeps them honest.
+pub struct HelperProp {
+  pub key: &'static str,
+  /// The Kotlin `updateProp` overload it arrives at
+  pub kotlin_type: &'static str,
+  /// Handles it on the host, with the value as `v`
+  pub kotlin: &'static str,
+}
+
+pub struct Helper {
+  pub name: &'static str,
+  pub props: &'static [HelperProp],
+}
+
+const fn prop(key: &'static str, kotlin_type: &'static str, kotlin: &'static str) -> HelperProp {
+  HelperProp {
+    key,
+    kotlin_type,
+    kotlin,
+  }
+}
+
+const PADDING: &str = "applyPadding(this, k, v.toInt())";
+const MARGIN: &str = "applyMargin(this, k, v.toInt())";
+
+/// The view_helpers traits a widget can opt into. They only need the derived UpdateProp, so
+/// an empty impl is enough on the Rust side.
+pub const HELPERS: &[Helper] = &[
+  Helper {
+    name: "SetXY",
+    props: &[prop("set_x", "Float", "x = v"), prop("set_y", "Float", "y = v")],
+  },
+  Helper {
+    name: "SetText",
+    props: &[prop("text", "String", "text = v")],
+  },
+  Helper {
+    name: "SetTextSize",
+    props: &[prop(
+      "text_size",
+      "Float",
+      "setTextSize(TypedValue.COMPLEX_UNIT_PX, v)",
+    )],
+  },
+  Helper {
+    name: "Padding",
+    props: &[
+      prop("pad_left", "Float", PADDING),
+      prop("pad_top", "Float", PADDING),
+      prop("pad_right", "Float", PADDING),
+      prop("pad_bottom", "Float", PADDING),
+    ],
+  },
+  Helper {
+    name: "Margin",
+    props: &[
+      prop("margin_left", "Float", MARGIN),
+      prop("margin_top", "Float", MARGIN),
+      prop("margin_right", "Float", MARGIN),
+      prop("margin_bottom", "Float", MARGIN),
+    ],
+  },
+  Helper {
+    name: "SetHeightWidth",
+    props: &[
+      prop(
+        "height",
+        "Float",
+        "layoutParams = layoutParams.apply { height = v.toInt() }",
+      ),
+      prop(
+        "width",
+        "Float",
+        "layoutParams = layoutParams.apply { width = v.toInt() }",
+      ),
+    ],
+  },
+  Helper {
+    name: "SetOrientation",
+    props: &[prop(
+      "orientation",
+      "Int",
+      "when (v) {
+    PropEnums.ORIENTATION_VERTICAL -> orientation = LinearLayout.VERTICAL
+    PropEnums.ORIENTATION_HORIZONTAL -> orientation = LinearLayout.HORIZONTAL
+}",
+    )],
+  },
+  Helper {
+    name: "SetFling",
+    props: &[prop(
+      "fling",
+      "Int",
+      "when (v) {
+    PropEnums.FLING_ENABLED -> {
+        physics.enableFling()
+        physics.enablePhysics()
+    }
+    PropEnums.FLING_DISABLED -> physics.disableFling()
+}",
+    )],
+  },
+  Helper {
+    name: "OnPress",
+    props: &[
+      prop("on_press", "RustCallback", "setOnClickListener { v.press() }"),
+      prop(
+        "on_long_press",
+        "RustCallback",
+        "setOnLongClickListener {
+    v.longPress()
+    true
+}",
+      ),
+    ],
+  },
+];
+
+pub fn helper(name: &str) -> Option<&'static Helper> {
+  HELPERS.iter().find(|h| h.name == name)
+}
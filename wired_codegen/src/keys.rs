@@ -0,0 +1,83 @@
+use crate::helpers::HELPERS;
+use crate::spec::Spec;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// Every prop key the host handles, in ID order: the helpers', then the widgets' props and
+/// events, then the ones only hand written views take. Both sides number keys by this order.
+pub fn prop_keys(spec: &Spec) -> Vec<&str> {
+  let helper_keys = HELPERS.iter().flat_map(|h| h.props.iter()).map(|p| p.key);
+  let widget_keys = spec.widgets.iter().flat_map(|w| {
+    let props = w.props.iter().map(|p| p.name.as_str());
+    props.chain(w.events.iter().map(|e| e.name.as_str()))
+  });
+  let extra_keys = spec.keys.iter().map(|k| k.as_str());
+
+  let mut seen = HashSet::new();
+  helper_keys
+    .chain(widget_keys)
+    .chain(extra_keys)
+    .filter(|k| seen.insert(*k))
+    .collect()
+}
+
+/// The key table as Rust source, for `bindings::prop_key` to include. Every key also gets a
+/// `PropKey` constant named like the host's, so widgets can't use a key the spec doesn't have.
+pub fn rust_prop_keys(spec: &Spec) -> String {
+  let keys = prop_keys(spec);
+  let mut out = String::new();
+  out.push_str("// Generated by wired_codegen from the widget spec. Edit the spec, not this file.\n");
+  out.push_str("pub const PROP_KEYS: &[&str] = &[\n");
+  for key in keys.iter() {
+    writeln!(out, "  \"{}\",", key).unwrap();
+  }
+  out.push_str("];\n\nimpl PropKey {\n");
+  for (id, key) in keys.iter().enumerate() {
+    writeln!(out, "  pub const {}: PropKey = PropKey({});", kotlin_const(key), id).unwrap();
+  }
+  out.push_str("}\n");
+  out
+}
+
+/// What the host's `PropKeys`, and the Rust `PropKey` constants, call a key
+pub fn kotlin_const(key: &str) -> String {
+  key.to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn numbers_every_key_once() {
+    let spec = Spec::parse(
+      r#"
+      keys = ["checked", "text"]
+
+      [[widget]]
+      name = "Slider"
+      kind = "SliderView"
+      props = [{ name = "value", type = "f32" }, { name = "max", type = "f32" }]
+      events = [{ name = "on_change", payload = "f32" }]
+
+      [[widget]]
+      name = "Other"
+      kind = "OtherView"
+      props = [{ name = "value", type = "f32" }]
+      "#,
+    )
+    .unwrap();
+    let keys = prop_keys(&spec);
+
+    assert_eq!(keys[0], "set_x");
+    let tail = &keys[keys.len() - 4..];
+    assert_eq!(tail, &["value", "max", "on_change", "checked"]);
+    // text comes from SetText, so it keeps that spot
+    assert_eq!(keys.iter().filter(|k| **k == "text").count(), 1);
+    let code = rust_prop_keys(&spec);
+    assert!(code.contains("  \"on_change\",\n  \"checked\",\n];\n"));
+    assert!(code.contains("  pub const SET_X: PropKey = PropKey(0);\n"));
+    let last = keys.len() - 1;
+    assert!(code.contains(&format!("  pub const CHECKED: PropKey = PropKey({});\n}}\n", last)));
+  }
+}
@@ -0,0 +1,275 @@
+use crate::commands::{write_kotlin_apply, write_kotlin_ops};
+use crate::helpers::helper;
+use crate::keys::{kotlin_const, prop_keys};
+use crate::spec::{Spec, WidgetSpec};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+const PACKAGE: &str = "dev.fruit.androiddemo";
+
+/// What the helper snippets and the command loop lean on
+const IMPORTS: &[&str] = &[
+  "android.content.Context",
+  "android.util.TypedValue",
+  "android.view.View",
+  "android.widget.LinearLayout",
+  "java.nio.ByteBuffer",
+  "java.nio.ByteOrder",
+];
+
+// The updateProp overloads of WiredPlatformView, and whether it leaves them abstract
+const OVERLOADS: &[(&str, bool)] = &[
+  ("Float", true),
+  ("Int", false),
+  ("Long", false),
+  ("Double", false),
+  ("Boolean", false),
+  ("String", true),
+  ("RustCallback", true),
+  ("Any", true),
+];
+
+/// The host side of the spec: the prop key IDs, a WiredPlatformView subclass for each widget
+/// with a `kotlin` section, and a view factory with their `create*` methods and the command
+/// loop for WiredViewFactory to extend.
+pub fn kotlin_glue(spec: &Spec) -> String {
+  let widgets: Vec<&WidgetSpec> = spec.widgets.iter().filter(|w| w.kotlin.is_some()).collect();
+
+  let mut imports: BTreeSet<&str> = IMPORTS.iter().cloned().collect();
+  for widget in widgets.iter() {
+    let kotlin = widget.kotlin.as_ref().unwrap();
+    imports.insert(&kotlin.base);
+    imports.extend(kotlin.imports.iter().map(|i| i.as_str()));
+  }
+
+  let mut out = String::new();
+  out.push_str("// Generated by wired_codegen from the widget spec. Edit the spec, not this file.\n");
+  out.push_str("// Regenerate with `cargo run -p wired_codegen -- kotlin`\n\n");
+  writeln!(out, "package {}\n", PACKAGE).unwrap();
+  for import in imports {
+    writeln!(out, "import {}", import).unwrap();
+  }
+  out.push('\n');
+  write_prop_keys(&mut out, spec);
+  out.push('\n');
+  write_kotlin_ops(&mut out);
+  for widget in widgets.iter() {
+    out.push('\n');
+    write_view(&mut out, widget);
+  }
+  out.push('\n');
+  write_factory(&mut out, &widgets);
+  out
+}
+
+fn write_prop_keys(out: &mut String, spec: &Spec) {
+  let keys = prop_keys(spec);
+  out.push_str("// Props cross the bridge as these IDs, Rust numbers its keys the same way\n");
+  out.push_str("object PropKeys {\n");
+  for (id, key) in keys.iter().enumerate() {
+    writeln!(out, "    const val {} = {}", kotlin_const(key), id).unwrap();
+  }
+  out.push_str("\n    val NAMES = arrayOf(\n");
+  for key in keys.iter() {
+    writeln!(out, "        \"{}\",", key).unwrap();
+  }
+  out.push_str("    )\n}\n");
+}
+
+pub fn class_name(widget: &WidgetSpec) -> String {
+  format!("Wired{}", widget.name)
+}
+
+/// Every (overload, key, handler) the widget's view has to deal with
+fn handlers(widget: &WidgetSpec) -> Vec<(&str, &str, &str)> {
+  let helper_props = widget
+    .helpers
+    .iter()
+    .flat_map(|name| helper(name).expect("Validated with the spec").props.iter())
+    .map(|p| (p.kotlin_type, p.key, p.kotlin));
+  let props = widget.props.iter().map(|p| {
+    let kotlin = p.kotlin.as_ref().expect("Validated with the spec");
+    (p.ty.kotlin_type(), p.name.as_str(), kotlin.as_str())
+  });
+  let events = widget.events.iter().map(|e| {
+    let kotlin = e.kotlin.as_ref().expect("Validated with the spec");
+    ("RustCallback", e.name.as_str(), kotlin.as_str())
+  });
+  helper_props.chain(props).chain(events).collect()
+}
+
+// Puts a snippet after `prefix`, with the lines after the first indented to match
+fn write_snippet(out: &mut String, indent: usize, prefix: &str, snippet: &str) {
+  let pad = " ".repeat(indent);
+  let mut lines = snippet.trim_end().lines();
+  writeln!(out, "{}{}{}", pad, prefix, lines.next().unwrap_or("")).unwrap();
+  for line in lines {
+    if line.is_empty() {
+      out.push('\n');
+    } else {
+      writeln!(out, "{}{}", pad, line).unwrap();
+    }
+  }
+}
+
+fn write_view(out: &mut String, widget: &WidgetSpec) {
+  let kotlin = widget.kotlin.as_ref().unwrap();
+  let base = kotlin.base.rsplit('.').next().unwrap();
+  writeln!(
+    out,
+    "class {}(mContext: Context): {}(mContext), WiredPlatformView {{",
+    class_name(widget),
+    base
+  )
+  .unwrap();
+
+  let handlers = handlers(widget);
+  let mut first = true;
+  for (overload, required) in OVERLOADS.iter() {
+    let branches: Vec<_> = handlers.iter().filter(|h| h.0 == *overload).collect();
+    if branches.is_empty() && !required {
+      continue;
+    }
+    if !first {
+      out.push('\n');
+    }
+    first = false;
+    writeln!(out, "    override fun updateProp(k: Int, v: {}) {{", overload).unwrap();
+    if !branches.is_empty() {
+      out.push_str("        when (k) {\n");
+      for (_, key, kotlin) in branches {
+        write_snippet(out, 12, &format!("PropKeys.{} -> ", kotlin_const(key)), kotlin);
+      }
+      out.push_str("        }\n");
+    }
+    out.push_str("    }\n");
+  }
+
+  // Views that aren't ViewGroups still need these for WiredPlatformView
+  if !widget.container {
+    for signature in [
+      "addView(child: View)",
+      "addView(child: View, idx: Int)",
+      "removeView(child: View)",
+      "removeViewAt(idx: Int)",
+    ]
+    .iter()
+    {
+      write!(
+        out,
+        "\n    override fun {} {{\n        throw Error(\"Undefined\")\n    }}\n",
+        signature
+      )
+      .unwrap();
+    }
+  }
+  out.push_str("}\n");
+}
+
+fn write_factory(out: &mut String, widgets: &[&WidgetSpec]) {
+  out.push_str("// WiredViewFactory extends this with the hand written views\n");
+  out.push_str("abstract class WiredGeneratedViewFactory(val mContext: Context) {\n");
+  write_kotlin_apply(out);
+  for widget in widgets.iter() {
+    out.push('\n');
+    writeln!(out, "    fun create{}(): WiredPlatformView {{", widget.kind).unwrap();
+    writeln!(out, "        val view = {}(mContext)", class_name(widget)).unwrap();
+    if let Some(create) = &widget.kotlin.as_ref().unwrap().create {
+      write_snippet(out, 8, "", create);
+    }
+    out.push_str("        return view\n    }\n");
+  }
+  out.push_str("}\n\n");
+  out.push_str(
+    r#"// What applyCommands throws once the frame is applied. Rust reads which commands failed from
+// it, and how far the host got if it had to stop early.
+class WiredCommandException(val offsets: IntArray, val end: Int, val causes: Array<Throwable>) :
+    RuntimeException("View commands at bytes ${offsets.joinToString()} failed", causes[0])
+"#,
+  );
+}
+
+/// The first line (1 based) where `existing` differs from what the spec generates
+pub fn first_difference(spec: &Spec, existing: &str) -> Option<usize> {
+  let generated = kotlin_glue(spec);
+  if generated == existing {
+    return None;
+  }
+  let mut generated_lines = generated.lines();
+  let mut existing_lines = existing.lines();
+  let mut line = 1;
+  loop {
+    match (generated_lines.next(), existing_lines.next()) {
+      (Some(a), Some(b)) if a == b => line += 1,
+      _ => return Some(line),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn spec() -> Spec {
+    Spec::parse(
+      r#"
+      [[widget]]
+      name = "Slider"
+      kind = "SliderView"
+      helpers = ["Padding", "OnPress"]
+      props = [
+        { name = "value", type = "f32", kotlin = "progress = v.toInt()" },
+        { name = "mode", type = "enum crate::style::Orientation", kotlin = "mode = v", internal = true },
+      ]
+      events = [{ name = "on_change", payload = "f32", kotlin = "listen(v)" }]
+
+      [widget.kotlin]
+      base = "android.widget.SeekBar"
+      create = "view.max = 10"
+
+      [[widget]]
+      name = "Hand"
+      kind = "HandView"
+      "#,
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn generates_views_and_factory() {
+    let code = kotlin_glue(&spec());
+
+    assert!(code.contains("import android.widget.SeekBar\n"));
+    assert!(code.contains("class WiredSlider(mContext: Context): SeekBar(mContext), WiredPlatformView {"));
+    assert!(code.contains("object PropKeys {\n    const val SET_X = 0\n"));
+    assert!(code.contains("    val NAMES = arrayOf(\n        \"set_x\",\n"));
+    assert!(code.contains("            PropKeys.PAD_LEFT -> applyPadding(this, k, v.toInt())\n"));
+    assert!(code.contains("            PropKeys.VALUE -> progress = v.toInt()\n"));
+    assert!(code.contains("override fun updateProp(k: Int, v: Int) {\n        when (k) {\n            PropKeys.MODE -> mode = v\n"));
+    assert!(code.contains("            PropKeys.ON_LONG_PRESS -> setOnLongClickListener {\n                v.longPress()\n                true\n            }\n"));
+    assert!(code.contains("            PropKeys.ON_CHANGE -> listen(v)\n"));
+    // Nothing to handle, but it has to be there
+    assert!(code.contains("    override fun updateProp(k: Int, v: Any) {\n    }\n"));
+    assert!(!code.contains("v: Boolean"));
+    assert!(code.contains("override fun removeViewAt(idx: Int) {"));
+    assert!(code.contains("object CommandOp {\n    const val ROOT_VIEW = 0\n"));
+    assert!(code.contains("abstract class WiredGeneratedViewFactory(val mContext: Context) {\n    // Views by the ID"));
+    assert!(code.contains("            CommandOp.APPEND_CHILD -> {\n                val child = buffer.int\n"));
+    assert!(code.contains("class WiredCommandException(val offsets: IntArray, val end: Int"));
+    assert!(code.contains("    fun createSliderView(): WiredPlatformView {\n        val view = WiredSlider(mContext)\n        view.max = 10\n        return view\n"));
+    // Hand written on the host
+    assert!(!code.contains("Hand"));
+  }
+
+  #[test]
+  fn finds_where_the_glue_diverges() {
+    let spec = spec();
+    let code = kotlin_glue(&spec);
+    assert_eq!(first_difference(&spec, &code), None);
+
+    let edited = code.replace("PropKeys.VALUE ->", "PropKeys.VALU ->");
+    let line = code.lines().position(|l| l.contains("PropKeys.VALUE ->")).unwrap() + 1;
+    assert_eq!(first_difference(&spec, &edited), Some(line));
+    assert!(first_difference(&spec, &code[..code.len() - 2]).is_some());
+  }
+}
@@ -0,0 +1,118 @@
+//! Generates widget bindings from a declarative spec, so adding a native widget is a spec edit.
+//! It also numbers every prop key, props cross the bridge as those IDs rather than as strings,
+//! and has the view command table both sides read frames of view changes with.
+//! The Rust side is generated by the root build.rs. The Kotlin side is checked in, regenerate
+//! it with `cargo run -p wired_codegen -- kotlin`, and the build fails if it's out of date.
+//!
+//! ```toml
+//! keys = ["checked"]       # Keys only hand written host views take
+//!
+//! [[widget]]
+//! name = "Slider"          # The Rust type
+//! kind = "SliderView"      # The host's view factory makes it with `createSliderView`
+//! container = false        # Containers get ParentWith
+//! helpers = ["Padding", "Margin"]
+//! props = [
+//!   { name = "value", type = "f32" },
+//!   { name = "orientation", type = "enum crate::style::Orientation" },
+//! ]
+//! events = [{ name = "on_change", payload = "f32" }]
+//!
+//! [widget.kotlin]             # Leave it out if the host view is hand written
+//! base = "android.widget.SeekBar"
+//! create = "view.max = 100"   # Runs in the view factory
+//! ```
+//!
+//! With a `kotlin` section every prop and event needs a `kotlin = "..."` handler, which gets
+//! the value (or the RustCallback) as `v`.
+
+pub mod commands;
+pub mod helpers;
+mod keys;
+mod kotlin;
+mod rust;
+pub mod spec;
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub use keys::{prop_keys, rust_prop_keys};
+pub use kotlin::kotlin_glue;
+pub use rust::rust_bindings;
+pub use spec::Spec;
+
+#[derive(Debug)]
+pub enum CodegenError {
+  Io(PathBuf, io::Error),
+  Parse(toml::de::Error),
+  /// The spec parsed, but wouldn't generate valid code
+  Invalid(String),
+  /// Checked in generated code that no longer matches the spec
+  OutOfDate { path: PathBuf, line: usize },
+}
+
+impl Display for CodegenError {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      CodegenError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+      CodegenError::Parse(e) => write!(f, "Couldn't parse widget spec: {}", e),
+      CodegenError::Invalid(msg) => write!(f, "Invalid widget spec: {}", msg),
+      CodegenError::OutOfDate { path, line } => write!(
+        f,
+        "{} doesn't match the widget spec (line {}), regenerate it with \
+         `cargo run -p wired_codegen -- kotlin`",
+        path.display(),
+        line
+      ),
+    }
+  }
+}
+
+impl Error for CodegenError {}
+
+pub fn load_spec(path: &Path) -> Result<Spec, CodegenError> {
+  let source = fs::read_to_string(path).map_err(|e| CodegenError::Io(path.into(), e))?;
+  Spec::parse(&source)
+}
+
+/// Reads the spec at `spec_path` and writes the Rust widgets to `out_path`, to be `include!`d
+pub fn generate_rust_bindings(spec_path: &Path, out_path: &Path) -> Result<(), CodegenError> {
+  let spec = load_spec(spec_path)?;
+  fs::write(out_path, rust_bindings(&spec)).map_err(|e| CodegenError::Io(out_path.into(), e))
+}
+
+/// Writes the prop key table for the spec at `spec_path` to `out_path`, to be `include!`d
+pub fn generate_rust_prop_keys(spec_path: &Path, out_path: &Path) -> Result<(), CodegenError> {
+  let spec = load_spec(spec_path)?;
+  fs::write(out_path, rust_prop_keys(&spec)).map_err(|e| CodegenError::Io(out_path.into(), e))
+}
+
+/// Writes the command buffer opcodes to `out_path`, to be `include!`d
+pub fn generate_rust_command_ops(out_path: &Path) -> Result<(), CodegenError> {
+  fs::write(out_path, commands::rust_command_ops())
+    .map_err(|e| CodegenError::Io(out_path.into(), e))
+}
+
+/// Writes the host's prop key IDs, command loop, view classes and view factory for the spec at `spec_path`
+pub fn generate_kotlin_glue(spec_path: &Path, out_path: &Path) -> Result<(), CodegenError> {
+  let spec = load_spec(spec_path)?;
+  fs::write(out_path, kotlin_glue(&spec)).map_err(|e| CodegenError::Io(out_path.into(), e))
+}
+
+/// Fails if the Kotlin at `kotlin_path` isn't what the spec generates, e.g. when a prop was
+/// added on one side only or the file was edited by hand
+pub fn check_kotlin_glue(spec_path: &Path, kotlin_path: &Path) -> Result<(), CodegenError> {
+  let spec = load_spec(spec_path)?;
+  let existing =
+    fs::read_to_string(kotlin_path).map_err(|e| CodegenError::Io(kotlin_path.into(), e))?;
+  match kotlin::first_difference(&spec, &existing) {
+    None => Ok(()),
+    Some(line) => Err(CodegenError::OutOfDate {
+      path: kotlin_path.into(),
+      line,
+    }),
+  }
+}
@@ -0,0 +1,26 @@
+//! `cargo run -p wired_codegen -- kotlin [--check]`, run from the repo root. Writes the host's
+//! generated Kotlin, or with `--check` fails if it doesn't match the widget spec.
+
+use std::env;
+use std::path::Path;
+use std::process;
+
+const SPEC: &str = "src/bindings/android/widgets.toml";
+const KOTLIN: &str = "android/app/src/main/java/dev/fruit/androiddemo/WiredGenerated.kt";
+
+fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+  let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+  let result = match args.as_slice() {
+    ["kotlin"] => wired_codegen::generate_kotlin_glue(Path::new(SPEC), Path::new(KOTLIN)),
+    ["kotlin", "--check"] => wired_codegen::check_kotlin_glue(Path::new(SPEC), Path::new(KOTLIN)),
+    _ => {
+      eprintln!("usage: wired_codegen kotlin [--check]");
+      process::exit(2);
+    }
+  };
+  if let Err(e) = result {
+    eprintln!("{}", e);
+    process::exit(1);
+  }
+}
@@ -0,0 +1,207 @@
+use crate::keys::kotlin_const;
+use crate::spec::{EventSpec, PropSpec, PropType, Spec, WidgetSpec};
+use std::fmt::Write;
+
+/// The widgets in `spec` as Rust source. It expects to be included in
+/// `bindings::android::views`, where `create_wired_native_view`, `PropKey` and friends are in
+/// scope.
+pub fn rust_bindings(spec: &Spec) -> String {
+  let mut out = String::new();
+  out.push_str("// Generated by wired_codegen from the widget spec. Edit the spec, not this file.\n");
+  for widget in spec.widgets.iter() {
+    out.push('\n');
+    write_widget(&mut out, widget);
+  }
+  out
+}
+
+fn write_widget(out: &mut String, widget: &WidgetSpec) {
+  let name = &widget.name;
+  if let Some(doc) = &widget.doc {
+    for line in doc.lines() {
+      writeln!(out, "/// {}", line).unwrap();
+    }
+  }
+  write!(
+    out,
+    r#"#[derive(UpdateProp)]
+pub struct {name} {{
+  pub(crate) inner: PlatformView,
+  after_remove: AttachedFutures,
+}}
+
+impl Default for {name} {{
+  fn default() -> Self {{
+    {name}::new()
+  }}
+}}
+
+impl {name} {{
+  pub fn new() -> Self {{
+    {name} {{
+      inner: PlatformView::new(create_wired_native_view("{kind}")),
+      after_remove: vec![],
+    }}
+  }}
+"#,
+    name = name,
+    kind = widget.kind,
+  )
+  .unwrap();
+  for prop in widget.props.iter().filter(|p| !p.internal) {
+    write_prop(out, prop);
+  }
+  for event in widget.events.iter() {
+    write_event(out, event);
+  }
+  out.push_str("}\n\n");
+
+  for helper in widget.helpers.iter() {
+    writeln!(out, "impl {} for {} {{}}", helper, name).unwrap();
+  }
+  if !widget.helpers.is_empty() {
+    out.push('\n');
+  }
+
+  if widget.container {
+    write!(
+      out,
+      r#"impl ParentWith for {name} {{
+  fn with<F>(mut self, f: F) -> Self
+  where
+    F: FnOnce(),
+  {{
+    with_parent(&mut self.inner, f);
+    self
+  }}
+}}
+
+"#,
+      name = name,
+    )
+    .unwrap();
+  }
+
+  write!(
+    out,
+    r#"impl Composable for {name} {{
+  fn compose(&mut self, composer: &mut Composer) {{
+    let mut after_remove = vec![];
+    std::mem::swap(&mut self.after_remove, &mut after_remove);
+    composer
+      .add_view_with_futures(&mut self.inner, Some(after_remove))
+      .expect("Couldn't add {name} view");
+  }}
+}}
+
+auto_compose!({name});
+"#,
+    name = name,
+  )
+  .unwrap();
+}
+
+fn write_prop(out: &mut String, prop: &PropSpec) {
+  let name = &prop.name;
+  let ty = prop.ty.rust_type();
+  // How a value (`v`) and a signal (`s`) of the setter's type become something the view takes
+  let (arg, value, signal) = match &prop.ty {
+    PropType::String => ("T".to_string(), "v.into()", "s"),
+    PropType::Enum(_) => (
+      ty.to_string(),
+      "crate::style::PropEnum::code(v)",
+      "s.map(crate::style::PropEnum::code)",
+    ),
+    _ => (ty.to_string(), "v", "s"),
+  };
+  let generics = match &prop.ty {
+    PropType::String => "<T: Into<String>>",
+    _ => "",
+  };
+  write!(
+    out,
+    r#"
+  pub fn {name}{generics}(mut self, v: {arg}) -> Self {{
+    self
+      .update_prop(PropKey::{key}, {value})
+      .expect("Couldn't update {name}");
+    self
+  }}
+
+  pub fn {name}_signal<S>(mut self, s: S) -> Self
+  where
+    S: 'static + Signal<Item = {ty}> + Send,
+  {{
+    self
+      .update_prop_signal(PropKey::{key}, {signal})
+      .expect("Couldn't update {name} from signal");
+    self
+  }}
+"#,
+    name = name,
+    key = kotlin_const(name),
+    generics = generics,
+    arg = arg,
+    value = value,
+    ty = ty,
+    signal = signal,
+  )
+  .unwrap();
+}
+
+fn write_event(out: &mut String, event: &EventSpec) {
+  write!(
+    out,
+    r#"
+  pub fn {name}(mut self, cb: Callback<{payload}>) -> Self {{
+    self
+      .update_prop(PropKey::{key}, cb)
+      .expect("Couldn't update {name}");
+    self
+  }}
+"#,
+    name = event.name,
+    key = kotlin_const(&event.name),
+    payload = event.payload.rust_type(),
+  )
+  .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn generates_a_widget() {
+    let spec = Spec::parse(
+      r#"
+      [[widget]]
+      name = "Slider"
+      kind = "SliderView"
+      container = true
+      helpers = ["Padding", "Margin"]
+      props = [
+        { name = "value", type = "f32" },
+        { name = "label", type = "String" },
+        { name = "orientation", type = "enum crate::style::Orientation" },
+      ]
+      events = [{ name = "on_change", payload = "f32" }]
+      "#,
+    )
+    .unwrap();
+    let code = rust_bindings(&spec);
+
+    assert!(code.contains("pub struct Slider {"));
+    assert!(code.contains("create_wired_native_view(\"SliderView\")"));
+    assert!(code.contains("pub fn value(mut self, v: f32) -> Self {"));
+    assert!(code.contains("S: 'static + Signal<Item = f32> + Send,"));
+    assert!(code.contains("pub fn label<T: Into<String>>(mut self, v: T) -> Self {"));
+    assert!(code.contains(".update_prop(PropKey::ORIENTATION, crate::style::PropEnum::code(v))"));
+    assert!(code.contains("(PropKey::ORIENTATION, s.map(crate::style::PropEnum::code))"));
+    assert!(code.contains(".update_prop(PropKey::ON_CHANGE, cb)"));
+    assert!(code.contains("pub fn on_change(mut self, cb: Callback<f32>) -> Self {"));
+    assert!(code.contains("impl Padding for Slider {}\nimpl Margin for Slider {}\n"));
+    assert!(code.contains("impl ParentWith for Slider {"));
+    assert!(code.contains("auto_compose!(Slider);"));
+  }
+}
@@ -0,0 +1,307 @@
+use crate::helpers::helper;
+use crate::CodegenError;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Spec {
+  /// Prop keys only the hand written host views take, so they get an ID too
+  #[serde(default)]
+  pub keys: Vec<String>,
+  #[serde(rename = "widget", default)]
+  pub widgets: Vec<WidgetSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WidgetSpec {
+  /// The Rust type
+  pub name: String,
+  /// What the host's view factory calls it, we ask it for `create{kind}`
+  pub kind: String,
+  /// Whether it takes children, containers get `ParentWith`
+  #[serde(default)]
+  pub container: bool,
+  #[serde(default)]
+  pub doc: Option<String>,
+  /// Names from `helpers::HELPERS`
+  #[serde(default)]
+  pub helpers: Vec<String>,
+  #[serde(default)]
+  pub props: Vec<PropSpec>,
+  #[serde(default)]
+  pub events: Vec<EventSpec>,
+  /// Widgets without it have a hand written host view
+  #[serde(default)]
+  pub kotlin: Option<KotlinSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KotlinSpec {
+  /// The fully qualified Android view the generated one extends
+  pub base: String,
+  #[serde(default)]
+  pub imports: Vec<String>,
+  /// Runs in the view factory after the view is made, as `view`
+  #[serde(default)]
+  pub create: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PropSpec {
+  pub name: String,
+  #[serde(rename = "type")]
+  pub ty: PropType,
+  /// Set by the framework rather than by users, so there's no Rust setter for it
+  #[serde(default)]
+  pub internal: bool,
+  /// Handles it on the host, with the value as `v`
+  #[serde(default)]
+  pub kotlin: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventSpec {
+  /// Usually `on_something`
+  pub name: String,
+  #[serde(default = "unit_payload")]
+  pub payload: Payload,
+  /// Hooks the RustCallback `v` up to the host view
+  #[serde(default)]
+  pub kotlin: Option<String>,
+}
+
+fn unit_payload() -> Payload {
+  Payload::Unit
+}
+
+/// What a prop carries across the bridge
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum PropType {
+  Bool,
+  I32,
+  I64,
+  F32,
+  F64,
+  String,
+  /// `enum path::to::Type`, a `prop_enum!` type that crosses as its i32 code
+  Enum(String),
+}
+
+impl PropType {
+  /// The type the setter takes
+  pub fn rust_type(&self) -> &str {
+    match self {
+      PropType::Bool => "bool",
+      PropType::I32 => "i32",
+      PropType::I64 => "i64",
+      PropType::F32 => "f32",
+      PropType::F64 => "f64",
+      PropType::String => "String",
+      PropType::Enum(path) => path,
+    }
+  }
+
+  /// The Kotlin `updateProp` overload it arrives at
+  pub fn kotlin_type(&self) -> &'static str {
+    match self {
+      PropType::Bool => "Boolean",
+      PropType::I32 | PropType::Enum(_) => "Int",
+      PropType::I64 => "Long",
+      PropType::F32 => "Float",
+      PropType::F64 => "Double",
+      PropType::String => "String",
+    }
+  }
+}
+
+impl TryFrom<String> for PropType {
+  type Error = String;
+
+  fn try_from(s: String) -> Result<Self, Self::Error> {
+    Ok(match s.as_str() {
+      "bool" => PropType::Bool,
+      "i32" => PropType::I32,
+      "i64" => PropType::I64,
+      "f32" => PropType::F32,
+      "f64" => PropType::F64,
+      "String" => PropType::String,
+      _ if s.starts_with("enum ") => PropType::Enum(s["enum ".len()..].trim().into()),
+      _ => return Err(format!("unknown prop type {}", s)),
+    })
+  }
+}
+
+/// What an event hands its callback, one of the `FromEvent` types
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Payload {
+  Unit,
+  Press,
+  Event,
+  String,
+  F32,
+  Bool,
+  I32,
+}
+
+impl Payload {
+  pub fn rust_type(&self) -> &'static str {
+    match self {
+      Payload::Unit => "()",
+      Payload::Press => "crate::bindings::callback::PressEvent",
+      Payload::Event => "crate::bindings::callback::Event",
+      Payload::String => "String",
+      Payload::F32 => "f32",
+      Payload::Bool => "bool",
+      Payload::I32 => "i32",
+    }
+  }
+}
+
+impl TryFrom<String> for Payload {
+  type Error = String;
+
+  fn try_from(s: String) -> Result<Self, Self::Error> {
+    Ok(match s.as_str() {
+      "()" => Payload::Unit,
+      "PressEvent" => Payload::Press,
+      "Event" => Payload::Event,
+      "String" => Payload::String,
+      "f32" => Payload::F32,
+      "bool" => Payload::Bool,
+      "i32" => Payload::I32,
+      _ => return Err(format!("unknown event payload {}", s)),
+    })
+  }
+}
+
+impl Spec {
+  pub fn parse(source: &str) -> Result<Spec, CodegenError> {
+    let spec: Spec = toml::from_str(source).map_err(CodegenError::Parse)?;
+    spec.validate()?;
+    Ok(spec)
+  }
+
+  // Catch what would otherwise be a confusing compile error in generated code
+  fn validate(&self) -> Result<(), CodegenError> {
+    let invalid = |msg: String| Err(CodegenError::Invalid(msg));
+    let mut names = HashSet::new();
+    let mut kinds = HashSet::new();
+    let mut keys = HashSet::new();
+    for key in self.keys.iter() {
+      if !is_ident(key) || !keys.insert(key) {
+        return invalid(format!("{} is a bad or duplicate key", key));
+      }
+    }
+    for widget in self.widgets.iter() {
+      if !is_ident(&widget.name) {
+        return invalid(format!("{} is not a valid widget name", widget.name));
+      }
+      if !names.insert(&widget.name) {
+        return invalid(format!("{} is declared twice", widget.name));
+      }
+      if !is_ident(&widget.kind) || !kinds.insert(&widget.kind) {
+        return invalid(format!("{} has a bad or duplicate kind {}", widget.name, widget.kind));
+      }
+      let mut methods = HashSet::new();
+      for name in widget.helpers.iter() {
+        let helper = match helper(name) {
+          Some(helper) => helper,
+          None => return invalid(format!("{} uses unknown helper {}", widget.name, name)),
+        };
+        methods.extend(helper.props.iter().map(|p| p.key.to_string()));
+      }
+
+      let prop_names = widget.props.iter().map(|p| &p.name);
+      for name in prop_names.chain(widget.events.iter().map(|e| &e.name)) {
+        if !is_ident(name) {
+          return invalid(format!("{}.{} is not a valid prop name", widget.name, name));
+        }
+        if !methods.insert(name.clone()) {
+          return invalid(format!("{}.{} is declared twice or by a helper", widget.name, name));
+        }
+      }
+
+      if let Some(kotlin) = &widget.kotlin {
+        if !kotlin.base.contains('.') {
+          return invalid(format!("{}'s Kotlin base should be fully qualified", widget.name));
+        }
+        let missing = widget
+          .props
+          .iter()
+          .filter(|p| p.kotlin.is_none())
+          .map(|p| &p.name)
+          .chain(widget.events.iter().filter(|e| e.kotlin.is_none()).map(|e| &e.name))
+          .next();
+        if let Some(name) = missing {
+          return invalid(format!("{}.{} needs a kotlin handler", widget.name, name));
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+fn is_ident(s: &str) -> bool {
+  let mut chars = s.chars();
+  match chars.next() {
+    Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+    _ => return false,
+  }
+  chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_a_widget() {
+    let spec = Spec::parse(
+      r#"
+      [[widget]]
+      name = "Slider"
+      kind = "SliderView"
+      helpers = ["Padding"]
+      props = [
+        { name = "value", type = "f32" },
+        { name = "orientation", type = "enum crate::style::Orientation" },
+      ]
+      events = [{ name = "on_change", payload = "f32" }, { name = "on_done" }]
+      "#,
+    )
+    .unwrap();
+
+    let slider = &spec.widgets[0];
+    assert!(!slider.container);
+    assert_eq!(slider.props[0].ty, PropType::F32);
+    assert_eq!(slider.props[1].ty, PropType::Enum("crate::style::Orientation".into()));
+    assert_eq!(slider.events[0].payload, Payload::F32);
+    assert_eq!(slider.events[1].payload, Payload::Unit);
+  }
+
+  #[test]
+  fn rejects_bad_specs() {
+    let bad = [
+      "[[widget]]\nname = \"A\"\nkind = \"AView\"\nhelpers = [\"Nope\"]",
+      "[[widget]]\nname = \"A\"\nkind = \"AView\"\nprops = [{ name = \"x\", type = \"u8\" }]",
+      "[[widget]]\nname = \"A\"\nkind = \"AView\"\n[[widget]]\nname = \"A\"\nkind = \"BView\"",
+      "[[widget]]\nname = \"A\"\nkind = \"AView\"\nprops = [{ name = \"x\", type = \"f32\" }]\nevents = [{ name = \"x\" }]",
+      "[[widget]]\nname = \"A\"\nkind = \"AView\"\ncolor = \"red\"",
+      "[[widget]]\nname = \"A\"\nkind = \"AView\"\nhelpers = [\"Padding\"]\nprops = [{ name = \"pad_top\", type = \"f32\" }]",
+      "[[widget]]\nname = \"A\"\nkind = \"AView\"\nprops = [{ name = \"x\", type = \"f32\" }]\n[widget.kotlin]\nbase = \"android.view.View\"",
+      "keys = [\"checked\", \"checked\"]",
+      "keys = [\"not-a-key\"]",
+    ];
+    for source in bad.iter() {
+      assert!(Spec::parse(source).is_err(), "{} should be rejected", source);
+    }
+  }
+}
@@ -0,0 +1,104 @@
+extern crate proc_macro;
+
+mod update_prop;
+mod view;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Declarative UI trees. Expands to the usual builder calls, so every widget still composes
+/// when its statement ends. Use it through the app crate's `view!`, which tells this one where
+/// the helpers live.
+///
+/// ```
+/// # // Stand-ins for the app crate, with the same constructors as the real widgets
+/// # macro_rules! view {
+/// #   ($($tokens:tt)*) => { wired_macros::view! { crate; $($tokens)* } };
+/// # }
+/// # mod helpers {
+/// #   pub fn if_signal<F: Fn(bool)>(s: bool, f: F) { f(s) }
+/// #   pub fn match_signal<M, F: Fn(M)>(s: M, f: F) { f(s) }
+/// #   pub fn for_signal_vec<T, F: Fn(T)>(s: Vec<T>, f: F) { s.into_iter().for_each(f) }
+/// # }
+/// # struct Mutable<T>(T);
+/// # impl<T: Clone> Mutable<T> {
+/// #   fn new(v: T) -> Self { Mutable(v) }
+/// #   fn signal(&self) -> T { self.0.clone() }
+/// #   fn signal_cloned(&self) -> T { self.0.clone() }
+/// # }
+/// # struct MutableVec<T>(Vec<T>);
+/// # impl<T: Clone> MutableVec<T> {
+/// #   fn new_with_values(v: Vec<T>) -> Self { MutableVec(v) }
+/// #   fn signal_vec_cloned(&self) -> Vec<T> { self.0.clone() }
+/// # }
+/// # enum Orientation { Vertical }
+/// # struct Sp;
+/// # fn sp(_: f32) -> Sp { Sp }
+/// # struct Text;
+/// # impl Text {
+/// #   fn new<S: Into<String>>(_: S) -> Self { Text }
+/// #   fn text<S: Into<String>>(self, _: S) -> Self { self }
+/// #   fn text_signal(self, _: String) -> Self { self }
+/// #   fn text_size(self, _: Sp) -> Self { self }
+/// # }
+/// # struct Button;
+/// # impl Button {
+/// #   fn new<F: Fn() + Send + Sync + 'static>(_: F) -> Self { Button }
+/// #   fn text<S: Into<String>>(self, _: S) -> Self { self }
+/// # }
+/// # struct StackLayout;
+/// # impl StackLayout {
+/// #   fn new() -> Self { StackLayout }
+/// #   fn orientation(self, _: Orientation) -> Self { self }
+/// #   fn with<F: FnOnce()>(self, f: F) -> Self { f(); self }
+/// # }
+/// # fn main() {
+/// let name = Mutable::new(String::from("Ada"));
+/// let show_button = Mutable::new(true);
+/// let slide = Mutable::new(0);
+/// let items = MutableVec::new_with_values(vec![String::from("a"), String::from("b")]);
+/// let go = || println!("Go!");
+///
+/// view! {
+///   StackLayout(orientation = Orientation::Vertical) {
+///     Text("Hello", text_size = sp(32.0));
+///     Text("", text = {name.signal_cloned()});
+///     if {show_button.signal()} {
+///       Button(go, text = "Go")
+///     } else {
+///       Text("Nothing to do")
+///     }
+///     match {slide.signal()} {
+///       0 => Text("First"),
+///       n if n < 3 => { Text(format!("Slide {}", n)) }
+///       _ => Text("Others"),
+///     }
+///     for item in {items.signal_vec_cloned()} {
+///       Text(item)
+///     }
+///     { println!("Any plain Rust"); }
+///   }
+/// }
+/// # }
+/// ```
+///
+/// - `Widget(a, b)` calls `Widget::new(a, b)`.
+/// - `attr = value` calls `.attr(value)`, and `attr = {signal}` calls `.attr_signal(signal)`.
+/// - Children in braces go in `.with(|| ..)`.
+/// - `if`, `match` and `for` take their signal in braces and lower to `helpers::if_signal`,
+///   `helpers::match_signal` and `helpers::for_signal_vec`. Their bodies are `move` closures.
+#[proc_macro]
+pub fn view(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as view::ViewInput);
+  input.nodes.expand(&input.krate).into()
+}
+
+/// Forwards `UpdateProp` and `UpdatePropSignal` to the struct's `inner: PlatformView`. Signal
+/// futures are pushed to its `after_remove: AttachedFutures`, so both fields are required.
+#[proc_macro_derive(UpdateProp)]
+pub fn derive_update_prop(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  update_prop::derive(input)
+    .unwrap_or_else(|e| e.to_compile_error())
+    .into()
+}
@@ -0,0 +1,102 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Data, DeriveInput, Error, Fields, Result};
+
+// The fields the generated impls use
+const REQUIRED_FIELDS: [&str; 2] = ["inner", "after_remove"];
+
+/// `UpdateProp` and `UpdatePropSignal` for any value the struct's `inner: PlatformView` takes.
+/// Signal futures go in `after_remove`, so they stop when the view is removed.
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+  check_fields(&input)?;
+  let name = &input.ident;
+
+  // The prop value is a type parameter of its own on top of the struct's
+  let mut generics = input.generics.clone();
+  generics.params.push(parse_quote!(__V));
+  let (impl_generics, _, _) = generics.split_for_impl();
+  let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+  let mut where_clause = where_clause.cloned().unwrap_or_else(|| parse_quote!(where));
+  where_clause.predicates.push(
+    parse_quote!(crate::ui_tree::PlatformView: crate::bindings::view_helpers::UpdateProp<__V>),
+  );
+  let mut signal_where_clause = where_clause.clone();
+  signal_where_clause
+    .predicates
+    .push(parse_quote!(__V: 'static));
+
+  Ok(quote! {
+    impl #impl_generics crate::bindings::view_helpers::UpdateProp<__V> for #name #ty_generics
+    #where_clause
+    {
+      fn update_prop(
+        &mut self,
+        k: crate::bindings::prop_key::PropKey,
+        v: __V,
+      ) -> Result<(), crate::error::WiredError> {
+        crate::bindings::view_helpers::UpdateProp::update_prop(&mut self.inner, k, v)
+      }
+    }
+
+    impl #impl_generics crate::bindings::view_helpers::UpdatePropSignal<__V> for #name #ty_generics
+    #signal_where_clause
+    {
+      fn update_prop_signal<S>(
+        &mut self,
+        k: crate::bindings::prop_key::PropKey,
+        s: S,
+      ) -> Result<(), crate::error::WiredError>
+      where
+        S: 'static + futures_signals::signal::Signal<Item = __V> + Send,
+      {
+        let mut inner = self.inner.clone();
+        let f = futures_signals::signal::SignalExt::for_each(s, move |v| {
+          crate::bindings::view_helpers::UpdateProp::update_prop(&mut inner, k, v)
+            .expect("Couldn't update prop from signal");
+          futures::future::ready(())
+        });
+        // Off Android it's the test executor
+        #[cfg(target_os = "android")]
+        let handle = crate::android_executor::spawn_future(f);
+        #[cfg(not(target_os = "android"))]
+        let handle = crate::ui_tree::spawn_future(f);
+        self.after_remove.push(handle);
+        Ok(())
+      }
+    }
+  })
+}
+
+fn check_fields(input: &DeriveInput) -> Result<()> {
+  let fields = match &input.data {
+    Data::Struct(data) => match &data.fields {
+      Fields::Named(fields) => fields,
+      _ => {
+        return Err(Error::new_spanned(
+          &input.ident,
+          "UpdateProp needs named fields",
+        ))
+      }
+    },
+    _ => {
+      return Err(Error::new_spanned(
+        &input.ident,
+        "UpdateProp only works on structs",
+      ))
+    }
+  };
+  for required in REQUIRED_FIELDS.iter() {
+    let found = fields
+      .named
+      .iter()
+      .filter_map(|f| f.ident.as_ref())
+      .any(|i| i == required);
+    if !found {
+      return Err(Error::new_spanned(
+        &input.ident,
+        format!("UpdateProp needs an `{}` field", required),
+      ));
+    }
+  }
+  Ok(())
+}
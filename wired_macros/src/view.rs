@@ -0,0 +1,398 @@
+use proc_macro2::{TokenStream, TokenTree};
+use quote::quote;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::punctuated::Punctuated;
+use syn::{
+  braced, parenthesized, token, AngleBracketedGenericArguments, Block, Expr, Ident, Pat, Path,
+  PathArguments, PathSegment, Token,
+};
+
+/// What `view!` gets: the path of the crate the helpers live in, then the nodes, e.g.
+/// `$crate; Text("Hi")`. Apps use the crate's own `view!`, which fills the path in.
+pub struct ViewInput {
+  pub krate: TokenStream,
+  pub nodes: Nodes,
+}
+
+/// A run of sibling nodes. `;` and `,` between them are optional.
+pub struct Nodes(Vec<Node>);
+
+enum Node {
+  Element(Element),
+  /// `if {signal} { .. } else { .. }`
+  If {
+    cond: Expr,
+    then: Nodes,
+    otherwise: Option<Nodes>,
+  },
+  /// `match {signal} { pat => node, .. }`
+  Match { signal: Expr, arms: Vec<Arm> },
+  /// `for pat in {signal_vec} { .. }`
+  For {
+    pat: Pat,
+    signal_vec: Expr,
+    body: Nodes,
+  },
+  /// Plain Rust, run in place
+  Block(Block),
+}
+
+struct Arm {
+  pat: Pat,
+  guard: Option<Expr>,
+  body: Nodes,
+}
+
+/// `Widget(args.., attr=value, attr={signal}) { children }`
+struct Element {
+  path: Path,
+  args: Vec<Expr>,
+  attrs: Vec<Attr>,
+  children: Option<Nodes>,
+}
+
+struct Attr {
+  name: Ident,
+  value: AttrValue,
+}
+
+enum AttrValue {
+  Value(Expr),
+  Signal(Expr),
+}
+
+// `{ expr }`, used wherever a signal goes
+fn braced_expr(input: ParseStream) -> Result<Expr> {
+  let content;
+  braced!(content in input);
+  let expr = content.parse()?;
+  if !content.is_empty() {
+    return Err(content.error("expected a single expression"));
+  }
+  Ok(expr)
+}
+
+fn braced_nodes(input: ParseStream) -> Result<Nodes> {
+  let content;
+  braced!(content in input);
+  content.parse()
+}
+
+impl Parse for ViewInput {
+  fn parse(input: ParseStream) -> Result<Self> {
+    let mut krate = TokenStream::new();
+    while !input.peek(Token![;]) {
+      if input.is_empty() {
+        return Err(input.error("expected the crate path followed by `;`"));
+      }
+      krate.extend(Some(input.parse::<TokenTree>()?));
+    }
+    input.parse::<Token![;]>()?;
+    Ok(ViewInput {
+      krate,
+      nodes: input.parse()?,
+    })
+  }
+}
+
+impl Parse for Nodes {
+  fn parse(input: ParseStream) -> Result<Self> {
+    let mut nodes = vec![];
+    while !input.is_empty() {
+      nodes.push(input.parse()?);
+      while input.peek(Token![;]) || input.peek(Token![,]) {
+        if input.peek(Token![;]) {
+          input.parse::<Token![;]>()?;
+        } else {
+          input.parse::<Token![,]>()?;
+        }
+      }
+    }
+    Ok(Nodes(nodes))
+  }
+}
+
+impl Parse for Node {
+  fn parse(input: ParseStream) -> Result<Self> {
+    if input.peek(Token![if]) {
+      input.parse::<Token![if]>()?;
+      let cond = braced_expr(input)?;
+      let then = braced_nodes(input)?;
+      let otherwise = if input.peek(Token![else]) {
+        input.parse::<Token![else]>()?;
+        Some(braced_nodes(input)?)
+      } else {
+        None
+      };
+      Ok(Node::If {
+        cond,
+        then,
+        otherwise,
+      })
+    } else if input.peek(Token![match]) {
+      input.parse::<Token![match]>()?;
+      let signal = braced_expr(input)?;
+      let content;
+      braced!(content in input);
+      let mut arms = vec![];
+      while !content.is_empty() {
+        arms.push(content.parse()?);
+      }
+      Ok(Node::Match { signal, arms })
+    } else if input.peek(Token![for]) {
+      input.parse::<Token![for]>()?;
+      let pat = input.parse()?;
+      input.parse::<Token![in]>()?;
+      let signal_vec = braced_expr(input)?;
+      let body = braced_nodes(input)?;
+      Ok(Node::For {
+        pat,
+        signal_vec,
+        body,
+      })
+    } else if input.peek(token::Brace) {
+      Ok(Node::Block(input.parse()?))
+    } else {
+      Ok(Node::Element(input.parse()?))
+    }
+  }
+}
+
+impl Parse for Arm {
+  fn parse(input: ParseStream) -> Result<Self> {
+    let pat = input.parse()?;
+    let guard = if input.peek(Token![if]) {
+      input.parse::<Token![if]>()?;
+      Some(input.parse()?)
+    } else {
+      None
+    };
+    input.parse::<Token![=>]>()?;
+    let body = if input.peek(token::Brace) {
+      braced_nodes(input)?
+    } else {
+      Nodes(vec![input.parse()?])
+    };
+    if input.peek(Token![,]) {
+      input.parse::<Token![,]>()?;
+    }
+    Ok(Arm { pat, guard, body })
+  }
+}
+
+// Like a mod style path, but the last segment may have a turbofish, e.g. `RadioGroup::<i32>`
+fn parse_widget_path(input: ParseStream) -> Result<Path> {
+  let mut path = Path {
+    leading_colon: input.parse()?,
+    segments: Punctuated::new(),
+  };
+  loop {
+    let ident: Ident = input.parse()?;
+    path.segments.push_value(PathSegment::from(ident));
+    if input.peek(Token![::]) && input.peek3(Token![<]) {
+      let generics: AngleBracketedGenericArguments = input.parse()?;
+      path.segments.last_mut().unwrap().arguments = PathArguments::AngleBracketed(generics);
+      return Ok(path);
+    }
+    if !(input.peek(Token![::]) && input.peek3(Ident)) {
+      return Ok(path);
+    }
+    path.segments.push_punct(input.parse()?);
+  }
+}
+
+impl Parse for Element {
+  fn parse(input: ParseStream) -> Result<Self> {
+    let path = parse_widget_path(input)?;
+    let mut args = vec![];
+    let mut attrs = vec![];
+    if input.peek(token::Paren) {
+      let content;
+      parenthesized!(content in input);
+      while !content.is_empty() {
+        // `name = ..` is an attribute, anything else goes to the constructor
+        if content.peek(Ident) && content.peek2(Token![=]) && !content.peek2(Token![==]) {
+          let name = content.parse()?;
+          content.parse::<Token![=]>()?;
+          let value = if content.peek(token::Brace) {
+            AttrValue::Signal(braced_expr(&content)?)
+          } else {
+            AttrValue::Value(content.parse()?)
+          };
+          attrs.push(Attr { name, value });
+        } else {
+          args.push(content.parse()?);
+        }
+        if content.is_empty() {
+          break;
+        }
+        content.parse::<Token![,]>()?;
+      }
+    }
+    let children = if input.peek(token::Brace) {
+      Some(braced_nodes(input)?)
+    } else {
+      None
+    };
+    Ok(Element {
+      path,
+      args,
+      attrs,
+      children,
+    })
+  }
+}
+
+impl Nodes {
+  /// `krate` is the path the helpers are under, usually `$crate` from the wrapping macro
+  pub fn expand(&self, krate: &TokenStream) -> TokenStream {
+    self.0.iter().map(|node| node.expand(krate)).collect()
+  }
+}
+
+impl Node {
+  fn expand(&self, krate: &TokenStream) -> TokenStream {
+    match self {
+      Node::Element(element) => {
+        let element = element.expand(krate);
+        quote! { #element; }
+      }
+      Node::If {
+        cond,
+        then,
+        otherwise,
+      } => {
+        let then = then.expand(krate);
+        let otherwise = otherwise.iter().map(|nodes| nodes.expand(krate));
+        quote! {
+          #krate::helpers::if_signal(#cond, move |__wired_cond: bool| {
+            if __wired_cond { #then } #(else { #otherwise })*
+          });
+        }
+      }
+      Node::Match { signal, arms } => {
+        let pats = arms.iter().map(|arm| &arm.pat);
+        let guards = arms.iter().map(|arm| {
+          arm.guard.as_ref().map(|guard| quote! { if #guard })
+        });
+        let bodies = arms.iter().map(|arm| arm.body.expand(krate));
+        quote! {
+          #krate::helpers::match_signal(#signal, move |__wired_value| {
+            match __wired_value {
+              #(#pats #guards => { #bodies })*
+            }
+          });
+        }
+      }
+      Node::For {
+        pat,
+        signal_vec,
+        body,
+      } => {
+        let body = body.expand(krate);
+        quote! {
+          #krate::helpers::for_signal_vec(#signal_vec, move |#pat| { #body });
+        }
+      }
+      Node::Block(block) => quote! { #block },
+    }
+  }
+}
+
+impl Element {
+  fn expand(&self, krate: &TokenStream) -> TokenStream {
+    let path = &self.path;
+    let args = &self.args;
+    let mut expanded = quote! { #path::new(#(#args),*) };
+    for attr in self.attrs.iter() {
+      expanded = match &attr.value {
+        AttrValue::Value(value) => {
+          let name = &attr.name;
+          quote! { #expanded.#name(#value) }
+        }
+        AttrValue::Signal(signal) => {
+          let name = Ident::new(&format!("{}_signal", attr.name), attr.name.span());
+          quote! { #expanded.#name(#signal) }
+        }
+      };
+    }
+    if let Some(children) = &self.children {
+      let children = children.expand(krate);
+      expanded = quote! { #expanded.with(|| { #children }) };
+    }
+    expanded
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn expand(input: &str) -> String {
+    let input: ViewInput = syn::parse_str(input).expect("Couldn't parse view");
+    input.nodes.expand(&input.krate).to_string()
+  }
+
+  #[test]
+  fn expands_if() {
+    assert_eq!(
+      expand("$crate; if {shown.signal()} { Text(\"Yes\") } else { Text(\"No\") }"),
+      quote! {
+        $crate::helpers::if_signal(shown.signal(), move |__wired_cond: bool| {
+          if __wired_cond { Text::new("Yes"); } else { Text::new("No"); }
+        });
+      }
+      .to_string()
+    );
+  }
+
+  #[test]
+  fn expands_match() {
+    assert_eq!(
+      expand("crate; match {n.signal()} { 0 => Text(\"None\"), n if n > 9 => { Text(\"Lots\") } _ => {} }"),
+      quote! {
+        crate::helpers::match_signal(n.signal(), move |__wired_value| {
+          match __wired_value {
+            0 => { Text::new("None"); }
+            n if n > 9 => { Text::new("Lots"); }
+            _ => {}
+          }
+        });
+      }
+      .to_string()
+    );
+  }
+
+  #[test]
+  fn expands_for() {
+    assert_eq!(
+      expand("::app; for (i, item) in {items.signal_vec_cloned()} { Text(item, pad_top = {gap.signal()}) }"),
+      quote! {
+        ::app::helpers::for_signal_vec(items.signal_vec_cloned(), move |(i, item)| {
+          Text::new(item).pad_top_signal(gap.signal());
+        });
+      }
+      .to_string()
+    );
+  }
+
+  #[test]
+  fn expands_nested_elements() {
+    assert_eq!(
+      expand("crate; StackLayout(orientation = Orientation::Vertical) { if {a} { Text(\"a\") } }"),
+      quote! {
+        StackLayout::new().orientation(Orientation::Vertical).with(|| {
+          crate::helpers::if_signal(a, move |__wired_cond: bool| {
+            if __wired_cond { Text::new("a"); }
+          });
+        });
+      }
+      .to_string()
+    );
+  }
+
+  #[test]
+  fn needs_the_crate_path() {
+    assert!(syn::parse_str::<ViewInput>("Text(\"a\")").is_err());
+  }
+}